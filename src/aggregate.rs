@@ -0,0 +1,190 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+
+// --------------------------------------------------
+// the aggregates that --agg computes for each group of rows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Median,
+    Count,
+    NUnique,
+}
+
+const AGGREGATE_NAMES: [(&str, Aggregate); 7] = [
+    ("sum", Aggregate::Sum),
+    ("mean", Aggregate::Mean),
+    ("min", Aggregate::Min),
+    ("max", Aggregate::Max),
+    ("median", Aggregate::Median),
+    ("count", Aggregate::Count),
+    ("n_unique", Aggregate::NUnique),
+];
+
+// --------------------------------------------------
+// an aggregate of --agg and the column that it is computed over. count()
+// has no column, and counts the rows of each group
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateSpec {
+    aggregate: Aggregate,
+    column: Option<String>,
+}
+
+impl AggregateSpec {
+    // --------------------------------------------------
+    // the name of the column of the aggregate, e.g. sum(revenue), or count
+    // for count()
+    fn get_name(&self) -> String {
+        let name = AGGREGATE_NAMES
+            .iter()
+            .find(|(_, aggregate)| *aggregate == self.aggregate)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        match &self.column {
+            Some(column) => format!("{}({})", name, column),
+            None => name.to_string(),
+        }
+    }
+
+    // --------------------------------------------------
+    fn get_expr(&self) -> Expr {
+        let Some(column) = &self.column else {
+            return count().alias("count");
+        };
+
+        let column = col(column);
+        match self.aggregate {
+            Aggregate::Sum => column.sum(),
+            Aggregate::Mean => column.mean(),
+            Aggregate::Min => column.min(),
+            Aggregate::Max => column.max(),
+            Aggregate::Median => column.median(),
+            // the nulls are left out of the count of a column
+            Aggregate::Count => column.clone().count() - column.null_count(),
+            Aggregate::NUnique => column.n_unique(),
+        }
+        .alias(&self.get_name())
+    }
+}
+
+// --------------------------------------------------
+// split the value of --agg at the commas outside the parentheses
+fn split_aggregates(value: &str) -> Vec<&str> {
+    let mut specs = Vec::<&str>::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                specs.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    specs.push(&value[start..]);
+    specs
+}
+
+// --------------------------------------------------
+// parse the value of --agg, e.g. "sum(revenue),mean(latency),count()". the
+// aggregates are checked here, before any data is read
+pub fn parse_aggregates(value: &str) -> Result<Vec<AggregateSpec>, String> {
+    let names: Vec<&str> = AGGREGATE_NAMES.iter().map(|(name, _)| *name).collect();
+
+    let mut specs = Vec::<AggregateSpec>::new();
+    for spec in split_aggregates(value) {
+        let spec = spec.trim();
+        let Some((name, column)) = spec.strip_suffix(')').and_then(|spec| spec.split_once('('))
+        else {
+            return Err(format!(
+                "expected an aggregate such as sum(revenue) or count(), got \"{}\"",
+                spec
+            ));
+        };
+
+        let name = name.trim().to_lowercase();
+        let Some(&(_, aggregate)) = AGGREGATE_NAMES.iter().find(|(n, _)| *n == name) else {
+            return Err(format!(
+                "unknown aggregate {}, expected one of {}",
+                name,
+                names.join(", ")
+            ));
+        };
+        let column = Some(column.trim().to_string()).filter(|column| !column.is_empty());
+        if column.is_none() && aggregate != Aggregate::Count {
+            return Err(format!("{} needs a column, e.g. {}(revenue)", name, name));
+        }
+        specs.push(AggregateSpec { aggregate, column });
+    }
+
+    Ok(specs)
+}
+
+// --------------------------------------------------
+// check that the columns grouped by and aggregated are among the columns
+// of the table
+pub fn check_aggregate_columns(
+    group_by: &[String],
+    aggregates: &[AggregateSpec],
+    columns: &[String],
+) {
+    let aggregated = aggregates.iter().filter_map(|spec| spec.column.as_ref());
+    for (column, option) in group_by
+        .iter()
+        .map(|column| (column, "--group-by"))
+        .chain(aggregated.map(|column| (column, "--agg")))
+    {
+        if !columns.contains(column) {
+            exit_with_error(&format!(
+                "No column {} of {}; the columns are {}",
+                column,
+                option,
+                columns.join(", ")
+            ));
+        }
+    }
+}
+
+// --------------------------------------------------
+// aggregate the groups of rows that share the values of the group_by
+// columns, from the largest first aggregate, with ties in the order of the
+// groups
+pub fn aggregate_table(
+    df: DataFrame,
+    group_by: &[String],
+    aggregates: &[AggregateSpec],
+) -> DataFrame {
+    let mut keys = Vec::<Expr>::new();
+    let mut descending = Vec::<bool>::new();
+    if let Some(spec) = aggregates.first() {
+        keys.push(col(&spec.get_name()));
+        descending.push(true);
+    }
+    for column in group_by {
+        keys.push(col(column));
+        descending.push(false);
+    }
+
+    df.lazy()
+        .group_by(
+            group_by
+                .iter()
+                .map(|column| col(column))
+                .collect::<Vec<Expr>>(),
+        )
+        .agg(
+            aggregates
+                .iter()
+                .map(AggregateSpec::get_expr)
+                .collect::<Vec<Expr>>(),
+        )
+        .sort_by_exprs(keys, descending, true, true)
+        .collect()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to aggregate the groups: {}", e)))
+}
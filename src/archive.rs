@@ -0,0 +1,129 @@
+use crate::compression::{
+    decompress, get_compression_from_filename, strip_compression_extension, Compression,
+};
+use crate::exit_with_error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tar::Archive as TarArchive;
+use zip::ZipArchive;
+
+// separates the path of an archive from the member to read
+const MEMBER_SEPARATOR: &str = "::";
+
+// --------------------------------------------------
+fn is_zip(filepath: &str) -> bool {
+    has_extension(filepath, "zip")
+}
+
+// --------------------------------------------------
+fn has_extension(filepath: &str, extension: &str) -> bool {
+    Path::new(filepath)
+        .extension()
+        .is_some_and(|file_extension| file_extension.eq_ignore_ascii_case(extension))
+}
+
+// --------------------------------------------------
+// tarballs are either plain (.tar), compressed (.tar.gz, .tar.zst, ...),
+// or gzipped with the short .tgz extension
+fn is_tar(filepath: &str) -> bool {
+    has_extension(filepath, "tgz") || has_extension(strip_compression_extension(filepath), "tar")
+}
+
+// --------------------------------------------------
+// split `archive::inner/file.csv` into the archive and the member, which is
+// None for a plain archive path
+fn split_archive_path(
+    filepath: &str,
+    is_archive: impl Fn(&str) -> bool,
+) -> Option<(&str, Option<&str>)> {
+    if let Some((archive, member)) = filepath.split_once(MEMBER_SEPARATOR) {
+        if is_archive(archive) {
+            return Some((archive, Some(member)));
+        }
+    }
+
+    is_archive(filepath).then_some((filepath, None))
+}
+
+// --------------------------------------------------
+pub fn split_zip_path(filepath: &str) -> Option<(&str, Option<&str>)> {
+    split_archive_path(filepath, is_zip)
+}
+
+// --------------------------------------------------
+pub fn split_tar_path(filepath: &str) -> Option<(&str, Option<&str>)> {
+    split_archive_path(filepath, is_tar)
+}
+
+// --------------------------------------------------
+pub fn open_zip_archive(archive: &str) -> ZipArchive<File> {
+    let file = File::open(archive)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to open {}: {}", archive, e)));
+
+    ZipArchive::new(file).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to read the archive {}: {}", archive, e))
+    })
+}
+
+// --------------------------------------------------
+// choose the member to read: the one given, otherwise the only member that
+// is a table. when there is no single choice, list the members and exit
+pub fn get_zip_member(
+    zip: &ZipArchive<File>,
+    archive: &str,
+    member: Option<&str>,
+    is_table: impl Fn(&str) -> bool,
+) -> String {
+    if let Some(member) = member {
+        return member.to_string();
+    }
+
+    let mut members: Vec<&str> = zip
+        .file_names()
+        .filter(|name| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+        .collect();
+    members.sort();
+
+    let tables: Vec<&str> = members
+        .iter()
+        .copied()
+        .filter(|name| is_table(name))
+        .collect();
+    match (tables.as_slice(), members.as_slice()) {
+        ([table], _) => return table.to_string(),
+        (_, [member]) => return member.to_string(),
+        _ => {}
+    }
+
+    for member in &members {
+        println!("{}", member);
+    }
+    exit_with_error(&format!(
+        "{} has {} members; choose one with {}{}MEMBER",
+        archive,
+        members.len(),
+        archive,
+        MEMBER_SEPARATOR
+    ))
+}
+
+// --------------------------------------------------
+// open a tar archive, decompressing it only as its members are read
+pub fn open_tar_archive(archive: &str) -> TarArchive<Box<dyn Read>> {
+    let file = File::open(archive)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to open {}: {}", archive, e)));
+
+    let compression = match has_extension(archive, "tgz") {
+        true => Some(Compression::Gzip),
+        false => get_compression_from_filename(archive),
+    };
+    let reader = match compression {
+        Some(compression) => decompress(file, &compression).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to decompress {}: {}", archive, e))
+        }),
+        None => Box::new(file),
+    };
+
+    TarArchive::new(reader)
+}
@@ -0,0 +1,187 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// --------------------------------------------------
+// settings of the on-disk cache of downloaded urls
+pub struct Cache {
+    directory: PathBuf,
+    ttl: Duration,
+    pub refresh: bool,
+}
+
+// --------------------------------------------------
+// a cached response. its headers precede the body in a single file, so an
+// entry is replaced by a single rename, and its modification time is when
+// it was last fetched or revalidated
+pub struct CacheEntry {
+    path: PathBuf,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    fetched: SystemTime,
+}
+
+// --------------------------------------------------
+// the headers of a response that are kept with its body
+pub struct CachedHeaders<'a> {
+    pub etag: Option<&'a str>,
+    pub last_modified: Option<&'a str>,
+    pub content_type: Option<&'a str>,
+}
+
+// --------------------------------------------------
+// $XDG_CACHE_HOME/printbl, falling back to ~/.cache/printbl
+fn get_cache_directory() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|directory| directory.join("printbl"))
+}
+
+// --------------------------------------------------
+// name a cache entry by the fnv-1a hash of its url, which unlike the hasher
+// of the standard library is stable across rust releases
+fn hash_url(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+// --------------------------------------------------
+// read the headers of a cache entry, up to the empty line before the body
+fn read_headers(reader: &mut impl BufRead) -> std::io::Result<Vec<(String, String)>> {
+    let mut headers = Vec::<(String, String)>::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            return Ok(headers);
+        }
+        if let Some((name, value)) = line.trim_end().split_once(": ") {
+            headers.push((name.to_string(), value.to_string()));
+        }
+    }
+}
+
+impl Cache {
+    // --------------------------------------------------
+    pub fn new(ttl: Duration, refresh: bool) -> Option<Cache> {
+        get_cache_directory().map(|directory| Cache {
+            directory,
+            ttl,
+            refresh,
+        })
+    }
+
+    // --------------------------------------------------
+    fn get_entry_path(&self, url: &str) -> PathBuf {
+        self.directory.join(hash_url(url))
+    }
+
+    // --------------------------------------------------
+    // look up the cached response of a url
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        let path = self.get_entry_path(url);
+        let file = File::open(&path).ok()?;
+        let fetched = file.metadata().and_then(|m| m.modified()).ok()?;
+        let headers = read_headers(&mut BufReader::new(file)).ok()?;
+        let get_header = |name: &str| {
+            headers
+                .iter()
+                .find(|(header, _)| header == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        // entries whose hashes collide belong to another url
+        if get_header("url").as_deref() != Some(url) {
+            return None;
+        }
+
+        Some(CacheEntry {
+            path,
+            etag: get_header("etag"),
+            last_modified: get_header("last-modified"),
+            content_type: get_header("content-type"),
+            fetched,
+        })
+    }
+
+    // --------------------------------------------------
+    // check whether an entry was fetched or revalidated within the ttl
+    pub fn is_fresh(&self, entry: &CacheEntry) -> bool {
+        !self.refresh
+            && entry
+                .fetched
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed < self.ttl)
+    }
+
+    // --------------------------------------------------
+    // make sure that the cache directory exists
+    pub fn create_directory(&self) -> std::io::Result<()> {
+        fs::create_dir_all(&self.directory)
+    }
+
+    // --------------------------------------------------
+    // store a response body under its url. it is written to a temporary file
+    // that is renamed once complete, so an interrupted download leaves the
+    // previous entry in place
+    pub fn store(
+        &self,
+        url: &str,
+        headers: &CachedHeaders,
+        body: &mut impl Read,
+    ) -> std::io::Result<CacheEntry> {
+        let path = self.get_entry_path(url);
+        let temporary_path = path.with_extension(format!("tmp-{}", std::process::id()));
+
+        let mut write = || -> std::io::Result<()> {
+            let mut file = File::create(&temporary_path)?;
+            writeln!(file, "url: {}", url)?;
+            for (name, value) in [
+                ("etag", headers.etag),
+                ("last-modified", headers.last_modified),
+                ("content-type", headers.content_type),
+            ] {
+                if let Some(value) = value.filter(|value| !value.contains(['\r', '\n'])) {
+                    writeln!(file, "{}: {}", name, value)?;
+                }
+            }
+            writeln!(file)?;
+            std::io::copy(body, &mut file)?;
+            file.sync_all()?;
+            fs::rename(&temporary_path, &path)
+        };
+
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(e);
+        }
+
+        self.get(url)
+            .ok_or_else(|| std::io::Error::other("the cache entry could not be read back"))
+    }
+}
+
+impl CacheEntry {
+    // --------------------------------------------------
+    // mark an entry as revalidated
+    pub fn touch(&self) {
+        let _ = File::options()
+            .append(true)
+            .open(&self.path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+    }
+
+    // --------------------------------------------------
+    // read the body of an entry
+    pub fn open(&self) -> std::io::Result<Box<dyn Read>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        read_headers(&mut reader)?;
+        Ok(Box::new(reader))
+    }
+}
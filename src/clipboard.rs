@@ -0,0 +1,136 @@
+// the filepath that stands for the system clipboard
+pub const CLIPBOARD_PATH: &str = "@clipboard";
+
+// --------------------------------------------------
+pub fn is_clipboard(filepath: &str) -> bool {
+    filepath == CLIPBOARD_PATH
+}
+
+// --------------------------------------------------
+// read the text on the clipboard, which spreadsheets fill with
+// tab-separated cells
+#[cfg(feature = "clipboard")]
+pub fn read_clipboard() -> String {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!("Unable to read text from the clipboard: {}", e))
+        });
+
+    if text.trim().is_empty() {
+        crate::exit_with_error("The clipboard is empty");
+    }
+    text
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn read_clipboard() -> String {
+    crate::exit_with_error(
+        "Unable to read the clipboard: printbl was built without the `clipboard` feature",
+    )
+}
+
+// --------------------------------------------------
+// the thread copying what is printed, with the stdout it prints to
+#[cfg(all(unix, feature = "clipboard"))]
+static COPY: std::sync::Mutex<Option<(std::thread::JoinHandle<Vec<u8>>, i32)>> =
+    std::sync::Mutex::new(None);
+
+// --------------------------------------------------
+// redirect stdout into a pipe, so that what is printed is both passed
+// through to stdout and kept to be copied to the clipboard
+#[cfg(all(unix, feature = "clipboard"))]
+pub fn start_copy() {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::fd::FromRawFd;
+
+    let copy_error = || -> ! {
+        crate::exit_with_error(&format!(
+            "Unable to copy the output: {}",
+            std::io::Error::last_os_error()
+        ))
+    };
+
+    let _ = std::io::stdout().flush();
+    let mut fds = [0; 2];
+    // SAFETY: the file descriptors are only used once they are created, and
+    // stdout is flushed before it is redirected
+    let (stdout, output) = unsafe {
+        if libc::pipe(fds.as_mut_ptr()) == -1 {
+            copy_error();
+        }
+        let stdout = libc::dup(libc::STDOUT_FILENO);
+        let output = libc::dup(stdout);
+        if stdout == -1 || output == -1 || libc::dup2(fds[1], libc::STDOUT_FILENO) == -1 {
+            copy_error();
+        }
+        libc::close(fds[1]);
+        (stdout, output)
+    };
+
+    // SAFETY: the read end of the pipe and the copy of stdout are owned by
+    // the thread alone
+    let (mut pipe, mut output) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(output)) };
+    let thread = std::thread::spawn(move || {
+        let mut copied = Vec::<u8>::new();
+        let mut buffer = [0; 8192];
+        while let Ok(n @ 1..) = pipe.read(&mut buffer) {
+            let _ = output.write_all(&buffer[..n]);
+            copied.extend_from_slice(&buffer[..n]);
+        }
+        copied
+    });
+    *COPY.lock().expect("the copy is not poisoned") = Some((thread, stdout));
+}
+
+// --------------------------------------------------
+// restore stdout, and put what was printed on the clipboard without its
+// colors
+#[cfg(all(unix, feature = "clipboard"))]
+pub fn finish_copy() {
+    use std::io::Write;
+
+    let Some((thread, stdout)) = COPY.lock().expect("the copy is not poisoned").take() else {
+        return;
+    };
+
+    let _ = std::io::stdout().flush();
+    // SAFETY: stdout is flushed, and restoring it closes the write end of
+    // the pipe, which ends the thread
+    unsafe {
+        libc::dup2(stdout, libc::STDOUT_FILENO);
+        libc::close(stdout);
+    }
+    let copied = thread.join().unwrap_or_default();
+
+    let colors = regex::Regex::new("\x1b\\[[0-9;]*m").expect("the pattern is valid");
+    let text = colors
+        .replace_all(&String::from_utf8_lossy(&copied), "")
+        .into_owned();
+    let n_bytes = text.len();
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!(
+                "Unable to copy the output to the clipboard: {}",
+                e
+            ))
+        });
+    eprintln!("Copied {} bytes to the clipboard", n_bytes);
+}
+
+#[cfg(not(all(unix, feature = "clipboard")))]
+pub fn start_copy() {
+    match cfg!(feature = "clipboard") {
+        true => {
+            crate::exit_with_error("Unable to copy the output: --copy is only supported on Unix")
+        }
+        false => crate::exit_with_error(
+            "Unable to copy the output: printbl was built without the `clipboard` feature",
+        ),
+    }
+}
+
+#[cfg(not(all(unix, feature = "clipboard")))]
+pub fn finish_copy() {}
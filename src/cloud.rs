@@ -0,0 +1,123 @@
+use polars::prelude::*;
+
+// --------------------------------------------------
+pub fn is_cloud_url(filepath: &str) -> bool {
+    filepath.starts_with("s3://") || filepath.starts_with("s3a://")
+}
+
+// --------------------------------------------------
+// read a section of an ini-style aws configuration file
+#[cfg(feature = "cloud")]
+fn read_aws_config_section(path: &std::path::Path, section: &str) -> Vec<(String, String)> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let mut in_section = false;
+    let mut values = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = line[1..line.len() - 1].trim() == section;
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                values.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+
+    values
+}
+
+// --------------------------------------------------
+// get credentials from the standard aws env vars, falling back to the
+// profile in ~/.aws/credentials and ~/.aws/config
+#[cfg(feature = "cloud")]
+fn get_cloud_options() -> cloud::CloudOptions {
+    use cloud::AmazonS3ConfigKey;
+    use std::env;
+    use std::path::PathBuf;
+
+    // the env vars are picked up by polars itself
+    if env::var_os("AWS_ACCESS_KEY_ID").is_some() {
+        return cloud::CloudOptions::default();
+    }
+
+    let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) else {
+        return cloud::CloudOptions::default();
+    };
+    let profile = env::var("AWS_PROFILE").unwrap_or_else(|_| String::from("default"));
+
+    let credentials_path = env::var_os("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(&home).join(".aws").join("credentials"));
+    let config_path = env::var_os("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(&home).join(".aws").join("config"));
+    let config_section = if profile == "default" {
+        profile.clone()
+    } else {
+        format!("profile {}", profile)
+    };
+
+    let mut configs = Vec::<(AmazonS3ConfigKey, String)>::new();
+    for (key, value) in read_aws_config_section(&credentials_path, &profile) {
+        match key.as_str() {
+            "aws_access_key_id" => configs.push((AmazonS3ConfigKey::AccessKeyId, value)),
+            "aws_secret_access_key" => configs.push((AmazonS3ConfigKey::SecretAccessKey, value)),
+            "aws_session_token" => configs.push((AmazonS3ConfigKey::Token, value)),
+            _ => {}
+        }
+    }
+    if env::var_os("AWS_REGION").is_none() && env::var_os("AWS_DEFAULT_REGION").is_none() {
+        for (key, value) in read_aws_config_section(&config_path, &config_section) {
+            if key == "region" {
+                configs.push((AmazonS3ConfigKey::Region, value));
+            }
+        }
+    }
+
+    cloud::CloudOptions::default().with_aws(configs)
+}
+
+// --------------------------------------------------
+// scan a parquet object lazily, so that only the footer and the row
+// groups needed for the selected columns and rows are fetched
+#[cfg(feature = "cloud")]
+pub fn scan_cloud_parquet(url: &str) -> LazyFrame {
+    let args = ScanArgsParquet {
+        cloud_options: Some(get_cloud_options()),
+        ..Default::default()
+    };
+
+    LazyFrame::scan_parquet(url, args)
+        .unwrap_or_else(|e| crate::exit_with_error(&format!("Unable to read {}: {}", url, e)))
+}
+
+#[cfg(not(feature = "cloud"))]
+pub fn scan_cloud_parquet(url: &str) -> LazyFrame {
+    crate::exit_with_error(&format!(
+        "Unable to read {}: printbl was built without the `cloud` feature",
+        url
+    ))
+}
+
+// --------------------------------------------------
+// parse a parquet object from cloud storage
+pub fn parse_cloud_parquet(
+    url: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    crate::dataset::collect_scan(scan_cloud_parquet(url), url, select_columns, n_rows)
+}
+
+// --------------------------------------------------
+// get column names from the parquet footer without reading any data
+pub fn get_cloud_parquet_column_names(url: &str) -> Vec<String> {
+    scan_cloud_parquet(url)
+        .schema()
+        .unwrap_or_else(|e| crate::exit_with_error(&format!("Unable to read {}: {}", url, e)))
+        .iter_names()
+        .map(|name| name.to_string())
+        .collect()
+}
@@ -0,0 +1,206 @@
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use xz2::read::XzDecoder;
+
+// --------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const BZIP2_MAGIC: [u8; 3] = [b'B', b'Z', b'h'];
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+// --------------------------------------------------
+// get compression from the last extension of the filepath, ignoring case
+pub fn get_compression_from_filename(filename: &str) -> Option<Compression> {
+    let file_extension = Path::new(filename)
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    match file_extension.as_deref() {
+        Some("gz") => Some(Compression::Gzip),
+        Some("zst") => Some(Compression::Zstd),
+        Some("bz2") => Some(Compression::Bzip2),
+        Some("xz") => Some(Compression::Xz),
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// remove the compression extension so the inner format can be detected
+pub fn strip_compression_extension(filename: &str) -> &str {
+    match get_compression_from_filename(filename) {
+        Some(_) => filename.rsplit_once('.').map_or(filename, |(stem, _)| stem),
+        None => filename,
+    }
+}
+
+// --------------------------------------------------
+// detect compression from the leading bytes of a stream
+pub fn get_compression_from_magic(bytes: &[u8]) -> Option<Compression> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        return Some(Compression::Gzip);
+    }
+
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        return Some(Compression::Zstd);
+    }
+
+    if bytes.starts_with(&BZIP2_MAGIC) {
+        return Some(Compression::Bzip2);
+    }
+
+    if bytes.starts_with(&XZ_MAGIC) {
+        return Some(Compression::Xz);
+    }
+
+    None
+}
+
+// --------------------------------------------------
+// wrap a reader in a streaming decoder
+pub fn decompress<'a>(
+    reader: impl Read + 'a,
+    compression: &Compression,
+) -> std::io::Result<Box<dyn Read + 'a>> {
+    let decoder: Box<dyn Read + 'a> = match compression {
+        Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        Compression::Bzip2 => Box::new(MultiBzDecoder::new(reader)),
+        Compression::Xz => Box::new(XzDecoder::new_multi_decoder(reader)),
+    };
+
+    Ok(decoder)
+}
+
+// --------------------------------------------------
+// read whole csv records until n_records are collected, so that the
+// rest of the stream never has to be decompressed. lines starting with
+// the comment character are kept but not counted
+pub fn read_records(
+    reader: impl Read,
+    n_records: Option<usize>,
+    comment_char: Option<u8>,
+) -> std::io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::<u8>::new();
+
+    let Some(n_records) = n_records else {
+        reader.read_to_end(&mut buffer)?;
+        return Ok(buffer);
+    };
+
+    let mut n_read = 0;
+    let mut in_quotes = false;
+    while n_read < n_records {
+        let start = buffer.len();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+
+        if !in_quotes && comment_char.is_some_and(|c| buffer.get(start) == Some(&c)) {
+            continue;
+        }
+
+        // a newline inside a quoted field does not end the record
+        let n_quotes = buffer[start..].iter().filter(|&&b| b == b'"').count();
+        if n_quotes % 2 == 1 {
+            in_quotes = !in_quotes;
+        }
+        if !in_quotes {
+            n_read += 1;
+        }
+    }
+
+    Ok(buffer)
+}
+
+// --------------------------------------------------
+// read the next n_lines lines as they are, e.g. the lines skipped before a
+// header
+pub fn read_lines(reader: &mut impl BufRead, n_lines: usize) -> std::io::Result<Vec<u8>> {
+    let mut buffer = Vec::<u8>::new();
+    for _ in 0..n_lines {
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+    }
+    Ok(buffer)
+}
+
+// --------------------------------------------------
+// the 1-based numbers of the lines that the first n_records csv records
+// (header included) start on. empty lines and the lines starting with the
+// comment character are skipped, as the csv reader skips them
+pub fn get_record_lines(
+    reader: impl Read,
+    n_records: usize,
+    comment_char: Option<u8>,
+) -> std::io::Result<Vec<usize>> {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::<u8>::new();
+    let mut record_lines = Vec::<usize>::new();
+
+    let mut line_number = 0;
+    let mut in_quotes = false;
+    while record_lines.len() < n_records {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        if !in_quotes {
+            let is_empty = matches!(line.as_slice(), b"\n" | b"\r\n");
+            if is_empty || comment_char.is_some_and(|c| line.first() == Some(&c)) {
+                continue;
+            }
+            record_lines.push(line_number);
+        }
+
+        // a newline inside a quoted field does not end the record
+        if line.iter().filter(|&&b| b == b'"').count() % 2 == 1 {
+            in_quotes = !in_quotes;
+        }
+    }
+
+    Ok(record_lines)
+}
+
+// --------------------------------------------------
+// read the first n_records lines of ndjson. content whose first line is not
+// a whole object is a single pretty-printed json document, so it is read in
+// full instead
+pub fn read_ndjson_records(reader: impl Read, n_records: usize) -> std::io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::<u8>::new();
+
+    let mut n_read = 0;
+    while n_read < n_records {
+        let start = buffer.len();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+
+        let line = buffer[start..].trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+        if n_read == 0 && !line.ends_with(b"}") {
+            reader.read_to_end(&mut buffer)?;
+            break;
+        }
+        n_read += 1;
+    }
+
+    Ok(buffer)
+}
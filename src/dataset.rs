@@ -0,0 +1,130 @@
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+// --------------------------------------------------
+// list the files below a directory, sorted so that the order is stable
+fn get_directory_files(directory: &str, extension: Option<&str>) -> Vec<PathBuf> {
+    let pattern = Path::new(&glob::Pattern::escape(directory))
+        .join("**")
+        .join("*");
+    let mut files: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!(
+                "Unable to list the directory {}: {}",
+                directory, e
+            ))
+        })
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            extension.is_none_or(|extension| {
+                path.extension()
+                    .is_some_and(|file_extension| file_extension == extension)
+            })
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+// --------------------------------------------------
+// a directory holding parquet files is read as a (hive partitioned) dataset
+pub fn is_parquet_dataset(directory: &str) -> bool {
+    Path::new(directory).is_dir() && !get_directory_files(directory, Some("parquet")).is_empty()
+}
+
+// --------------------------------------------------
+// replace directories of other files by the files they contain, which are
+// then concatenated like any other list of inputs
+pub fn expand_directories(filepaths: Vec<String>) -> Vec<String> {
+    let mut expanded = Vec::<String>::new();
+    for filepath in filepaths {
+        if !Path::new(&filepath).is_dir() || is_parquet_dataset(&filepath) {
+            expanded.push(filepath);
+            continue;
+        }
+
+        let files = get_directory_files(&filepath, None);
+        if files.is_empty() {
+            crate::exit_with_error(&format!("no files found in directory {}", filepath));
+        }
+        expanded.extend(
+            files
+                .into_iter()
+                .map(|path| path.to_string_lossy().into_owned()),
+        );
+    }
+
+    expanded
+}
+
+// --------------------------------------------------
+// scan the parquet files of a directory lazily, adding the key=value
+// segments of their paths as partition columns
+fn scan_parquet_dataset(directory: &str) -> LazyFrame {
+    let args = ScanArgsParquet {
+        hive_partitioning: true,
+        ..Default::default()
+    };
+
+    let files = get_directory_files(directory, Some("parquet"));
+    LazyFrame::scan_parquet_files(files.into(), args).unwrap_or_else(|e| {
+        crate::exit_with_error(&format!("Unable to read the dataset {}: {}", directory, e))
+    })
+}
+
+// --------------------------------------------------
+// collect a lazy scan, pushing the column selection and row limit down so
+// that only the files and row groups needed are read
+pub fn collect_scan(
+    lf: LazyFrame,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let mut lf = lf;
+    if let Some(columns) = select_columns {
+        lf = lf.select(
+            columns
+                .iter()
+                .map(|column| col(column))
+                .collect::<Vec<Expr>>(),
+        );
+    }
+    if let Some(n) = n_rows {
+        lf = lf.limit(n as IdxSize);
+    }
+
+    lf.collect()
+        .unwrap_or_else(|e| crate::exit_with_error(&format!("Unable to read {}: {}", source, e)))
+}
+
+// --------------------------------------------------
+// parse a directory of parquet files
+pub fn parse_parquet_dataset(
+    directory: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    collect_scan(
+        scan_parquet_dataset(directory),
+        directory,
+        select_columns,
+        n_rows,
+    )
+}
+
+// --------------------------------------------------
+// get the column names of a dataset, including its partition columns,
+// without reading any data
+pub fn get_parquet_dataset_column_names(directory: &str) -> Vec<String> {
+    scan_parquet_dataset(directory)
+        .schema()
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!("Unable to read the dataset {}: {}", directory, e))
+        })
+        .iter_names()
+        .map(|name| name.to_string())
+        .collect()
+}
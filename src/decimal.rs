@@ -0,0 +1,66 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+
+// --------------------------------------------------
+// check whether a value is a number written with a decimal comma, and
+// optionally with dots between its groups of thousands, e.g. 1.234,56
+fn is_decimal_comma_number(value: &str) -> bool {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once(',').unwrap_or((unsigned, "0"));
+
+    let mut groups = integer.split('.');
+    let first_group = groups.next().unwrap_or_default();
+    let all_digits =
+        |digits: &str| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+
+    all_digits(first_group)
+        && groups.all(|group| group.len() == 3 && all_digits(group))
+        && all_digits(fraction)
+}
+
+// --------------------------------------------------
+fn parse_decimal_comma_number(value: &str) -> Option<f64> {
+    value.trim().replace('.', "").replace(',', ".").parse().ok()
+}
+
+// --------------------------------------------------
+// convert a string column to floats when every value is written with a
+// decimal comma, leaving any other column as it is
+fn parse_decimal_comma_column(series: &Series) -> Series {
+    let Ok(values) = series.utf8() else {
+        return series.clone();
+    };
+
+    if values.null_count() == values.len()
+        || !values
+            .into_iter()
+            .flatten()
+            .all(|value| is_decimal_comma_number(value.trim()))
+    {
+        return series.clone();
+    }
+
+    let column: Vec<Option<f64>> = values
+        .into_iter()
+        .map(|value| value.and_then(parse_decimal_comma_number))
+        .collect();
+    Series::new(series.name(), column)
+}
+
+// --------------------------------------------------
+// read the numbers of european exports (e.g. 3,14 or 1.234,56) that the
+// csv reader leaves as strings
+pub fn parse_decimal_commas(df: DataFrame, source: &str) -> DataFrame {
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(parse_decimal_comma_column)
+        .collect();
+
+    DataFrame::new(columns).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to parse the decimal commas of {}: {}",
+            source, e
+        ))
+    })
+}
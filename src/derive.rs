@@ -0,0 +1,310 @@
+use crate::exit_with_error;
+use crate::expression::{Arithmetic, Parser, Token};
+use polars::prelude::*;
+
+// --------------------------------------------------
+// a parsed --derive expression: columns and literals, combined by arithmetic
+// and by the functions of FUNCTIONS
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Column(String),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Negate(Box<Value>),
+    Apply(Box<Value>, Arithmetic, Box<Value>),
+    Call(String, Vec<Value>),
+}
+
+const FUNCTIONS: [&str; 6] = ["len", "lower", "upper", "trim", "substr", "abs"];
+
+// --------------------------------------------------
+// the grammar of --derive, parsed by recursive descent, where * / and % bind
+// tighter than + and -
+impl Parser<'_> {
+    // --------------------------------------------------
+    // the name of the derived column before the =
+    fn parse_name(&mut self) -> Result<String, String> {
+        let name = match self.peek() {
+            Some(Token::Word(name) | Token::Column(name)) => name.clone(),
+            _ => return Err(self.expected("the name of the new column")),
+        };
+        self.position += 1;
+        match self.next() {
+            Some(Token::Assign) => Ok(name),
+            _ => {
+                self.position -= 1;
+                Err(self.expected("="))
+            }
+        }
+    }
+
+    // --------------------------------------------------
+    fn parse_sum(&mut self) -> Result<Value, String> {
+        let mut value = self.parse_product()?;
+        while let Some(Token::Arithmetic(arithmetic @ (Arithmetic::Add | Arithmetic::Subtract))) =
+            self.peek()
+        {
+            let arithmetic = *arithmetic;
+            self.position += 1;
+            value = Value::Apply(Box::new(value), arithmetic, Box::new(self.parse_product()?));
+        }
+        Ok(value)
+    }
+
+    // --------------------------------------------------
+    fn parse_product(&mut self) -> Result<Value, String> {
+        let mut value = self.parse_negation()?;
+        while let Some(Token::Arithmetic(
+            arithmetic @ (Arithmetic::Multiply | Arithmetic::Divide | Arithmetic::Remainder),
+        )) = self.peek()
+        {
+            let arithmetic = *arithmetic;
+            self.position += 1;
+            value = Value::Apply(
+                Box::new(value),
+                arithmetic,
+                Box::new(self.parse_negation()?),
+            );
+        }
+        Ok(value)
+    }
+
+    // --------------------------------------------------
+    fn parse_negation(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(Token::Arithmetic(Arithmetic::Subtract)) => {
+                self.position += 1;
+                Ok(Value::Negate(Box::new(self.parse_negation()?)))
+            }
+            _ => self.parse_value(),
+        }
+    }
+
+    // --------------------------------------------------
+    // a column, a literal, a call of a function, or an expression in
+    // parentheses. a word followed by ( calls a function, and other words
+    // name columns
+    fn parse_value(&mut self) -> Result<Value, String> {
+        let value = match self.next() {
+            Some(Token::OpenParen) => {
+                let value = self.parse_sum()?;
+                match self.next() {
+                    Some(Token::CloseParen) => return Ok(value),
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected(")"));
+                    }
+                }
+            }
+            Some(Token::Column(name)) => Value::Column(name),
+            Some(Token::Text(text)) => Value::Text(text),
+            Some(Token::Number(number)) => match number.parse::<i64>() {
+                Ok(integer) => Value::Integer(integer),
+                Err(_) => Value::Float(number.parse::<f64>().map_err(|_| {
+                    self.position -= 1;
+                    format!("{} at {} is not a number", number, self.offset() + 1)
+                })?),
+            },
+            Some(Token::Word(word)) if self.peek() == Some(&Token::OpenParen) => {
+                self.position += 1;
+                let mut arguments = Vec::<Value>::new();
+                if self.peek() == Some(&Token::CloseParen) {
+                    self.position += 1;
+                    return Ok(Value::Call(word.to_lowercase(), arguments));
+                }
+                loop {
+                    arguments.push(self.parse_sum()?);
+                    match self.next() {
+                        Some(Token::Comma) => {}
+                        Some(Token::CloseParen) => break,
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.expected(", or )"));
+                        }
+                    }
+                }
+                Value::Call(word.to_lowercase(), arguments)
+            }
+            Some(Token::Word(word)) => Value::Column(word),
+            _ => {
+                self.position -= 1;
+                return Err(self.expected("a column, a value, or a function"));
+            }
+        };
+        Ok(value)
+    }
+}
+
+// --------------------------------------------------
+// parse a --derive expression into the name of the new column and its value
+fn parse_derivation(source: &str) -> Result<(String, Value), String> {
+    let mut parser = Parser::new(source)?;
+    let name = parser.parse_name()?;
+    let value = parser.parse_sum()?;
+    match parser.peek() {
+        None => Ok((name, value)),
+        Some(_) => Err(parser.expected("an operator or the end")),
+    }
+}
+
+// --------------------------------------------------
+// what a value holds, which decides what it can be combined with
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Number,
+    Text,
+    Other(DataType),
+}
+
+// --------------------------------------------------
+// a number without a fraction given to a function
+fn get_integer(value: &Value, function: &str) -> Result<i64, String> {
+    match value {
+        Value::Integer(integer) => Ok(*integer),
+        Value::Negate(value) => get_integer(value, function).map(|integer| -integer),
+        _ => Err(format!("{} takes whole numbers after the text", function)),
+    }
+}
+
+// --------------------------------------------------
+// a value as a polars expression, checked against the types of the columns
+// of the table, with what it holds, and described for the errors
+fn compile_value(value: &Value, schema: &Schema) -> Result<(Expr, Kind, String), String> {
+    let numbers = |value: &Value| -> Result<Expr, String> {
+        match compile_value(value, schema)? {
+            (expr, Kind::Number, _) => Ok(expr),
+            (_, _, name) => Err(format!("{} does not hold numbers", name)),
+        }
+    };
+    let text = |value: &Value| -> Result<Expr, String> {
+        match compile_value(value, schema)? {
+            (expr, Kind::Text, _) => Ok(expr),
+            (_, _, name) => Err(format!("{} does not hold text", name)),
+        }
+    };
+
+    Ok(match value {
+        Value::Column(name) => {
+            let dtype = schema
+                .get(name)
+                .ok_or_else(|| format!("there is no column {}", name))?;
+            let kind = match dtype {
+                dtype if dtype.is_numeric() => Kind::Number,
+                DataType::Utf8 => Kind::Text,
+                dtype => Kind::Other(dtype.clone()),
+            };
+            (col(name), kind, format!("the column {} ({})", name, dtype))
+        }
+        Value::Integer(integer) => (
+            lit(*integer),
+            Kind::Number,
+            format!("the number {}", integer),
+        ),
+        Value::Float(float) => (lit(*float), Kind::Number, format!("the number {}", float)),
+        Value::Text(text) => (
+            lit(text.as_str()),
+            Kind::Text,
+            format!("the text {:?}", text),
+        ),
+        Value::Negate(value) => (
+            numbers(value)? * lit(-1),
+            Kind::Number,
+            String::from("a negation"),
+        ),
+        // a division or remainder by zero is null, and a division is of
+        // floats, so that 1 / 2 is 0.5
+        Value::Apply(left, arithmetic, right) => {
+            let (left, right) = (numbers(left)?, numbers(right)?);
+            let is_zero = right.clone().eq(lit(0));
+            let expr = match arithmetic {
+                Arithmetic::Add => left + right,
+                Arithmetic::Subtract => left - right,
+                Arithmetic::Multiply => left * right,
+                Arithmetic::Divide => when(is_zero)
+                    .then(lit(NULL))
+                    .otherwise(left.cast(DataType::Float64) / right.cast(DataType::Float64)),
+                // both sides of the when are computed, so the integers are
+                // not divided by zero itself
+                Arithmetic::Remainder => {
+                    let divisor = when(is_zero.clone()).then(lit(1)).otherwise(right);
+                    when(is_zero).then(lit(NULL)).otherwise(left % divisor)
+                }
+            };
+            (expr, Kind::Number, String::from("an arithmetic"))
+        }
+        Value::Call(function, arguments) => {
+            let expr = match (function.as_str(), arguments.as_slice()) {
+                ("len", [value]) => text(value)?.str().len_chars(),
+                ("lower", [value]) => text(value)?.str().to_lowercase(),
+                ("upper", [value]) => text(value)?.str().to_uppercase(),
+                ("trim", [value]) => text(value)?.str().strip_chars(lit(NULL)),
+                ("substr", [value, start, rest @ ..]) if rest.len() <= 1 => {
+                    let start = get_integer(start, "substr")?;
+                    let length = match rest.first() {
+                        Some(length) => Some(get_integer(length, "substr")?),
+                        None => None,
+                    };
+                    if start < 1 || length.is_some_and(|length| length < 0) {
+                        return Err(String::from(
+                            "substr counts the characters from 1, e.g. substr(name, 1, 3)",
+                        ));
+                    }
+                    text(value)?
+                        .str()
+                        .slice(start - 1, length.map(|length| length as u64))
+                }
+                ("abs", [value]) => {
+                    let expr = numbers(value)?;
+                    when(expr.clone().lt(lit(0)))
+                        .then(expr.clone() * lit(-1))
+                        .otherwise(expr)
+                }
+                (function, _) if FUNCTIONS.contains(&function) => {
+                    return Err(format!(
+                        "{} takes {}",
+                        function,
+                        match function {
+                            "substr" => "text, the first character, and optionally a length",
+                            "abs" => "a number",
+                            _ => "text",
+                        }
+                    ))
+                }
+                (function, _) => {
+                    return Err(format!(
+                        "unknown function {}, expected one of {}",
+                        function,
+                        FUNCTIONS.join(", ")
+                    ))
+                }
+            };
+            let kind = match function.as_str() {
+                "len" | "abs" => Kind::Number,
+                _ => Kind::Text,
+            };
+            (expr, kind, format!("{}()", function))
+        }
+    })
+}
+
+// --------------------------------------------------
+// add the columns of the --derive expressions to a table, in order, so that
+// each can use the columns derived before it
+pub fn derive_columns(df: DataFrame, derivations: &[String]) -> DataFrame {
+    let mut df = df;
+    for derivation in derivations {
+        let (name, value) = parse_derivation(derivation).unwrap_or_else(|e| {
+            exit_with_error(&format!("Invalid --derive `{}`: {}", derivation, e))
+        });
+        let (expr, _, _) = compile_value(&value, &df.schema()).unwrap_or_else(|e| {
+            exit_with_error(&format!("Invalid --derive `{}`: {}", derivation, e))
+        });
+        df = df
+            .lazy()
+            .with_column(expr.alias(&name))
+            .collect()
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to derive {}: {}", name, e)));
+    }
+    df
+}
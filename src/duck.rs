@@ -0,0 +1,150 @@
+use polars::prelude::*;
+
+// --------------------------------------------------
+// quote an identifier for use in a duckdb query
+#[cfg(feature = "duckdb")]
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+// --------------------------------------------------
+#[cfg(feature = "duckdb")]
+fn open_database(filepath: &str) -> ::duckdb::Connection {
+    use ::duckdb::{AccessMode, Config, Connection};
+
+    Config::default()
+        .access_mode(AccessMode::ReadOnly)
+        .and_then(|config| Connection::open_with_flags(filepath, config))
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!("Unable to open the database {}: {}", filepath, e))
+        })
+}
+
+// --------------------------------------------------
+// get the names of the tables and views in the main schema of the database
+#[cfg(feature = "duckdb")]
+pub fn get_duckdb_table_names(filepath: &str) -> Vec<String> {
+    let connection = open_database(filepath);
+    let list_error = |e: ::duckdb::Error| -> ! {
+        crate::exit_with_error(&format!("Unable to list the tables in {}: {}", filepath, e))
+    };
+
+    let mut statement = connection
+        .prepare(
+            "SELECT table_name FROM information_schema.tables \
+             WHERE table_schema = 'main' ORDER BY table_name",
+        )
+        .unwrap_or_else(|e| list_error(e));
+
+    statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(Iterator::collect)
+        .unwrap_or_else(|e| list_error(e))
+}
+
+// --------------------------------------------------
+// parse a table (or view) of a duckdb database, or the result of a query.
+// the result arrives as arrow record batches, and the row limit becomes a
+// limit clause so that duckdb stops early
+#[cfg(feature = "duckdb")]
+pub fn parse_duckdb(
+    filepath: &str,
+    table: &str,
+    query: Option<&str>,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let connection = open_database(filepath);
+
+    let columns = match select_columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<String>>()
+            .join(", "),
+        None => String::from("*"),
+    };
+    let mut sql = match query {
+        Some(query) => {
+            let query = query.trim().trim_end_matches(';');
+            match columns == "*" && n_rows.is_none() {
+                true => query.to_string(),
+                false => format!("SELECT {} FROM ({}) AS query", columns, query),
+            }
+        }
+        None => format!("SELECT {} FROM {}", columns, quote_identifier(table)),
+    };
+    if let Some(n) = n_rows {
+        sql.push_str(&format!(" LIMIT {}", n));
+    }
+
+    let query_error = |e: ::duckdb::Error| -> ! {
+        match query {
+            Some(query) => crate::exit_with_error(&format!(
+                "Unable to run the query `{}` against the database {}: {}",
+                query, filepath, e
+            )),
+            None => crate::exit_with_error(&format!(
+                "Unable to query the table {} in the database {}: {}",
+                table, filepath, e
+            )),
+        }
+    };
+
+    let mut statement = connection.prepare(&sql).unwrap_or_else(|e| query_error(e));
+    let batches: Vec<DataFrame> = statement
+        .query_polars([])
+        .unwrap_or_else(|e| query_error(e))
+        .collect();
+
+    // an empty result has no batches, only column names
+    let Some((first, rest)) = batches.split_first() else {
+        let columns: Vec<Series> = statement
+            .column_names()
+            .iter()
+            .map(|name| Series::new_empty(name, &DataType::Utf8))
+            .collect();
+        return DataFrame::new(columns).unwrap_or_else(|e| {
+            crate::exit_with_error(&format!(
+                "Unable to parse the result of the query against {}: {}",
+                filepath, e
+            ))
+        });
+    };
+
+    let mut df = first.clone();
+    for batch in rest {
+        df.vstack_mut(batch).unwrap_or_else(|e| {
+            crate::exit_with_error(&format!(
+                "Unable to parse the result of the query against {}: {}",
+                filepath, e
+            ))
+        });
+    }
+    df.align_chunks();
+    df
+}
+
+#[cfg(not(feature = "duckdb"))]
+fn missing_feature(filepath: &str) -> ! {
+    crate::exit_with_error(&format!(
+        "Unable to read the DuckDB database {}: printbl was built without the `duckdb` feature",
+        filepath
+    ))
+}
+
+#[cfg(not(feature = "duckdb"))]
+pub fn get_duckdb_table_names(filepath: &str) -> Vec<String> {
+    missing_feature(filepath)
+}
+
+#[cfg(not(feature = "duckdb"))]
+pub fn parse_duckdb(
+    filepath: &str,
+    _table: &str,
+    _query: Option<&str>,
+    _select_columns: Option<Vec<String>>,
+    _n_rows: Option<usize>,
+) -> DataFrame {
+    missing_feature(filepath)
+}
@@ -0,0 +1,60 @@
+use std::io::{BufRead, BufReader, Cursor, Read};
+
+pub const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16BE_BOM: &[u8] = b"\xfe\xff";
+
+// --------------------------------------------------
+// drop the utf-8 byte order mark that spreadsheet exports often start with
+pub fn strip_utf8_bom(mut bytes: Vec<u8>) -> Vec<u8> {
+    if bytes.starts_with(UTF8_BOM) {
+        bytes.drain(..UTF8_BOM.len());
+    }
+    bytes
+}
+
+// --------------------------------------------------
+// check whether content starts with a utf-16 byte order mark
+pub fn is_utf16(prefix: &[u8]) -> bool {
+    prefix.starts_with(UTF16LE_BOM) || prefix.starts_with(UTF16BE_BOM)
+}
+
+// --------------------------------------------------
+// transcode utf-16 text (after its byte order mark) to utf-8, replacing
+// unpaired surrogates
+fn utf16_to_utf8(bytes: &[u8], little_endian: bool) -> Vec<u8> {
+    let units = bytes.chunks_exact(2).map(|pair| match little_endian {
+        true => u16::from_le_bytes([pair[0], pair[1]]),
+        false => u16::from_be_bytes([pair[0], pair[1]]),
+    });
+
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect::<String>()
+        .into_bytes()
+}
+
+// --------------------------------------------------
+// skip a utf-8 byte order mark, or transcode utf-16 text to utf-8. utf-16
+// text is read in full, as it cannot be cut short record by record
+pub fn decode_bom<'a>(reader: impl Read + 'a) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+    let prefix = reader.fill_buf()?;
+
+    if prefix.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+        return Ok(Box::new(reader));
+    }
+
+    if is_utf16(prefix) {
+        let little_endian = prefix.starts_with(UTF16LE_BOM);
+        let mut bytes = Vec::<u8>::new();
+        reader.read_to_end(&mut bytes)?;
+        return Ok(Box::new(Cursor::new(utf16_to_utf8(
+            &bytes[2..],
+            little_endian,
+        ))));
+    }
+
+    Ok(Box::new(reader))
+}
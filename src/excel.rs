@@ -0,0 +1,355 @@
+use crate::exit_with_error;
+use crate::output::format_column;
+use calamine::{
+    open_workbook_auto_from_rs, Data, DataType as CellType, Dimensions, Range, Reader,
+    SheetVisible, Sheets,
+};
+use polars::export::chrono::NaiveDateTime;
+use polars::prelude::*;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+
+// the most rows (with the header) and columns that an excel sheet holds
+pub const EXCEL_MAX_ROWS: usize = 1_048_576;
+pub const EXCEL_MAX_COLUMNS: usize = 16_384;
+
+// the serial numbers of 1970-01-01, of the first date after the day that
+// excel wrongly takes 1900 to have had, and of the day after 9999-12-31
+const UNIX_EPOCH_SERIAL: f64 = 25_569.0;
+const MIN_SERIAL: f64 = 61.0;
+const MAX_SERIAL: f64 = 2_958_466.0;
+
+// --------------------------------------------------
+fn open_workbook(bytes: Vec<u8>, source: &str) -> Sheets<Cursor<Vec<u8>>> {
+    open_workbook_auto_from_rs(Cursor::new(bytes)).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to open the spreadsheet {}: {}", source, e))
+    })
+}
+
+// --------------------------------------------------
+// find a sheet by its 0-based index or by its case-insensitive name
+fn find_sheet_name(sheet_names: &[String], source: &str, sheet: &str) -> String {
+    let by_index = sheet
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| sheet_names.get(index));
+    let by_name = || {
+        sheet_names
+            .iter()
+            .find(|name| name.to_lowercase() == sheet.to_lowercase())
+    };
+
+    by_index.or_else(by_name).cloned().unwrap_or_else(|| {
+        exit_with_error(&format!(
+            "Sheet {} not found in the spreadsheet {}; the sheets are {}",
+            sheet,
+            source,
+            sheet_names.join(", ")
+        ))
+    })
+}
+
+// --------------------------------------------------
+// read a sheet by its 0-based index or by its name
+fn get_sheet_range(bytes: Vec<u8>, source: &str, sheet: Option<&str>) -> Range<Data> {
+    let mut workbook = open_workbook(bytes, source);
+    let sheet = find_sheet_name(&workbook.sheet_names(), source, sheet.unwrap_or("0"));
+
+    workbook.worksheet_range(&sheet).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to read the sheet {} of the spreadsheet {}: {}",
+            sheet, source, e
+        ))
+    })
+}
+
+// --------------------------------------------------
+// get the number of rows and columns of a sheet. xlsx sheets declare their
+// dimensions, so their cells are only read when the declaration is missing
+fn get_sheet_size(workbook: &mut Sheets<Cursor<Vec<u8>>>, sheet: &str) -> Option<(usize, usize)> {
+    if let Sheets::Xlsx(xlsx) = workbook {
+        let dimensions = xlsx
+            .worksheet_cells_reader(sheet)
+            .map(|reader| reader.dimensions());
+        if let Ok(Dimensions { start, end }) = dimensions {
+            if (start, end) != ((0, 0), (0, 0)) {
+                return Some((
+                    (end.0 - start.0 + 1) as usize,
+                    (end.1 - start.1 + 1) as usize,
+                ));
+            }
+        }
+    }
+
+    workbook
+        .worksheet_range(sheet)
+        .ok()
+        .map(|range| range.get_size())
+}
+
+// --------------------------------------------------
+// print the sheets of a spreadsheet with their dimensions, marking the
+// hidden ones
+pub fn list_sheets(bytes: Vec<u8>, source: &str) {
+    let mut workbook = open_workbook(bytes, source);
+
+    for (index, sheet) in workbook.sheets_metadata().to_vec().iter().enumerate() {
+        let size = match get_sheet_size(&mut workbook, &sheet.name) {
+            Some((n_rows, n_columns)) => format!("{} rows x {} columns", n_rows, n_columns),
+            None => String::from("not a worksheet"),
+        };
+        let hidden = match sheet.visible {
+            SheetVisible::Visible => "",
+            SheetVisible::Hidden | SheetVisible::VeryHidden => " (hidden)",
+        };
+        println!("{}: {}, {}{}", index, sheet.name, size, hidden);
+    }
+}
+
+// --------------------------------------------------
+// name the columns from the header row, generating names for empty
+// (e.g. merged) cells and de-duplicating repeated names
+fn get_column_names(header: Option<&[Data]>, width: usize) -> Vec<String> {
+    let mut seen = HashSet::<String>::new();
+
+    (0..width)
+        .map(|i| {
+            let name = match header.and_then(|row| row.get(i)) {
+                Some(Data::Empty) | None => format!("column_{}", i + 1),
+                Some(cell) => cell.to_string().trim().to_string(),
+            };
+
+            let mut unique_name = name.clone();
+            let mut suffix = 1;
+            while !seen.insert(unique_name.clone()) {
+                unique_name = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            unique_name
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// build a typed series from a column of cells, falling back to strings
+// when the cells do not share a type
+fn cells_to_series(name: &str, cells: &[&Data]) -> Series {
+    let values: Vec<&Data> = cells
+        .iter()
+        .copied()
+        .filter(|cell| !matches!(cell, Data::Empty))
+        .collect();
+
+    if values.is_empty() {
+        return Series::full_null(name, cells.len(), &DataType::Utf8);
+    }
+
+    // spreadsheets store every number as a float, so whole numbers become integers
+    if values.iter().all(|cell| match cell {
+        Data::Int(_) => true,
+        Data::Float(value) => value.fract() == 0.0 && value.abs() < i64::MAX as f64,
+        _ => false,
+    }) {
+        let column: Vec<Option<i64>> = cells
+            .iter()
+            .map(|cell| cell.as_f64().map(|value| value as i64))
+            .collect();
+        return Series::new(name, column);
+    }
+
+    if values
+        .iter()
+        .all(|cell| matches!(cell, Data::Int(_) | Data::Float(_)))
+    {
+        let column: Vec<Option<f64>> = cells.iter().map(|cell| cell.as_f64()).collect();
+        return Series::new(name, column);
+    }
+
+    if values.iter().all(|cell| matches!(cell, Data::Bool(_))) {
+        let column: Vec<Option<bool>> = cells.iter().map(|cell| cell.get_bool()).collect();
+        return Series::new(name, column);
+    }
+
+    if values
+        .iter()
+        .all(|cell| matches!(cell, Data::DateTime(_) | Data::DateTimeIso(_)))
+    {
+        let column: Vec<Option<NaiveDateTime>> =
+            cells.iter().map(|cell| cell.as_datetime()).collect();
+        return Series::new(name, column);
+    }
+
+    let column: Vec<Option<String>> = cells
+        .iter()
+        .map(|cell| match cell {
+            Data::Empty => None,
+            _ => Some(cell.to_string()),
+        })
+        .collect();
+    Series::new(name, column)
+}
+
+// --------------------------------------------------
+// parse a sheet of an excel (or open document) spreadsheet
+pub fn parse_excel(
+    bytes: Vec<u8>,
+    source: &str,
+    sheet: Option<&str>,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    has_header: bool,
+) -> DataFrame {
+    let range = get_sheet_range(bytes, source, sheet);
+    let width = range.width();
+
+    // leading empty rows are not part of the table
+    let mut rows = range
+        .rows()
+        .skip_while(|row| row.iter().all(|cell| matches!(cell, Data::Empty)));
+
+    let header = if has_header { rows.next() } else { None };
+    let column_names = get_column_names(header, width);
+
+    let rows: Vec<&[Data]> = rows.take(n_rows.unwrap_or(usize::MAX)).collect();
+
+    let columns: Vec<Series> = column_names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let cells: Vec<&Data> = rows
+                .iter()
+                .map(|row| row.get(i).unwrap_or(&Data::Empty))
+                .collect();
+            cells_to_series(name, &cells)
+        })
+        .collect();
+
+    let df = DataFrame::new(columns).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to parse the spreadsheet {}: {}",
+            source, e
+        ))
+    });
+
+    match select_columns {
+        Some(columns) => df.select(columns).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to select columns from {}: {}", source, e))
+        }),
+        None => df,
+    }
+}
+
+// --------------------------------------------------
+// the values of a date, datetime, or time column as excel serial numbers,
+// in days since 1900, with the number format that shows them. dates that
+// excel cannot show are left out as none
+fn get_serial_dates(series: &Series) -> Option<(Vec<Option<f64>>, &'static str)> {
+    let (day, offset, format) = match series.dtype() {
+        DataType::Date => (1.0, UNIX_EPOCH_SERIAL, "yyyy-mm-dd"),
+        DataType::Datetime(TimeUnit::Nanoseconds, _) => {
+            (86_400e9, UNIX_EPOCH_SERIAL, "yyyy-mm-dd hh:mm:ss")
+        }
+        DataType::Datetime(TimeUnit::Microseconds, _) => {
+            (86_400e6, UNIX_EPOCH_SERIAL, "yyyy-mm-dd hh:mm:ss")
+        }
+        DataType::Datetime(TimeUnit::Milliseconds, _) => {
+            (86_400e3, UNIX_EPOCH_SERIAL, "yyyy-mm-dd hh:mm:ss")
+        }
+        DataType::Time => (86_400e9, 0.0, "hh:mm:ss"),
+        _ => return None,
+    };
+
+    let serials = series
+        .to_physical_repr()
+        .cast(&DataType::Float64)
+        .expect("dates can be cast to f64")
+        .f64()
+        .expect("the column was cast to f64")
+        .into_iter()
+        .map(|value| {
+            value
+                .map(|value| value / day + offset)
+                .filter(|&serial| offset == 0.0 || (MIN_SERIAL..MAX_SERIAL).contains(&serial))
+        })
+        .collect();
+    Some((serials, format))
+}
+
+// --------------------------------------------------
+// write a table as a sheet of an excel workbook, with a bold header and
+// columns as wide as their values. numbers, booleans, and dates keep their
+// types, and everything else is written as text
+pub fn write_excel<W: Write + Send>(
+    df: &DataFrame,
+    writer: W,
+    sheet_name: Option<&str>,
+) -> Result<(), XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    if let Some(sheet_name) = sheet_name {
+        worksheet.set_name(sheet_name)?;
+    }
+
+    let bold = Format::new().set_bold();
+    for (j, series) in df.get_columns().iter().enumerate() {
+        let col = j as u16;
+        worksheet.write_string_with_format(0, col, series.name(), &bold)?;
+        // the text of the values, for those that are not written as numbers
+        let texts = format_column(series, None);
+
+        if let Some((serials, format)) = get_serial_dates(series) {
+            let format = Format::new().set_num_format(format);
+            for (i, (serial, text)) in serials.iter().zip(&texts).enumerate() {
+                let row = i as u32 + 1;
+                match (serial, text) {
+                    (Some(serial), _) => {
+                        worksheet.write_number_with_format(row, col, *serial, &format)?
+                    }
+                    (None, Some(text)) => worksheet.write_string(row, col, text)?,
+                    (None, None) => continue,
+                };
+            }
+            continue;
+        }
+
+        match series.dtype() {
+            DataType::Boolean => {
+                let values = series.bool().expect("the column is boolean");
+                for (i, value) in values.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet.write_boolean(i as u32 + 1, col, value)?;
+                    }
+                }
+            }
+            // excel has no nan or infinity, so those are written as text
+            dtype if dtype.is_numeric() => {
+                let values = series
+                    .cast(&DataType::Float64)
+                    .expect("numbers can be cast to f64");
+                let values = values.f64().expect("the column was cast to f64");
+                for (i, value) in values.into_iter().enumerate() {
+                    match value {
+                        Some(value) if value.is_finite() => {
+                            worksheet.write_number(i as u32 + 1, col, value)?
+                        }
+                        Some(value) => {
+                            worksheet.write_string(i as u32 + 1, col, value.to_string())?
+                        }
+                        None => continue,
+                    };
+                }
+            }
+            _ => {
+                for (i, text) in texts.iter().enumerate() {
+                    if let Some(text) = text {
+                        worksheet.write_string(i as u32 + 1, col, text)?;
+                    }
+                }
+            }
+        }
+    }
+    worksheet.autofit();
+
+    workbook.save_to_writer(writer)
+}
@@ -0,0 +1,72 @@
+use std::io::Read;
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+// the filepath that stands for the output of the --exec command
+pub const EXEC_PATH: &str = "@exec";
+
+// --------------------------------------------------
+pub fn is_exec(filepath: &str) -> bool {
+    filepath == EXEC_PATH
+}
+
+// --------------------------------------------------
+// the standard output of a command. once it is read to the end, a failing
+// command exits with its own status; a command that is still running when
+// the output is dropped (because enough rows were read) is killed
+pub struct CommandOutput {
+    command: String,
+    child: Child,
+    stdout: ChildStdout,
+    finished: bool,
+}
+
+impl Read for CommandOutput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.stdout.read(buf)?;
+        if n == 0 && !buf.is_empty() && !self.finished {
+            self.finished = true;
+            let status = self.child.wait()?;
+            if !status.success() {
+                crate::exit_with_code(
+                    &format!("`{}` failed with {}", self.command, status),
+                    status.code().filter(|&code| code != 0).unwrap_or(1),
+                );
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl Drop for CommandOutput {
+    fn drop(&mut self) {
+        if !self.finished && self.child.try_wait().is_ok_and(|status| status.is_none()) {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+// --------------------------------------------------
+// run a command through the shell, passing its standard error through
+pub fn spawn_command(command: &str) -> CommandOutput {
+    let (shell, flag) = match cfg!(windows) {
+        true => ("cmd", "/C"),
+        false => ("sh", "-c"),
+    };
+
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .unwrap_or_else(|e| crate::exit_with_error(&format!("Unable to run `{}`: {}", command, e)));
+    let stdout = child.stdout.take().expect("the standard output is piped");
+
+    CommandOutput {
+        command: command.to_string(),
+        child,
+        stdout,
+        finished: false,
+    }
+}
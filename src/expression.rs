@@ -0,0 +1,254 @@
+// --------------------------------------------------
+// a piece of a --filter or --derive expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    OpenParen,
+    CloseParen,
+    Comma,
+    Assign,
+    And,
+    Or,
+    Not,
+    Compare(Comparison),
+    Arithmetic(Arithmetic),
+    Word(String),
+    Text(String),
+    Column(String),
+    Number(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+// --------------------------------------------------
+// split an expression into its tokens, each with the offsets where it starts
+// and ends. strings are quoted with " or ', and column names with spaces or
+// other characters are quoted with backticks. a minus sign is a token of its
+// own, even before a number
+fn tokenize(source: &str) -> Result<Vec<(Token, usize, usize)>, String> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(source.len(), |&(offset, _)| offset);
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+
+    let mut tokens = Vec::<(Token, usize, usize)>::new();
+    let mut i = 0;
+    while let Some(&(start, c)) = chars.get(i) {
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::OpenParen, 1),
+            (')', _) => (Token::CloseParen, 1),
+            (',', _) => (Token::Comma, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Compare(Comparison::Equal), 2),
+            ('!', Some('=')) => (Token::Compare(Comparison::NotEqual), 2),
+            ('<', Some('=')) => (Token::Compare(Comparison::LessEqual), 2),
+            ('>', Some('=')) => (Token::Compare(Comparison::GreaterEqual), 2),
+            ('=', _) => (Token::Assign, 1),
+            ('!', _) => (Token::Not, 1),
+            ('<', _) => (Token::Compare(Comparison::Less), 1),
+            ('>', _) => (Token::Compare(Comparison::Greater), 1),
+            ('+', _) => (Token::Arithmetic(Arithmetic::Add), 1),
+            ('-', _) => (Token::Arithmetic(Arithmetic::Subtract), 1),
+            ('*', _) => (Token::Arithmetic(Arithmetic::Multiply), 1),
+            ('/', _) => (Token::Arithmetic(Arithmetic::Divide), 1),
+            ('%', _) => (Token::Arithmetic(Arithmetic::Remainder), 1),
+            ('"' | '\'' | '`', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&(_, quote)| quote == c)
+                    .map(|n| i + 1 + n)
+                    .ok_or_else(|| format!("the quote {} at {} is not closed", c, start + 1))?;
+                let text = source[offset(i + 1)..offset(end)].to_string();
+                let token = match c {
+                    '`' => Token::Column(text),
+                    _ => Token::Text(text),
+                };
+                (token, end + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() => {
+                let length = chars[i..]
+                    .iter()
+                    .take_while(|&&(_, c)| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                    .count();
+                let number = source[start..offset(i + length)].replace('_', "");
+                (Token::Number(number), length)
+            }
+            (c, _) if is_word(c) => {
+                let length = chars[i..].iter().take_while(|&&(_, c)| is_word(c)).count();
+                (
+                    Token::Word(source[start..offset(i + length)].to_string()),
+                    length,
+                )
+            }
+            (c, _) => return Err(format!("unexpected {} at {}", c, start + 1)),
+        };
+        tokens.push((token, start, offset(i + length)));
+        i += length;
+    }
+    Ok(tokens)
+}
+
+// --------------------------------------------------
+// the tokens of an expression and the position of the next one, which the
+// recursive descent parsers of --filter and --derive move through
+pub struct Parser<'a> {
+    pub source: &'a str,
+    pub tokens: Vec<(Token, usize, usize)>,
+    pub position: usize,
+}
+
+impl<'a> Parser<'a> {
+    // --------------------------------------------------
+    pub fn new(source: &'a str) -> Result<Self, String> {
+        Ok(Parser {
+            source,
+            tokens: tokenize(source)?,
+            position: 0,
+        })
+    }
+
+    // --------------------------------------------------
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _, _)| token)
+    }
+
+    // --------------------------------------------------
+    // whether the next token is the given keyword, in any case
+    pub fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    // --------------------------------------------------
+    pub fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    // --------------------------------------------------
+    // where the next token starts, or the end of the expression
+    pub fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.source.len(), |&(_, start, _)| start)
+    }
+
+    // --------------------------------------------------
+    // the text of the expression from the given offset to the last token
+    pub fn clause(&self, start: usize) -> String {
+        let end = self.tokens[..self.position]
+            .last()
+            .map_or(start, |&(_, _, end)| end);
+        self.source[start..end].to_string()
+    }
+
+    // --------------------------------------------------
+    pub fn expected(&self, what: &str) -> String {
+        match self.tokens.get(self.position) {
+            Some((_, start, end)) => format!(
+                "expected {} at {}, found {}",
+                what,
+                start + 1,
+                &self.source[*start..*end]
+            ),
+            None => format!("expected {} at the end", what),
+        }
+    }
+
+    // --------------------------------------------------
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        match self.peek_keyword(keyword) {
+            true => {
+                self.position += 1;
+                Ok(())
+            }
+            false => Err(self.expected(keyword)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_filters_and_derivations() {
+        assert_eq!(
+            tokens("price >= -1.5 && !(`first name` == 'a')"),
+            [
+                Token::Word(String::from("price")),
+                Token::Compare(Comparison::GreaterEqual),
+                Token::Arithmetic(Arithmetic::Subtract),
+                Token::Number(String::from("1.5")),
+                Token::And,
+                Token::Not,
+                Token::OpenParen,
+                Token::Column(String::from("first name")),
+                Token::Compare(Comparison::Equal),
+                Token::Text(String::from("a")),
+                Token::CloseParen,
+            ]
+        );
+        assert_eq!(
+            tokens("total = a*1_000 % 7"),
+            [
+                Token::Word(String::from("total")),
+                Token::Assign,
+                Token::Word(String::from("a")),
+                Token::Arithmetic(Arithmetic::Multiply),
+                Token::Number(String::from("1000")),
+                Token::Arithmetic(Arithmetic::Remainder),
+                Token::Number(String::from("7")),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_where_tokens_go_wrong() {
+        assert_eq!(
+            tokenize("a == 'b").unwrap_err(),
+            "the quote ' at 6 is not closed"
+        );
+        assert_eq!(tokenize("a ^ b").unwrap_err(), "unexpected ^ at 3");
+
+        let parser = Parser::new("a == b c").unwrap();
+        let parser = Parser {
+            position: 3,
+            ..parser
+        };
+        assert_eq!(parser.expected("the end"), "expected the end at 8, found c");
+        assert_eq!(parser.clause(0), "a == b");
+    }
+}
@@ -0,0 +1,443 @@
+use crate::exit_with_error;
+use crate::expression::{Arithmetic, Comparison, Parser, Token};
+use polars::export::chrono::{NaiveDate, NaiveTime, Timelike};
+use polars::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextMatch {
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+// --------------------------------------------------
+// a column or a literal value of a condition
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Column(String),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Boolean(bool),
+    Null,
+}
+
+// --------------------------------------------------
+// a parsed --filter expression, whose clauses keep their text for the
+// errors that point at them
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Compare(Operand, Comparison, Operand, String),
+    IsNull(Operand, bool, String),
+    In(Operand, Vec<Operand>, bool, String),
+    Match(Operand, TextMatch, Operand, String),
+    Column(Operand, String),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+// --------------------------------------------------
+// the grammar of --filter, parsed by recursive descent, where && binds
+// tighter than ||
+impl Parser<'_> {
+    // --------------------------------------------------
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            condition = Condition::Or(Box::new(condition), Box::new(self.parse_and()?));
+        }
+        Ok(condition)
+    }
+
+    // --------------------------------------------------
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_not()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            condition = Condition::And(Box::new(condition), Box::new(self.parse_not()?));
+        }
+        Ok(condition)
+    }
+
+    // --------------------------------------------------
+    fn parse_not(&mut self) -> Result<Condition, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.position += 1;
+                Ok(Condition::Not(Box::new(self.parse_not()?)))
+            }
+            Some(Token::OpenParen) => {
+                self.position += 1;
+                let condition = self.parse_or()?;
+                match self.next() {
+                    Some(Token::CloseParen) => Ok(condition),
+                    _ => {
+                        self.position -= 1;
+                        Err(self.expected(")"))
+                    }
+                }
+            }
+            _ => self.parse_clause(),
+        }
+    }
+
+    // --------------------------------------------------
+    fn parse_number(&self, number: &str) -> Result<Operand, String> {
+        match number.parse::<i64>() {
+            Ok(integer) => Ok(Operand::Integer(integer)),
+            Err(_) => Ok(Operand::Float(number.parse::<f64>().map_err(|_| {
+                format!("{} at {} is not a number", number, self.offset() + 1)
+            })?)),
+        }
+    }
+
+    // --------------------------------------------------
+    // a column or a literal. words other than true, false, and null name
+    // columns
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        let operand = match self.peek() {
+            Some(Token::Column(name)) => Operand::Column(name.clone()),
+            Some(Token::Text(text)) => Operand::Text(text.clone()),
+            Some(Token::Number(number)) => self.parse_number(number)?,
+            // a minus sign before a number makes it negative
+            Some(Token::Arithmetic(Arithmetic::Subtract)) => {
+                match self.tokens.get(self.position + 1) {
+                    Some((Token::Number(number), _, _)) => {
+                        let operand = self.parse_number(&format!("-{}", number))?;
+                        self.position += 1;
+                        operand
+                    }
+                    _ => return Err(self.expected("a column or a value")),
+                }
+            }
+            Some(Token::Word(word)) => match word.to_ascii_lowercase().as_str() {
+                "true" => Operand::Boolean(true),
+                "false" => Operand::Boolean(false),
+                "null" => Operand::Null,
+                _ => Operand::Column(word.clone()),
+            },
+            _ => return Err(self.expected("a column or a value")),
+        };
+        self.position += 1;
+        Ok(operand)
+    }
+
+    // --------------------------------------------------
+    // a comparison, a test for nulls, a list of values, or a text match,
+    // or a boolean column on its own
+    fn parse_clause(&mut self) -> Result<Condition, String> {
+        let start = self.offset();
+        let operand = self.parse_operand()?;
+
+        // = compares like ==
+        let comparison = match self.peek() {
+            Some(Token::Compare(comparison)) => Some(*comparison),
+            Some(Token::Assign) => Some(Comparison::Equal),
+            _ => None,
+        };
+        if let Some(comparison) = comparison {
+            self.position += 1;
+            let other = self.parse_operand()?;
+            return Ok(Condition::Compare(
+                operand,
+                comparison,
+                other,
+                self.clause(start),
+            ));
+        }
+
+        if self.peek_keyword("is") {
+            self.position += 1;
+            let negated = self.peek_keyword("not");
+            if negated {
+                self.position += 1;
+            }
+            self.expect_keyword("null")?;
+            return Ok(Condition::IsNull(operand, negated, self.clause(start)));
+        }
+
+        let negated = self.peek_keyword("not");
+        if negated {
+            self.position += 1;
+        }
+        if self.peek_keyword("in") {
+            self.position += 1;
+            if self.next() != Some(Token::OpenParen) {
+                self.position -= 1;
+                return Err(self.expected("("));
+            }
+            let mut values = vec![self.parse_operand()?];
+            loop {
+                match self.next() {
+                    Some(Token::Comma) => values.push(self.parse_operand()?),
+                    Some(Token::CloseParen) => break,
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected(", or )"));
+                    }
+                }
+            }
+            return Ok(Condition::In(operand, values, negated, self.clause(start)));
+        }
+        if negated {
+            return Err(self.expected("in"));
+        }
+
+        let text_match = [
+            ("contains", TextMatch::Contains),
+            ("startswith", TextMatch::StartsWith),
+            ("endswith", TextMatch::EndsWith),
+        ]
+        .into_iter()
+        .find(|(keyword, _)| self.peek_keyword(keyword));
+        if let Some((_, text_match)) = text_match {
+            self.position += 1;
+            let pattern = self.parse_operand()?;
+            return Ok(Condition::Match(
+                operand,
+                text_match,
+                pattern,
+                self.clause(start),
+            ));
+        }
+
+        Ok(Condition::Column(operand, self.clause(start)))
+    }
+}
+
+// --------------------------------------------------
+fn parse_condition(source: &str) -> Result<Condition, String> {
+    let mut parser = Parser::new(source)?;
+    let condition = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(condition),
+        Some(_) => Err(parser.expected("&&, ||, or the end")),
+    }
+}
+
+// --------------------------------------------------
+// what an operand holds, which decides what it can be compared with
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Number,
+    Text,
+    Boolean,
+    Temporal(DataType),
+    Null,
+    Other(DataType),
+}
+
+// --------------------------------------------------
+// an operand as a polars expression, described for the errors
+fn compile_operand(operand: &Operand, schema: &Schema) -> Result<(Expr, Kind, String), String> {
+    Ok(match operand {
+        Operand::Column(name) => {
+            let dtype = schema
+                .get(name)
+                .ok_or_else(|| format!("there is no column {}", name))?;
+            let kind = match dtype {
+                dtype if dtype.is_numeric() => Kind::Number,
+                DataType::Utf8 => Kind::Text,
+                DataType::Boolean => Kind::Boolean,
+                DataType::Date
+                | DataType::Datetime(_, _)
+                | DataType::Time
+                | DataType::Duration(_) => Kind::Temporal(dtype.clone()),
+                dtype => Kind::Other(dtype.clone()),
+            };
+            (col(name), kind, format!("the column {} ({})", name, dtype))
+        }
+        Operand::Integer(integer) => (
+            lit(*integer),
+            Kind::Number,
+            format!("the number {}", integer),
+        ),
+        Operand::Float(float) => (lit(*float), Kind::Number, format!("the number {}", float)),
+        Operand::Text(text) => (
+            lit(text.as_str()),
+            Kind::Text,
+            format!("the text {:?}", text),
+        ),
+        Operand::Boolean(boolean) => (lit(*boolean), Kind::Boolean, boolean.to_string()),
+        Operand::Null => (lit(NULL), Kind::Null, String::from("null")),
+    })
+}
+
+// --------------------------------------------------
+// a date, datetime, or time written as text, e.g. 2024-01-31, 2024-01-31
+// 12:30, or 12:30:15, as a literal of the type of the column it is
+// compared with. a date alone is the midnight of that day
+fn parse_temporal(text: &str, dtype: &DataType) -> Option<Expr> {
+    let text = text.trim();
+    let parse_date = || NaiveDate::parse_from_str(text, "%Y-%m-%d").ok();
+    let parse_time = |text: &str| {
+        ["%H:%M:%S%.f", "%H:%M"]
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(text, format).ok())
+    };
+
+    match dtype {
+        DataType::Date => parse_date().map(|date| lit(date).cast(DataType::Date)),
+        DataType::Datetime(_, _) => {
+            let datetime = text
+                .split_once(['T', ' '])
+                .and_then(|(date, time)| {
+                    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+                    Some(date.and_time(parse_time(time.trim_end_matches('Z'))?))
+                })
+                .or_else(|| parse_date().and_then(|date| date.and_hms_opt(0, 0, 0)))?;
+            Some(lit(datetime).cast(dtype.clone()))
+        }
+        DataType::Time => {
+            let time = parse_time(text)?;
+            let nanoseconds =
+                time.num_seconds_from_midnight() as i64 * 1_000_000_000 + time.nanosecond() as i64;
+            Some(lit(nanoseconds).cast(DataType::Time))
+        }
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// the two sides of a comparison, when they can be compared. text compared
+// with a date, datetime, time, or duration column is read as one
+fn compile_comparable(
+    left: &Operand,
+    right: &Operand,
+    schema: &Schema,
+) -> Result<(Expr, Expr), String> {
+    let (left_expr, left_kind, left_name) = compile_operand(left, schema)?;
+    let (right_expr, right_kind, right_name) = compile_operand(right, schema)?;
+
+    let as_temporal = |text: &Operand, dtype: &DataType| -> Result<Expr, String> {
+        let Operand::Text(text) = text else {
+            return Err(format!(
+                "{} cannot be compared with {}",
+                left_name, right_name
+            ));
+        };
+        parse_temporal(text, dtype)
+            .ok_or_else(|| format!("{:?} is not a value of type {}", text, dtype))
+    };
+
+    match (&left_kind, &right_kind) {
+        (Kind::Null, _) | (_, Kind::Null) => Err(String::from(
+            "nulls are tested with `is null` or `is not null`",
+        )),
+        (Kind::Number, Kind::Number)
+        | (Kind::Text, Kind::Text)
+        | (Kind::Boolean, Kind::Boolean) => Ok((left_expr, right_expr)),
+        (Kind::Temporal(left_dtype), Kind::Temporal(right_dtype)) if left_dtype == right_dtype => {
+            Ok((left_expr, right_expr))
+        }
+        (Kind::Temporal(dtype), Kind::Text) => Ok((left_expr, as_temporal(right, dtype)?)),
+        (Kind::Text, Kind::Temporal(dtype)) => Ok((as_temporal(left, dtype)?, right_expr)),
+        _ => Err(format!(
+            "{} cannot be compared with {}",
+            left_name, right_name
+        )),
+    }
+}
+
+// --------------------------------------------------
+// a parsed --filter expression as a polars expression, checked against the
+// types of the columns of the table
+fn compile_condition(condition: &Condition, schema: &Schema) -> Result<Expr, String> {
+    let in_clause = |clause: &str, e: String| format!("in `{}`, {}", clause, e);
+
+    match condition {
+        Condition::Compare(left, comparison, right, clause) => {
+            let (left, right) =
+                compile_comparable(left, right, schema).map_err(|e| in_clause(clause, e))?;
+            Ok(match comparison {
+                Comparison::Equal => left.eq(right),
+                Comparison::NotEqual => left.neq(right),
+                Comparison::Less => left.lt(right),
+                Comparison::LessEqual => left.lt_eq(right),
+                Comparison::Greater => left.gt(right),
+                Comparison::GreaterEqual => left.gt_eq(right),
+            })
+        }
+        Condition::IsNull(operand, negated, clause) => {
+            let (expr, _, _) =
+                compile_operand(operand, schema).map_err(|e| in_clause(clause, e))?;
+            Ok(match negated {
+                true => expr.is_not_null(),
+                false => expr.is_null(),
+            })
+        }
+        Condition::In(operand, values, negated, clause) => {
+            let mut expr = lit(false);
+            for value in values {
+                let (left, right) =
+                    compile_comparable(operand, value, schema).map_err(|e| in_clause(clause, e))?;
+                expr = expr.or(left.eq(right));
+            }
+            Ok(match negated {
+                true => expr.not(),
+                false => expr,
+            })
+        }
+        Condition::Match(operand, text_match, pattern, clause) => {
+            let (expr, kind, name) =
+                compile_operand(operand, schema).map_err(|e| in_clause(clause, e))?;
+            if kind != Kind::Text {
+                return Err(in_clause(clause, format!("{} does not hold text", name)));
+            }
+            let Operand::Text(pattern) = pattern else {
+                return Err(in_clause(
+                    clause,
+                    String::from("the pattern must be quoted text"),
+                ));
+            };
+            let pattern = lit(pattern.as_str());
+            Ok(match text_match {
+                TextMatch::Contains => expr.str().contains_literal(pattern),
+                TextMatch::StartsWith => expr.str().starts_with(pattern),
+                TextMatch::EndsWith => expr.str().ends_with(pattern),
+            })
+        }
+        Condition::Column(operand, clause) => {
+            let (expr, kind, name) =
+                compile_operand(operand, schema).map_err(|e| in_clause(clause, e))?;
+            match kind {
+                Kind::Boolean => Ok(expr),
+                _ => Err(in_clause(
+                    clause,
+                    format!("{} is not a condition; compare it with a value", name),
+                )),
+            }
+        }
+        Condition::Not(condition) => Ok(compile_condition(condition, schema)?.not()),
+        Condition::And(left, right) => {
+            Ok(compile_condition(left, schema)?.and(compile_condition(right, schema)?))
+        }
+        Condition::Or(left, right) => {
+            Ok(compile_condition(left, schema)?.or(compile_condition(right, schema)?))
+        }
+    }
+}
+
+// --------------------------------------------------
+// keep the rows of a table that meet all of the --filter expressions, up to
+// the given number of rows
+pub fn filter_table(df: DataFrame, filters: &[String], n_rows: Option<usize>) -> DataFrame {
+    let schema = df.schema();
+    let mut predicate = lit(true);
+    for filter in filters {
+        let condition = parse_condition(filter)
+            .unwrap_or_else(|e| exit_with_error(&format!("Invalid --filter `{}`: {}", filter, e)));
+        let condition = compile_condition(&condition, &schema)
+            .unwrap_or_else(|e| exit_with_error(&format!("Invalid --filter: {}", e)));
+        predicate = predicate.and(condition);
+    }
+
+    let mut lazy = df.lazy().filter(predicate);
+    if let Some(n) = n_rows {
+        lazy = lazy.limit(n as IdxSize);
+    }
+    lazy.collect()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to filter the table: {}", e)))
+}
@@ -0,0 +1,61 @@
+use crate::tsv::escape_field;
+
+// --------------------------------------------------
+// slice a line into the byte ranges of its columns, trimming the padding.
+// the last column takes the rest of the line, and columns past the end of
+// a short line are left empty
+fn slice_line(line: &[u8], ranges: &[(usize, usize)]) -> Vec<String> {
+    ranges
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let end = if i + 1 == ranges.len() {
+                line.len()
+            } else {
+                end
+            };
+            let field = &line[start.min(line.len())..end.min(line.len())];
+            String::from_utf8_lossy(field).trim().to_string()
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// rewrite fixed-width text as tab-separated text with a header row. the
+// column names are those given, those sliced from the first line, or
+// generated when the text has no header
+pub fn fixed_width_to_tsv(
+    bytes: &[u8],
+    ranges: &[(usize, usize)],
+    names: Option<&[String]>,
+    has_header: bool,
+) -> Vec<u8> {
+    let mut lines = bytes
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace));
+
+    let header: Vec<String> = match names {
+        Some(names) => names.to_vec(),
+        None if has_header => lines
+            .next()
+            .map(|line| slice_line(line, ranges))
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let header: Vec<String> = (0..ranges.len())
+        .map(|i| match header.get(i) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!("column_{}", i + 1),
+        })
+        .collect();
+
+    let mut tsv = String::new();
+    for fields in std::iter::once(header).chain(lines.map(|line| slice_line(line, ranges))) {
+        let fields: Vec<String> = fields.iter().map(|field| escape_field(field)).collect();
+        tsv.push_str(&fields.join("\t"));
+        tsv.push('\n');
+    }
+
+    tsv.into_bytes()
+}
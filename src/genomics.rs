@@ -0,0 +1,48 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+
+// names of the nine tab-separated fields of gff and gtf files
+const GFF_COLUMN_NAMES: [&str; 9] = [
+    "seqid",
+    "source",
+    "type",
+    "start",
+    "end",
+    "score",
+    "strand",
+    "phase",
+    "attributes",
+];
+
+// --------------------------------------------------
+// skip the ## meta-information lines of a vcf file, and the # that
+// starts its #CHROM header line
+pub fn strip_vcf_meta_lines(bytes: &[u8]) -> &[u8] {
+    let mut rest = bytes;
+    while rest.starts_with(b"##") {
+        rest = match rest.iter().position(|&b| b == b'\n') {
+            Some(i) => &rest[i + 1..],
+            None => &[],
+        };
+    }
+
+    rest.strip_prefix(b"#").unwrap_or(rest)
+}
+
+// --------------------------------------------------
+// name the columns of a gff or gtf table, which has no header row
+pub fn name_gff_columns(mut df: DataFrame, source: &str) -> DataFrame {
+    let column_names: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    for (column_name, gff_column_name) in column_names.iter().zip(GFF_COLUMN_NAMES) {
+        df.rename(column_name, gff_column_name).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to name the columns of {}: {}", source, e))
+        });
+    }
+
+    df
+}
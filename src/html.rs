@@ -0,0 +1,161 @@
+use crate::exit_with_error;
+use crate::tsv::escape_field;
+use scraper::{ElementRef, Html, Selector};
+
+// --------------------------------------------------
+// the text of a cell (but not of the tables nested in it), with its runs of
+// whitespace collapsed
+fn get_cell_text(cell: &ElementRef) -> String {
+    let table = get_parent_table(cell);
+    let text: String = cell
+        .descendants()
+        .filter(|node| node.ancestors().filter_map(ElementRef::wrap).find(is_table) == table)
+        .filter_map(|node| node.value().as_text().map(|text| &**text))
+        .collect();
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// --------------------------------------------------
+// the number of columns or rows a cell spans
+fn get_span(cell: &ElementRef, attribute: &str) -> usize {
+    cell.value()
+        .attr(attribute)
+        .and_then(|span| span.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 1000)
+}
+
+// --------------------------------------------------
+fn is_table(element: &ElementRef) -> bool {
+    element.value().name() == "table"
+}
+
+// --------------------------------------------------
+// the closest table an element is nested in
+fn get_parent_table<'a>(element: &ElementRef<'a>) -> Option<ElementRef<'a>> {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(is_table)
+}
+
+// --------------------------------------------------
+// lay out the rows of a table (but not of the tables nested in it) as a
+// grid, copying a cell that spans several columns or rows into each of
+// them. the first row is a header when all of its cells are <th>, and rows
+// without cells are skipped
+fn get_table_rows(table: &ElementRef) -> (Vec<Vec<String>>, bool) {
+    let row_selector = Selector::parse("tr").expect("the selector is valid");
+    let rows = table
+        .select(&row_selector)
+        .filter(|row| get_parent_table(row).as_ref() == Some(table));
+
+    let mut grid = Vec::<Vec<String>>::new();
+    let mut has_header = false;
+    // the cells spanning down from earlier rows, with the rows they have left
+    let mut carried = Vec::<Option<(String, usize)>>::new();
+    for row in rows {
+        let cells: Vec<ElementRef> = row
+            .child_elements()
+            .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+            .collect();
+        let all_th = cells.iter().all(|cell| cell.value().name() == "th");
+
+        let mut fields = Vec::<String>::new();
+        let mut cells = cells.into_iter();
+        loop {
+            let column = fields.len();
+            if let Some((text, rows_left)) = carried.get_mut(column).and_then(Option::take) {
+                if rows_left > 1 {
+                    carried[column] = Some((text.clone(), rows_left - 1));
+                }
+                fields.push(text);
+                continue;
+            }
+
+            let Some(cell) = cells.next() else {
+                // leave a gap before the cells still spanning down on the right
+                if carried[column.min(carried.len())..]
+                    .iter()
+                    .any(Option::is_some)
+                {
+                    fields.push(String::new());
+                    continue;
+                }
+                break;
+            };
+
+            let text = get_cell_text(&cell);
+            let rowspan = get_span(&cell, "rowspan");
+            for _ in 0..get_span(&cell, "colspan") {
+                if rowspan > 1 {
+                    let column = fields.len();
+                    if carried.len() <= column {
+                        carried.resize(column + 1, None);
+                    }
+                    carried[column] = Some((text.clone(), rowspan - 1));
+                }
+                fields.push(text.clone());
+            }
+        }
+
+        if fields.is_empty() {
+            continue;
+        }
+        if grid.is_empty() {
+            has_header = all_th;
+        }
+        grid.push(fields);
+    }
+
+    (grid, has_header)
+}
+
+// --------------------------------------------------
+// rewrite a table of an html page as tab-separated text with a header row.
+// the column names come from a first row of <th> cells, or are generated
+pub fn html_table_to_tsv(bytes: &[u8], source: &str, table_index: usize) -> Vec<u8> {
+    let document = Html::parse_document(&String::from_utf8_lossy(bytes));
+    let table_selector = Selector::parse("table").expect("the selector is valid");
+    let tables: Vec<ElementRef> = document.select(&table_selector).collect();
+
+    let Some(table) = tables.get(table_index) else {
+        match tables.len() {
+            0 => exit_with_error(&format!("No tables found in {}", source)),
+            n => exit_with_error(&format!(
+                "table {} is out of range, {} has {} tables (0-{})",
+                table_index,
+                source,
+                n,
+                n - 1
+            )),
+        }
+    };
+
+    let (mut rows, has_header) = get_table_rows(table);
+    let n_columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    if n_columns == 0 {
+        exit_with_error(&format!("table {} of {} is empty", table_index, source));
+    }
+
+    let header = match has_header {
+        true => rows.remove(0),
+        false => Vec::new(),
+    };
+    let header: Vec<String> = (0..n_columns)
+        .map(|i| match header.get(i) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!("column_{}", i + 1),
+        })
+        .collect();
+
+    let mut tsv = String::new();
+    for mut fields in std::iter::once(header).chain(rows) {
+        fields.resize(n_columns, String::new());
+        let fields: Vec<String> = fields.iter().map(|field| escape_field(field)).collect();
+        tsv.push_str(&fields.join("\t"));
+        tsv.push('\n');
+    }
+
+    tsv.into_bytes()
+}
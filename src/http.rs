@@ -0,0 +1,146 @@
+use crate::cache::{Cache, CacheEntry, CachedHeaders};
+use crate::{exit_with_error, FileFormat};
+use std::io::Read;
+use ureq::http::StatusCode;
+
+// number of leading bytes of an error response that are shown
+const ERROR_BODY_LENGTH: u64 = 512;
+
+// --------------------------------------------------
+// a response body that is read as it is needed
+pub struct Download {
+    pub content_type: Option<String>,
+    pub reader: Box<dyn Read>,
+}
+
+// --------------------------------------------------
+pub fn is_url(filepath: &str) -> bool {
+    filepath.starts_with("http://") || filepath.starts_with("https://")
+}
+
+// --------------------------------------------------
+// get the path of a url, without the query string or fragment
+pub fn get_url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+// --------------------------------------------------
+// determine format based on the content type of a response
+pub fn get_format_from_content_type(content_type: &str) -> Option<FileFormat> {
+    match content_type {
+        "text/csv" => Some(FileFormat::Csv),
+        "text/tab-separated-values" => Some(FileFormat::Tsv),
+        "application/json" | "application/x-ndjson" | "application/jsonl" => Some(FileFormat::Json),
+        "application/vnd.apache.parquet" | "application/x-parquet" => Some(FileFormat::Parquet),
+        "application/vnd.apache.arrow.file" => Some(FileFormat::Ipc),
+        "application/vnd.apache.arrow.stream" => Some(FileFormat::IpcStream),
+        "application/avro" | "avro/binary" => Some(FileFormat::Avro),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        | "application/vnd.ms-excel" => Some(FileFormat::Excel),
+        "text/html" | "application/xhtml+xml" => Some(FileFormat::Html),
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// start downloading a url, following redirects. responses other than 2xx
+// exit with their status and the start of their body. with a cache, fresh
+// entries are read without a request, stale ones are revalidated, and new
+// responses are stored before they are read
+pub fn download(
+    url: &str,
+    headers: &[(String, String)],
+    bearer_token: Option<&str>,
+    cache: Option<&Cache>,
+) -> Download {
+    let cache_error =
+        |e: std::io::Error| -> ! { exit_with_error(&format!("Unable to cache {}: {}", url, e)) };
+
+    // an unusable cache directory only disables the cache
+    let cache = cache.filter(|cache| cache.create_directory().is_ok());
+    let entry = cache.and_then(|cache| cache.get(url));
+    if let (Some(cache), Some(entry)) = (cache, &entry) {
+        if cache.is_fresh(entry) {
+            return open_cache_entry(entry).unwrap_or_else(|e| cache_error(e));
+        }
+    }
+
+    let mut request = ureq::get(url).config().http_status_as_error(false).build();
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(token) = bearer_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+    if let Some(entry) = entry.as_ref().filter(|_| cache.is_some_and(|c| !c.refresh)) {
+        if let Some(etag) = &entry.etag {
+            request = request.header("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header("If-Modified-Since", last_modified);
+        }
+    }
+
+    let response = request
+        .call()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to download {}: {}", url, e)));
+
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = &entry {
+            entry.touch();
+            return open_cache_entry(entry).unwrap_or_else(|e| cache_error(e));
+        }
+    }
+    if !status.is_success() {
+        let mut body = Vec::<u8>::new();
+        let _ = response
+            .into_body()
+            .into_reader()
+            .take(ERROR_BODY_LENGTH)
+            .read_to_end(&mut body);
+        exit_with_error(&format!(
+            "Unable to download {}: HTTP {}\n{}",
+            url,
+            status,
+            String::from_utf8_lossy(&body).trim()
+        ));
+    }
+
+    let content_type = response.body().mime_type().map(String::from);
+    let get_header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let (etag, last_modified) = (get_header("etag"), get_header("last-modified"));
+    let mut reader = response.into_body().into_reader();
+
+    match cache {
+        Some(cache) => {
+            let cached_headers = CachedHeaders {
+                etag: etag.as_deref(),
+                last_modified: last_modified.as_deref(),
+                content_type: content_type.as_deref(),
+            };
+            let entry = cache
+                .store(url, &cached_headers, &mut reader)
+                .unwrap_or_else(|e| cache_error(e));
+            open_cache_entry(&entry).unwrap_or_else(|e| cache_error(e))
+        }
+        None => Download {
+            content_type,
+            reader: Box::new(reader),
+        },
+    }
+}
+
+// --------------------------------------------------
+fn open_cache_entry(entry: &CacheEntry) -> std::io::Result<Download> {
+    Ok(Download {
+        content_type: entry.content_type.clone(),
+        reader: entry.open()?,
+    })
+}
@@ -1,48 +1,423 @@
+mod aggregate;
+mod archive;
+mod cache;
+mod clipboard;
+mod cloud;
+mod compression;
+mod dataset;
+mod decimal;
+mod derive;
+mod duck;
+mod encoding;
+mod excel;
+mod exec;
+mod expression;
+mod filter;
+mod fixed_width;
+mod genomics;
+mod html;
+mod http;
+mod output;
+mod pager;
+mod parquet;
+mod progress;
+mod query;
+mod sniff;
+mod sqlite;
+mod tsv;
+mod whitespace;
+mod yaml;
+
+use aggregate::{aggregate_table, check_aggregate_columns, parse_aggregates, AggregateSpec};
+use archive::{get_zip_member, open_tar_archive, open_zip_archive, split_tar_path, split_zip_path};
+use cache::Cache;
+use chrono::format::{Item, StrftimeItems};
+use clap::parser::ValueSource;
 use clap::*;
 use clap::{Arg, ArgAction, Command};
+use clipboard::{finish_copy, is_clipboard, read_clipboard, start_copy, CLIPBOARD_PATH};
+use cloud::{get_cloud_parquet_column_names, is_cloud_url, parse_cloud_parquet};
+use compression::{
+    decompress, get_compression_from_filename, get_compression_from_magic, get_record_lines,
+    read_lines, read_ndjson_records, read_records, strip_compression_extension, Compression,
+};
+use dataset::{
+    collect_scan, expand_directories, get_parquet_dataset_column_names, is_parquet_dataset,
+    parse_parquet_dataset,
+};
+use decimal::parse_decimal_commas;
+use derive::derive_columns;
+use duck::{get_duckdb_table_names, parse_duckdb};
+use encoding::{decode_bom, strip_utf8_bom};
+use excel::{list_sheets, parse_excel};
+use exec::{is_exec, spawn_command, EXEC_PATH};
+use filter::filter_table;
+use fixed_width::fixed_width_to_tsv;
+use genomics::{name_gff_columns, strip_vcf_meta_lines};
+use html::html_table_to_tsv;
+use http::{download, get_format_from_content_type, get_url_path, is_url};
+use output::{
+    chunk_table, fit_table, format_binary, format_column, format_datetimes, format_matches,
+    format_nested, format_omitted_columns, format_omitted_values, format_scientific, format_shape,
+    get_alignment, get_null_map, get_repeats, get_terminal_width, get_totals, humanize_columns,
+    print_arrow_stream, print_asciidoc, print_delimited, print_html, print_json, print_latex,
+    print_line, print_markdown, print_org, print_plain, print_pretty, print_rst, print_sql,
+    print_typst, print_vertical, set_highlight, truncate_table, write_table, Aggregate, Alignment,
+    BinaryDisplay, Humanize, Notation, OutputFormat, SqlDialect, TableStyle, Truncation,
+};
+use pager::{get_terminal_height, start_pager, wait_for_pager, Paging};
+use parquet::{parse_parquet_row_groups, parse_parquet_sorted_rows};
 use polars::io::mmap::MmapBytesReader;
 use polars::prelude::*;
+use progress::{finish_progress, start_progress, track_progress};
+use query::{get_query_table_name, run_query};
+use regex::Regex;
+use sniff::{sniff_delimiter, sniff_format, sniff_utf16, SNIFF_LENGTH};
+use sqlite::{get_table_names, parse_sqlite_table};
 use std::env;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Seek};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use whitespace::whitespace_to_tsv;
+use yaml::parse_yaml;
 
 // --------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum FileFormat {
     Csv,
     Tsv,
     Parquet,
+    Json,
+    NdJson,
+    Ipc,
+    IpcStream,
+    Avro,
+    Excel,
+    Sqlite,
+    DuckDb,
+    Vcf,
+    Gff,
+    Yaml,
+    Html,
+}
+
+// the number of lines that the ndjson reader infers the schema from
+const NDJSON_SCHEMA_LENGTH: usize = 128;
+
+// the display width that values are truncated to in printed tables
+const DEFAULT_MAX_COL_WIDTH: usize = 32;
+
+// the display width that printed tables are fitted to when not printing to a
+// terminal
+const DEFAULT_WIDTH: usize = 120;
+
+// names of the formats, as given to --format
+const FORMAT_NAMES: [(&str, FileFormat); 15] = [
+    ("csv", FileFormat::Csv),
+    ("tsv", FileFormat::Tsv),
+    ("parquet", FileFormat::Parquet),
+    ("json", FileFormat::Json),
+    ("ndjson", FileFormat::NdJson),
+    ("ipc", FileFormat::Ipc),
+    ("arrow-stream", FileFormat::IpcStream),
+    ("avro", FileFormat::Avro),
+    ("excel", FileFormat::Excel),
+    ("sqlite", FileFormat::Sqlite),
+    ("duckdb", FileFormat::DuckDb),
+    ("vcf", FileFormat::Vcf),
+    ("gff", FileFormat::Gff),
+    ("yaml", FileFormat::Yaml),
+    ("html", FileFormat::Html),
+];
+
+// file extensions of the formats, matched after lowercasing
+const EXTENSION_FORMATS: &[(&str, FileFormat)] = &[
+    ("csv", FileFormat::Csv),
+    ("psv", FileFormat::Csv),
+    ("txt", FileFormat::Csv),
+    ("tsv", FileFormat::Tsv),
+    ("tab", FileFormat::Tsv),
+    ("parquet", FileFormat::Parquet),
+    ("pq", FileFormat::Parquet),
+    ("parq", FileFormat::Parquet),
+    ("json", FileFormat::Json),
+    ("ndjson", FileFormat::NdJson),
+    ("jsonl", FileFormat::NdJson),
+    ("arrow", FileFormat::Ipc),
+    ("ipc", FileFormat::Ipc),
+    ("feather", FileFormat::Ipc),
+    ("arrows", FileFormat::IpcStream),
+    ("avro", FileFormat::Avro),
+    ("xlsx", FileFormat::Excel),
+    ("xlsm", FileFormat::Excel),
+    ("xlsb", FileFormat::Excel),
+    ("xls", FileFormat::Excel),
+    ("ods", FileFormat::Excel),
+    ("db", FileFormat::Sqlite),
+    ("sqlite", FileFormat::Sqlite),
+    ("sqlite3", FileFormat::Sqlite),
+    ("duckdb", FileFormat::DuckDb),
+    ("ddb", FileFormat::DuckDb),
+    ("vcf", FileFormat::Vcf),
+    ("gff", FileFormat::Gff),
+    ("gff3", FileFormat::Gff),
+    ("gtf", FileFormat::Gff),
+    ("yaml", FileFormat::Yaml),
+    ("yml", FileFormat::Yaml),
+    ("html", FileFormat::Html),
+    ("htm", FileFormat::Html),
+];
+
+// --------------------------------------------------
+// the alignments given by --align, either one per column or those of the
+// named columns
+#[derive(Debug, Clone)]
+enum Alignments {
+    Columns(Vec<Alignment>),
+    Named(Vec<(String, Alignment)>),
 }
 
 // --------------------------------------------------
 #[derive(Debug)]
 struct CliArgs {
-    filepath: String,
+    filepaths: Vec<String>,
+    exec: Option<String>,
+    no_glob: bool,
+    http_headers: Vec<(String, String)>,
+    bearer_token: Option<String>,
+    no_cache: bool,
+    no_progress: bool,
+    refresh: bool,
+    cache_ttl: u64,
+    format: Option<FileFormat>,
+    with_source_column: bool,
+    index: bool,
+    stacked: bool,
     max_rows: Option<u32>,
     delimiter: Option<char>,
+    ext_map: Vec<(String, char)>,
+    comment_char: Option<char>,
+    decimal_comma: bool,
+    whitespace: bool,
+    fixed_width: Option<Vec<(usize, usize)>>,
+    fwf_names: Option<Vec<String>>,
     selected_columns: Option<Vec<String>>,
+    excluded_columns: Option<Vec<String>>,
+    select_dtypes: Option<Vec<String>>,
+    filters: Vec<String>,
+    derivations: Vec<String>,
+    renames: Vec<(String, String)>,
+    snake_case_headers: bool,
+    unique: bool,
+    unique_by: Option<Vec<String>>,
+    keep_last: bool,
+    drop_nulls: Option<Vec<String>>,
+    only_nulls: Option<Vec<String>>,
+    grep: Option<String>,
+    grep_column: Option<(String, String)>,
+    grep_regex: bool,
+    ignore_case: bool,
+    sort: Option<Vec<(String, bool)>>,
+    nulls_first: bool,
+    top: Option<(usize, String)>,
+    bottom: Option<(usize, String)>,
+    reverse: bool,
+    sql: Option<String>,
+    sheet: Option<String>,
+    list_sheets: bool,
+    row_groups: Option<Vec<usize>>,
+    table: Option<String>,
+    table_index: usize,
+    query: Option<String>,
     no_header: bool,
+    skip_rows: usize,
+    skip_rows_after_header: usize,
+    show_skipped: bool,
+    infer_schema_length: Option<usize>,
     column_names_only: bool,
+    number_columns: bool,
     describe: bool,
+    value_counts: Option<String>,
+    group_by: Option<Vec<String>>,
+    aggregates: Vec<AggregateSpec>,
     head: bool,
     tail: bool,
     sample: bool,
-    markdown: bool,
+    sample_frac: Option<f64>,
+    with_replacement: bool,
+    shuffle: bool,
+    seed: Option<u64>,
+    rows: Option<(usize, Option<usize>)>,
+    verbose: bool,
+    output: Option<String>,
+    force: bool,
+    tee: bool,
+    compression: Option<String>,
+    row_group_size: Option<usize>,
+    sheet_name: Option<String>,
+    output_format: OutputFormat,
+    output_delimiter: char,
+    html_class: Option<String>,
+    table_name: String,
+    sql_dialect: SqlDialect,
+    sql_batch: usize,
+    no_shape: bool,
+    no_dtypes: bool,
+    show_dtypes: bool,
+    null_display: String,
+    datetime_format: Option<String>,
+    local_time: bool,
+    humanize: Vec<(Humanize, String)>,
+    duration_unit: i64,
+    totals: Option<Aggregate>,
+    null_map: bool,
+    elide_repeats: Option<Vec<String>>,
+    style: TableStyle,
+    highlight: Option<Regex>,
+    binary: BinaryDisplay,
+    color: bool,
+    paging: Paging,
+    copy: bool,
+    html_null: String,
+    max_col_width: Option<usize>,
+    truncation: Truncation,
+    no_truncate: bool,
+    width: usize,
+    max_cols: Option<usize>,
+    all_cols: bool,
+    chunk_cols: bool,
+    id_column: Option<String>,
+    transpose: bool,
+    flatten_structs: bool,
+    explode: Option<String>,
+    transpose_header: Option<String>,
+    align: Option<Alignments>,
+    float_precision: Option<usize>,
+    thousands_sep: Option<char>,
+    notation: Notation,
 }
 
 // --------------------------------------------------
 fn get_args() -> CliArgs {
+    get_args_from(env::args_os())
+}
+
+// --------------------------------------------------
+// parse the arguments, the first of which is the name of the program
+fn get_args_from<I, T>(args: I) -> CliArgs
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     let args_match: ArgMatches = Command::new(crate_name!())
         .about(crate_description!())
         .version(crate_version!())
         .author(crate_authors!("\n"))
         .arg(
             Arg::new("filepath")
-                .help("The path to the file")
+                .help("The path to the file (multiple files are concatenated)")
                 .required(false)
+                .num_args(1..)
                 .default_value("-"),
         )
+        .arg(
+            Arg::new("clipboard")
+                .long("clipboard")
+                .help("Read the table from the clipboard, as tab-separated text by default")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("filepath"),
+        )
+        .arg(
+            Arg::new("exec")
+                .long("exec")
+                .value_name("COMMAND")
+                .help("Run a command through the shell and read the table from its output")
+                .conflicts_with_all(["filepath", "clipboard"]),
+        )
+        .arg(
+            Arg::new("no_glob")
+                .long("no-glob")
+                .help("Treat filepaths literally instead of expanding glob patterns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("http_header")
+                .long("http-header")
+                .value_name("NAME: VALUE")
+                .help("Header to send when reading a URL (repeatable)")
+                .value_parser(parse_http_header)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("bearer_token")
+                .long("bearer-token")
+                .value_name("TOKEN")
+                .help("Bearer token to send when reading a URL [env: PRINTBL_HTTP_TOKEN]")
+                .required(false),
+        )
+        .arg(
+            Arg::new("no_progress")
+                .long("no-progress")
+                .help("Do not show the progress of loading large inputs on stderr")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_cache")
+                .long("no-cache")
+                .help("Download URLs without caching them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("refresh")
+                .long("refresh")
+                .help("Download URLs again, replacing their cached copies")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no_cache"),
+        )
+        .arg(
+            Arg::new("cache_ttl")
+                .long("cache-ttl")
+                .value_name("SECONDS")
+                .help("Age after which cached URLs are revalidated with the server")
+                .value_parser(value_parser!(u64))
+                .default_value("3600"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .help("Format of the input, overriding detection from the file extension")
+                .value_parser(FORMAT_NAMES.map(|(name, _)| name))
+                .required(false),
+        )
+        .arg(
+            Arg::new("list_formats")
+                .long("list-formats")
+                .help("List the formats with the file extensions they are recognized by")
+                .action(ArgAction::SetTrue)
+                .exclusive(true),
+        )
+        .arg(
+            Arg::new("with_source_column")
+                .long("with-source-column")
+                .help("Add a source_file column naming the file each row came from")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("index")
+                .long("index")
+                .help("Add a row column numbering the rows from 1 as in the input, by the lines they start on in CSV and TSV files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("stacked")
+                .long("stacked")
+                .help("Print each input as its own table under a heading, instead of concatenating them")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("with_source_column"),
+        )
         .arg(
             Arg::new("max_rows")
                 .short('n')
@@ -57,14 +432,270 @@ fn get_args() -> CliArgs {
             Arg::new("delimiter")
                 .short('d')
                 .long("delimiter")
-                .help("Character used to separate columns")
+                .help("Character used to separate columns (overrides the default of --format)")
+                .long_help(
+                    "Character used to separate columns (overrides the default of --format). \
+                     Accepts a single character, the escapes \\t and \\0, or one of the \
+                     words tab, comma, semicolon, pipe, and space",
+                )
+                .value_parser(parse_delimiter)
+                .required(false),
+        )
+        .arg(
+            Arg::new("whitespace")
+                .long("whitespace")
+                .help("Split columns on runs of whitespace, as in the output of ps or df")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["delimiter", "format"]),
+        )
+        .arg(
+            Arg::new("fixed_width")
+                .long("fixed-width")
+                .value_name("RANGES")
+                .help("Slice each line into columns at these byte ranges or widths, e.g. \"0-8,9-20,21-35\"")
+                .value_parser(parse_fixed_width)
+                .conflicts_with_all(["delimiter", "format", "whitespace"]),
+        )
+        .arg(
+            Arg::new("fwf_names")
+                .long("fwf-names")
+                .value_name("NAMES")
+                .help("Names of the --fixed-width columns, when the first line does not hold them")
+                .requires("fixed_width")
+                .required(false),
+        )
+        .arg(
+            Arg::new("ext_map")
+                .long("ext-map")
+                .value_name("EXT=DELIMITER,...")
+                .help("Read files with these extensions as delimited text, e.g. \"dat=|,log=\\t\"")
+                .value_parser(parse_ext_map)
+                .required(false),
+        )
+        .arg(
+            Arg::new("decimal_comma")
+                .long("decimal-comma")
+                .help("Read numbers written with a decimal comma, e.g. 3,14 or 1.234,56")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("comment_char")
+                .long("comment-char")
+                .value_name("CHAR")
+                .help("Skip lines of delimited text that start with this character")
+                .value_parser(parse_comment_char)
                 .required(false),
         )
         .arg(
             Arg::new("select_columns")
                 .short('s')
                 .long("select")
-                .help("Columns to display")
+                .help("Columns to display, by name, by 1-based index in the file, or by a glob pattern or a regex after re:, e.g. \"id,3,5-8,sample_*\", where -1 is the last column")
+                .allow_hyphen_values(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("excluded_columns")
+                .long("exclude")
+                .help("Columns to leave out, by name, index, or pattern as in --select")
+                .allow_hyphen_values(true)
+                .conflicts_with("select_columns")
+                .required(false),
+        )
+        .arg(
+            Arg::new("select_dtype")
+                .long("select-dtype")
+                .value_name("KIND")
+                .help("Display only the columns of a kind of type, out of numeric, string, temporal, and boolean (repeatable, and a column of any of them is displayed)")
+                .value_parser(["numeric", "string", "temporal", "boolean"])
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+                .conflicts_with("select_columns"),
+        )
+        .arg(
+            Arg::new("filter")
+                .long("filter")
+                .value_name("EXPRESSION")
+                .help("Rows to print, e.g. 'price > 100 && status == \"active\"', with comparisons, &&, ||, !, parentheses, is [not] null, [not] in (...), contains, startswith, and endswith (repeatable, and all must hold)")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("rename")
+                .long("rename")
+                .value_name("OLD=NEW")
+                .help("Rename a column as soon as it is read, so that the other options use the new name, e.g. 'Total Sales (USD)=total_usd' (repeatable)")
+                .value_parser(parse_rename)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("snake_case_headers")
+                .long("snake-case-headers")
+                .help("Rename the columns in snake case as soon as they are read, trimmed, in lowercase, and with underscores for the other characters than letters and digits, e.g. total_sales_usd")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("derive")
+                .long("derive")
+                .value_name("EXPRESSION")
+                .help("Add a column computed from the others, e.g. 'margin = (revenue - cost) / revenue', with + - * / %, parentheses, and len, lower, upper, trim, substr, and abs (repeatable, and each may use the columns derived before it)")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("unique")
+                .long("unique")
+                .help("Print each row only once, leaving out the rows that repeat every printed column of an earlier row")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("unique_by"),
+        )
+        .arg(
+            Arg::new("unique_by")
+                .long("unique-by")
+                .value_name("COLUMNS")
+                .help("Columns to print each combination of values of only once, e.g. id or first_name,last_name")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("keep")
+                .long("keep")
+                .value_name("ROW")
+                .help("Row kept of those that --unique-by finds repeated")
+                .value_parser(["first", "last"])
+                .default_value("first")
+                .requires("unique_by"),
+        )
+        .arg(
+            Arg::new("drop_nulls")
+                .long("drop-nulls")
+                .value_name("COLUMNS")
+                .help("Leave out the rows with a null in any of the comma-separated columns, or in any column")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value(""),
+        )
+        .arg(
+            Arg::new("only_nulls")
+                .long("only-nulls")
+                .value_name("COLUMNS")
+                .help("Print only the rows with a null in any of the columns, e.g. to find the rows that a join did not match")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("grep")
+                .long("grep")
+                .value_name("PATTERN")
+                .help("Print only the rows with a value that contains the text in any column, as the value is printed, e.g. timeout or 404")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("grep_column")
+                .long("grep-column")
+                .value_name("COLUMN=PATTERN")
+                .help("Print only the rows with a value in the column that contains the text, e.g. message=timeout")
+                .value_parser(parse_grep_column)
+                .conflicts_with("grep"),
+        )
+        .arg(
+            Arg::new("grep_regex")
+                .long("grep-regex")
+                .help("Match the pattern of --grep or --grep-column as a regex rather than as text")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("ignore_case")
+                .short('i')
+                .long("ignore-case")
+                .help("Match the pattern of --grep or --grep-column regardless of case")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("COLUMNS")
+                .help("Columns to sort the rows by, each descending when prefixed with -, e.g. region,-revenue")
+                .value_parser(parse_sort_key)
+                .value_delimiter(',')
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_names(["K", "COLUMN"])
+                .help("Print only the k rows with the largest values of the column, e.g. --top 20 revenue, without sorting all of the rows")
+                .num_args(2)
+                .conflicts_with_all(["head", "tail", "sample", "sample_frac", "sort", "bottom"]),
+        )
+        .arg(
+            Arg::new("bottom")
+                .long("bottom")
+                .value_names(["K", "COLUMN"])
+                .help("Print only the k rows with the smallest values of the column, e.g. --bottom 20 latency")
+                .num_args(2)
+                .conflicts_with_all(["head", "tail", "sample", "sample_frac", "sort"]),
+        )
+        .arg(
+            Arg::new("nulls_first")
+                .long("nulls-first")
+                .help("Sort the nulls before the other values rather than after them")
+                .action(ArgAction::SetTrue)
+                .requires("sort"),
+        )
+        .arg(
+            Arg::new("reverse")
+                .long("reverse")
+                .help("Print the rows in reverse order, after --sort and before -n, so that -n 20 prints the last 20 rows from the last")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["top", "bottom"]),
+        )
+        .arg(
+            Arg::new("sql")
+                .long("sql")
+                .value_name("QUERY")
+                .help("SQL query to print the result of, where each input is a table named after its file, e.g. sales for sales.csv, and the first input is also named tbl")
+                .conflicts_with_all(["query", "stacked", "select_columns", "excluded_columns", "select_dtype", "filter", "derive"]),
+        )
+        .arg(
+            Arg::new("sheet")
+                .long("sheet")
+                .value_name("NAME_OR_INDEX")
+                .help("Spreadsheet sheet to display, by 0-based index or case-insensitive name")
+                .required(false),
+        )
+        .arg(
+            Arg::new("list_sheets")
+                .long("list-sheets")
+                .help("List the sheets of a spreadsheet with their dimensions")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("sheet"),
+        )
+        .arg(
+            Arg::new("row_groups")
+                .long("row-groups")
+                .value_name("GROUPS")
+                .help("Parquet row groups to read, by 0-based index or range, e.g. \"3,7-9\"")
+                .value_parser(parse_row_groups)
+                .required(false),
+        )
+        .arg(
+            Arg::new("table")
+                .long("table")
+                .value_name("TABLE")
+                .help("Database table to display (lists the tables when omitted)")
+                .required(false),
+        )
+        .arg(
+            Arg::new("table_index")
+                .long("table-index")
+                .value_name("N")
+                .help("0-based index of the table to display from an HTML page")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("query")
+                .long("query")
+                .value_name("SQL")
+                .help("SQL query to run against a SQLite or DuckDB database, instead of reading a table")
+                .conflicts_with("table")
                 .required(false),
         )
         .arg(
@@ -73,27 +704,98 @@ fn get_args() -> CliArgs {
                 .help("Table has no header row")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("skip_rows")
+                .long("skip-rows")
+                .value_name("N")
+                .help("Number of lines before the header of delimited text to skip, e.g. the metadata above the header of an export")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("skip_rows_after_header")
+                .long("skip-rows-after-header")
+                .value_name("M")
+                .help("Number of rows between the header of delimited text and its data to skip")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("show_skipped")
+                .long("show-skipped")
+                .help("Print the lines skipped by --skip-rows and --skip-rows-after-header to stderr")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("infer_schema_length")
+                .long("infer-schema-length")
+                .value_name("N")
+                .help("Number of rows that the column types of delimited text are inferred from, 0 to read every column as text, or \"all\" [default: 100]")
+                .value_parser(parse_infer_schema_length)
+                .required(false),
+        )
         .arg(
             Arg::new("head")
                 .long("head")
-                .help("Print only the first n rows")
+                .help("Print only the first n rows, or with --tail the first and last n rows")
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["sample", "tail"]),
+                .conflicts_with_all(["sample", "sample_frac"]),
         )
         .arg(
             Arg::new("tail")
                 .long("tail")
                 .help("Print only the last n rows")
                 .action(ArgAction::SetTrue)
-                .conflicts_with_all(["head", "sample"]),
+                .conflicts_with_all(["sample", "sample_frac"]),
         )
         .arg(
             Arg::new("sample")
                 .long("sample")
                 .help("Print only a random subset of n rows")
                 .action(ArgAction::SetTrue)
+                .groups(["random", "sampled"])
                 .conflicts_with_all(["head", "tail"]),
         )
+        .arg(
+            Arg::new("sample_frac")
+                .long("sample-frac")
+                .value_name("FRACTION")
+                .help("Print only a random subset of this fraction of the rows, e.g. 0.01 for 1% of them")
+                .value_parser(parse_sample_fraction)
+                .groups(["random", "sampled"])
+                .conflicts_with_all(["head", "tail", "sample", "max_rows"]),
+        )
+        .arg(
+            Arg::new("with_replacement")
+                .long("with-replacement")
+                .help("Draw the rows of --sample or --sample-frac with replacement, so that a row can be drawn more than once, as for a bootstrap")
+                .action(ArgAction::SetTrue)
+                .requires("sampled"),
+        )
+        .arg(
+            Arg::new("shuffle")
+                .long("shuffle")
+                .help("Print all of the rows in a random order, or the first n of them")
+                .action(ArgAction::SetTrue)
+                .group("random")
+                .conflicts_with_all(["head", "tail", "sample", "sample_frac"]),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("N")
+                .help("Seed of the random order of --sample and --shuffle, so that the same seed picks the same rows of the same file")
+                .value_parser(clap::value_parser!(u64))
+                .requires("random"),
+        )
+        .arg(
+            Arg::new("rows")
+                .long("rows")
+                .value_name("START:END")
+                .help("Rows to print, numbered from 1 after the header, e.g. 10000:10050, 500: for the rows from the 500th, or :200 for the first 200 (both ends included)")
+                .value_parser(parse_row_range)
+                .conflicts_with_all(["head", "tail", "sample", "sample_frac"]),
+        )
         .arg(
             Arg::new("describe")
                 .short('D')
@@ -102,281 +804,4661 @@ fn get_args() -> CliArgs {
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("markdown")
-                .short('m')
-                .long("markdown")
-                .help("Format print for markdown documents")
-                .action(ArgAction::SetTrue),
+            Arg::new("value_counts")
+                .long("value-counts")
+                .value_name("COLUMN")
+                .help("Print how often each value of the column occurs, from the most common, with nulls counted as a value and -n limiting the values printed")
+                .conflicts_with_all(["describe", "tail", "sample", "sample_frac", "explode"]),
         )
         .arg(
-            Arg::new("column_names_only")
-                .short('c')
-                .long("column-names-only")
-                .help("Get column names")
-                .action(ArgAction::SetTrue)
+            Arg::new("group_by")
+                .long("group-by")
+                .value_name("COLUMNS")
+                .help("Columns to group the rows by, printing a row of the --agg aggregates for each group, from the largest first aggregate and with -n limiting the groups printed")
+                .value_delimiter(',')
                 .conflicts_with_all([
-                    "head",
+                    "value_counts",
+                    "describe",
                     "tail",
                     "sample",
-                    "max_rows",
+                    "sample_frac",
+                    "explode",
                     "select_columns",
-                    "no_header",
-                    "describe",
-                    "markdown",
+                    "excluded_columns",
+                    "select_dtype",
                 ]),
         )
-        .get_matches();
-
-    CliArgs {
-        filepath: args_match
-            .get_one::<String>("filepath")
-            .expect("Filepath is required")
-            .clone(),
-        max_rows: args_match.get_one::<u32>("max_rows").copied(),
-        delimiter: args_match.get_one::<char>("delimiter").copied(),
-        selected_columns: args_match
-            .get_one::<String>("select_columns")
-            .map(|s| s.split(',').map(String::from).collect()),
-        no_header: args_match.get_flag("no_header"),
-        column_names_only: args_match.get_flag("column_names_only"),
-        describe: args_match.get_flag("describe"),
-        head: args_match.get_flag("head"),
-        tail: args_match.get_flag("tail"),
-        sample: args_match.get_flag("sample"),
-        markdown: args_match.get_flag("markdown"),
-    }
-}
-
-// --------------------------------------------------
-// get extension from filepath
-// adapted from https://stackoverflow.com/a/45292067
-fn get_format_from_filename(filename: &str) -> Option<&FileFormat> {
-    let file_extension = Path::new(filename).extension().and_then(OsStr::to_str);
-    match file_extension {
-        Some("csv") => Some(&FileFormat::Csv),
-        Some("tsv") => Some(&FileFormat::Tsv),
-        Some("parquet") => Some(&FileFormat::Parquet),
-        _ => None,
-    }
-}
-
-// --------------------------------------------------
-// determine delimiter based on file extension
-fn get_default_delimiter(format: Option<&FileFormat>) -> char {
-    match format {
-        Some(&FileFormat::Tsv) => '\t',
-        _ => ',',
-    }
-}
-
-// --------------------------------------------------
-// get the number of rows to parse
-fn get_num_rows_to_parse(
-    max_rows: Option<u32>,
-    head: bool,
-    tail: bool,
-    sample: bool,
-    column_names_only: bool,
-) -> Option<usize> {
-    if column_names_only {
-        return Some(1);
-    }
-
-    if tail || sample {
-        return None;
-    }
-
-    if let Some(n_rows) = max_rows {
-        return Some(n_rows as usize);
-    }
-
-    if head {
-        return Some(10);
-    }
-
-    None
-}
-
-// --------------------------------------------------
-// get delimiter to use
-fn get_delimiter(file_format: Option<&FileFormat>, delimiter: Option<char>) -> char {
-    if let Some(character) = delimiter {
-        return character;
-    }
-
-    let delimiter = get_default_delimiter(file_format);
-
-    delimiter
-}
-
-// --------------------------------------------------
-// adapted from https://stackoverflow.com/a/77156312/11392276
-fn parse_from_stdin(
-    select_columns: Option<Vec<String>>,
-    n_rows: Option<usize>,
-    delimiter: char,
-    has_header: bool,
-) -> DataFrame {
-    let mut v = Vec::<u8>::new();
-    let _reader = std::io::stdin()
-        .lock()
-        .read_to_end(&mut v)
-        .expect("cannot read from stdin");
-
-    let cursor = std::io::Cursor::new(v);
-    let file = Box::new(cursor) as Box<dyn MmapBytesReader>;
-
-    CsvReader::new(file)
-        .with_separator(delimiter as u8)
-        .has_header(has_header)
-        .with_columns(select_columns)
-        .with_n_rows(n_rows)
-        .finish()
-        .expect("Unable to parse table from stdin")
-}
-
-// --------------------------------------------------
-// parse csv and tsv files
-fn parse_csv_file(
-    filepath: &str,
-    select_columns: Option<Vec<String>>,
-    n_rows: Option<usize>,
-    delimiter: char,
+        .arg(
+            Arg::new("agg")
+                .long("agg")
+                .value_name("AGGREGATES")
+                .help("Aggregates of the groups of --group-by, e.g. 'sum(revenue),mean(latency),count()', out of sum, mean, min, max, median, count, and n_unique")
+                .value_parser(parse_aggregates)
+                .default_value("count()")
+                .requires("group_by"),
+        )
+        .arg(
+            Arg::new("markdown")
+                .short('m')
+                .long("markdown")
+                .help("Format print for markdown documents, as --output-format markdown")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max_col_width")
+                .long("max-col-width")
+                .value_name("WIDTH")
+                .help("Truncate the values and column names of the printed table to WIDTH characters [default: 32]")
+                .value_parser(clap::value_parser!(u16).range(1..))
+                .required(false),
+        )
+        .arg(
+            Arg::new("truncate")
+                .long("truncate")
+                .value_name("WHERE")
+                .help("Where the ellipsis of a truncated value goes, e.g. middle for file paths [default: end]")
+                .value_parser(["start", "middle", "end"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("no_truncate")
+                .long("no-truncate")
+                .help("Print values and column names in full")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["max_col_width", "truncate"]),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .value_name("N")
+                .help("Fit the printed table to N characters [default: the width of the terminal, or 120]")
+                .value_parser(clap::value_parser!(u16).range(1..))
+                .required(false),
+        )
+        .arg(
+            Arg::new("max_cols")
+                .long("max-cols")
+                .value_name("N")
+                .help("Print at most the first N columns of the table")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .required(false),
+        )
+        .arg(
+            Arg::new("all_cols")
+                .long("all-cols")
+                .help("Print every column of the table, however wide")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["width", "max_cols"]),
+        )
+        .arg(
+            Arg::new("chunk_cols")
+                .long("chunk-cols")
+                .help("Print the columns that do not fit the width in further tables under the first, instead of leaving them out")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["max_cols", "all_cols"]),
+        )
+        .arg(
+            Arg::new("id_column")
+                .long("id-column")
+                .value_name("COLUMN")
+                .help("Column repeated on the left of each table printed by --chunk-cols, to tell the rows apart")
+                .requires("chunk_cols")
+                .required(false),
+        )
+        .arg(
+            Arg::new("transpose")
+                .long("transpose")
+                .help("Print the columns as rows, for summaries with few rows and many columns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("transpose_header")
+                .long("transpose-header")
+                .value_name("COLUMN")
+                .help("Column whose values name the columns of the --transpose table [default: describe with --describe]")
+                .requires("transpose")
+                .required(false),
+        )
+        .arg(
+            Arg::new("flatten_structs")
+                .long("flatten-structs")
+                .help("Split the struct columns into a column per field, named e.g. address.city")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("explode")
+                .long("explode")
+                .value_name("COLUMN")
+                .help("Turn the elements of a list column into rows of their own")
+                .required(false),
+        )
+        .arg(
+            Arg::new("plain")
+                .long("plain")
+                .help("Print the table as columns aligned with spaces, without borders, as --output-format plain [default when not printing to a terminal]")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["markdown", "output_format", "vertical"]),
+        )
+        .arg(
+            Arg::new("pretty")
+                .long("pretty")
+                .help("Print the table with borders even when not printing to a terminal, as --output-format table")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["markdown", "output_format", "vertical", "plain"]),
+        )
+        .arg(
+            Arg::new("vertical")
+                .long("vertical")
+                .help("Print each row as a block of name: value lines, for wide tables")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["markdown", "output_format"]),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Report detected input settings on stderr")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write the table to a file, in the format given by its extension, instead of printing it")
+                .conflicts_with_all(["stacked", "list_sheets"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("output_format")
+                .long("output-format")
+                .value_name("FORMAT")
+                .help("Print the table as a pretty table, aligned plain text, a markdown, org, reStructuredText, AsciiDoc, or Typst table, delimited text, a JSON array of rows, JSON lines, an HTML table, a LaTeX tabular, SQL insert statements, or an Arrow IPC stream")
+                .value_parser(["table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "csv", "tsv", "json", "jsonl", "html", "latex", "sql", "arrow-stream"])
+                .default_value("table")
+                .conflicts_with_all(["markdown", "stacked"]),
+        )
+        .arg(
+            Arg::new("output_delimiter")
+                .long("output-delimiter")
+                .value_name("DELIMITER")
+                .help("Delimiter of --output-format csv, e.g. \"|\" [default: , or tab for tsv]")
+                .value_parser(parse_delimiter)
+                .required(false),
+        )
+        .arg(
+            Arg::new("no_shape")
+                .long("no-shape")
+                .help("Leave out the shape footer of the printed table")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_dtypes")
+                .long("no-dtypes")
+                .help("Leave out the row of column types of the printed table")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("show_dtypes")
+                .long("show-dtypes")
+                .help("Print the column types of markdown tables, as a first row")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no_dtypes"),
+        )
+        .arg(
+            Arg::new("null_display")
+                .long("null-display")
+                .value_name("TEXT")
+                .help("Text of the nulls in the printed table, e.g. \"∅\" or \"\" [default: null]")
+                .required(false),
+        )
+        .arg(
+            Arg::new("datetime_format")
+                .long("datetime-format")
+                .value_name("FORMAT")
+                .help("strftime format of the dates, datetimes, and times of the printed table, e.g. \"%Y-%m-%d %H:%M\"")
+                .value_parser(parse_datetime_format)
+                .required(false),
+        )
+        .arg(
+            Arg::new("local_time")
+                .long("local-time")
+                .help("Print the datetimes with a time zone in the local time zone")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("humanize")
+                .long("humanize")
+                .value_name("KIND:COLUMN,...")
+                .help("Print columns of durations or byte counts as e.g. \"2h 13m 5s\" or \"1.4 GiB\" in the printed table, e.g. \"duration:elapsed,bytes:size\"")
+                .value_parser(parse_humanize)
+                .required(false),
+        )
+        .arg(
+            Arg::new("duration_unit")
+                .long("duration-unit")
+                .value_name("UNIT")
+                .help("Unit of the integer columns humanized as durations")
+                .value_parser(["s", "ms", "us"])
+                .default_value("s"),
+        )
+        .arg(
+            Arg::new("totals")
+                .long("totals")
+                .value_name("AGGREGATE")
+                .help("Print a footer row with the sum, mean, min, max, or count of each numeric column of the printed table")
+                .value_parser(["sum", "mean", "min", "max", "count"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .value_name("STYLE")
+                .help("Borders of the pretty table: rounded, sharp, double, heavy, ascii (only 7-bit characters), or borderless [env: PRINTBL_STYLE] [default: rounded]")
+                .value_parser(["rounded", "sharp", "double", "heavy", "ascii", "borderless"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("null_map")
+                .long("null-map")
+                .help("Print a marker in place of each value, telling whether it is null, and the number of nulls of each column below")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["totals", "humanize"]),
+        )
+        .arg(
+            Arg::new("elide_repeats")
+                .long("elide-repeats")
+                .value_name("COLUMNS")
+                .help("Leave out (or dim, in color) the values equal to the one above them, in the comma-separated columns or in all of them")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value(""),
+        )
+        .arg(
+            Arg::new("highlight")
+                .long("highlight")
+                .value_name("TEXT")
+                .help("Highlight the cells containing the text in the colored table and records")
+                .required(false),
+        )
+        .arg(
+            Arg::new("highlight_regex")
+                .long("highlight-regex")
+                .value_name("PATTERN")
+                .help("Highlight the cells matching the regular expression in the colored table and records")
+                .value_parser(parse_regex)
+                .conflicts_with("highlight")
+                .required(false),
+        )
+        .arg(
+            Arg::new("binary")
+                .long("binary")
+                .value_name("DISPLAY")
+                .help("How the binary values of the printed table are shown: hex (of the bytes that fit, and the length), full-hex, base64, or hidden (only the length)")
+                .value_parser(["hex", "full-hex", "base64", "hidden"])
+                .default_value("hex"),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("Color the header, nulls, negative numbers, and booleans of the printed table: auto (when printing to a terminal, unless NO_COLOR is set), always, or never")
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("pager")
+                .long("pager")
+                .value_name("WHEN")
+                .help("Page the printed table through $PAGER [default: less -SRX]: auto (when it has more rows than the terminal), always, or never, and only when printing to a terminal")
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("copy")
+                .long("copy")
+                .help("Copy the printed table to the clipboard as well, without its colors")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("html_class")
+                .long("html-class")
+                .value_name("CLASS")
+                .help("Class attribute of the table printed by --output-format html")
+                .required(false),
+        )
+        .arg(
+            Arg::new("html_null")
+                .long("html-null")
+                .value_name("TEXT")
+                .help("Text of null cells in the table printed by --output-format html [default: empty]")
+                .required(false),
+        )
+        .arg(
+            Arg::new("force_binary_stdout")
+                .long("force-binary-stdout")
+                .help("Write the stream of --output-format arrow-stream even when stdout is a terminal")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("table_name")
+                .long("table-name")
+                .value_name("TABLE")
+                .help("Table that the statements printed by --output-format sql insert into, e.g. \"users\" or \"public.users\"")
+                .default_value("data"),
+        )
+        .arg(
+            Arg::new("sql_dialect")
+                .long("sql-dialect")
+                .value_name("DIALECT")
+                .help("Database that the statements printed by --output-format sql are meant for, which decides how names are quoted and booleans are written")
+                .value_parser(["postgres", "sqlite", "mysql"])
+                .default_value("postgres"),
+        )
+        .arg(
+            Arg::new("sql_batch")
+                .long("sql-batch")
+                .value_name("ROWS")
+                .help("Number of rows inserted by each statement printed by --output-format sql")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .default_value("500"),
+        )
+        .arg(
+            Arg::new("align")
+                .long("align")
+                .value_name("ALIGNMENTS")
+                .help("Alignment (l, c, or r) of the named columns of the printed table, e.g. \"price:r,name:l\", or of each column, e.g. \"lrr\" [default: r for numbers, c for booleans, l otherwise]")
+                .value_parser(parse_alignments)
+                .required(false),
+        )
+        .arg(
+            Arg::new("float_precision")
+                .long("float-precision")
+                .value_name("DECIMALS")
+                .help("Number of decimals of the floats of the printed tables and the HTML and LaTeX output")
+                .value_parser(clap::value_parser!(usize))
+                .required(false),
+        )
+        .arg(
+            Arg::new("thousands_sep")
+                .long("thousands-sep")
+                .value_name("SEPARATOR")
+                .help("Separator of the thousands of the numbers in the printed table, e.g. \",\"")
+                .value_parser(parse_thousands_separator)
+                .required(false),
+        )
+        .arg(
+            Arg::new("scientific")
+                .long("scientific")
+                .help("Print the floats of the table in scientific notation")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_scientific")
+                .long("no-scientific")
+                .help("Print the floats of the table in full, never in scientific notation")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("scientific"),
+        )
+        .arg(
+            Arg::new("scientific_above")
+                .long("scientific-above")
+                .value_name("MAGNITUDE")
+                .help("Print the floats of the table from MAGNITUDE on (or below 1/MAGNITUDE) in scientific notation, e.g. 1e9")
+                .value_parser(parse_magnitude)
+                .conflicts_with_all(["scientific", "no_scientific"]),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Overwrite the --output file if it exists")
+                .action(ArgAction::SetTrue)
+                .requires("output"),
+        )
+        .arg(
+            Arg::new("tee")
+                .long("tee")
+                .help("Print the table as well as writing it to the --output file")
+                .action(ArgAction::SetTrue)
+                .requires("output"),
+        )
+        .arg(
+            Arg::new("compression")
+                .long("compression")
+                .value_name("CODEC")
+                .help("Compression of the --output parquet file [default: zstd]")
+                .value_parser(["zstd", "snappy", "lz4", "gzip", "brotli", "uncompressed"])
+                .requires("output"),
+        )
+        .arg(
+            Arg::new("row_group_size")
+                .long("row-group-size")
+                .value_name("N")
+                .help("Number of rows of each row group of the --output parquet file")
+                .value_parser(clap::value_parser!(usize))
+                .requires("output"),
+        )
+        .arg(
+            Arg::new("sheet_name")
+                .long("sheet-name")
+                .value_name("NAME")
+                .help("Name of the sheet of the --output xlsx file [default: Sheet1]")
+                .requires("output"),
+        )
+        .arg(
+            Arg::new("number_columns")
+                .long("number-columns")
+                .help("Prefix each column name of the printed table (and of --column-names-only) with its 1-based index in the file, e.g. 3:price")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("transpose"),
+        )
+        .arg(
+            Arg::new("column_names_only")
+                .short('c')
+                .long("column-names-only")
+                .help("Get column names")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "head",
+                    "tail",
+                    "sample",
+                    "sample_frac",
+                    "rows",
+                    "max_rows",
+                    "select_columns",
+                    "excluded_columns",
+                    "select_dtype",
+                    "filter",
+                    "derive",
+                    "unique",
+                    "unique_by",
+                    "drop_nulls",
+                    "only_nulls",
+                    "grep",
+                    "grep_column",
+                    "sort",
+                    "top",
+                    "bottom",
+                    "sql",
+                    "no_header",
+                    "describe",
+                    "markdown",
+                    "output",
+                ]),
+        )
+        .get_matches_from(args);
+
+    if args_match.get_flag("list_formats") {
+        list_formats();
+        std::process::exit(0);
+    }
+
+    let fixed_width = args_match
+        .get_one::<Vec<(usize, usize)>>("fixed_width")
+        .cloned();
+    let fwf_names: Option<Vec<String>> = args_match
+        .get_one::<String>("fwf_names")
+        .map(|s| s.split(',').map(String::from).collect());
+    if args_match.get_flag("decimal_comma") && args_match.get_one::<char>("delimiter") == Some(&',')
+    {
+        exit_with_error("--decimal-comma cannot be combined with a comma delimiter");
+    }
+    if let (Some(ranges), Some(names)) = (&fixed_width, &fwf_names) {
+        if ranges.len() != names.len() {
+            exit_with_error(&format!(
+                "--fwf-names has {} names for {} --fixed-width columns",
+                names.len(),
+                ranges.len()
+            ));
+        }
+    }
+
+    // tsv is csv output with a tab delimiter
+    // -m is kept as a shorthand of --output-format markdown, alongside the
+    // other shorthands. tables printed to a file or a pipe are plain unless a
+    // format is chosen
+    let shorthands = [
+        ("markdown", "markdown"),
+        ("vertical", "vertical"),
+        ("plain", "plain"),
+        ("pretty", "table"),
+    ];
+    let output_format = shorthands
+        .iter()
+        .find(|(flag, _)| args_match.get_flag(flag))
+        .map(|(_, format)| *format)
+        .or_else(|| match args_match.value_source("output_format") {
+            Some(ValueSource::DefaultValue) if !std::io::stdout().is_terminal() => Some("plain"),
+            _ => args_match
+                .get_one::<String>("output_format")
+                .map(String::as_str),
+        });
+    // the options of an output format need that format
+    let output_options: [(&str, &[&str]); 28] = [
+        ("output_delimiter", &["csv", "tsv"]),
+        ("html_class", &["html"]),
+        ("html_null", &["html"]),
+        ("force_binary_stdout", &["arrow-stream"]),
+        ("table_name", &["sql"]),
+        ("sql_dialect", &["sql"]),
+        ("sql_batch", &["sql"]),
+        (
+            "align",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "latex",
+            ],
+        ),
+        ("max_col_width", &["table", "plain", "vertical"]),
+        ("truncate", &["table", "plain", "vertical"]),
+        ("thousands_sep", &["table", "plain"]),
+        (
+            "null_display",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        ("scientific_above", &["table", "plain"]),
+        (
+            "datetime_format",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        (
+            "local_time",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        (
+            "humanize",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        (
+            "duration_unit",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        (
+            "totals",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+            ],
+        ),
+        (
+            "null_map",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst",
+            ],
+        ),
+        ("elide_repeats", &["table", "plain"]),
+        ("number_columns", &["table", "plain"]),
+        ("style", &["table"]),
+        (
+            "binary",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+                "html", "latex",
+            ],
+        ),
+        (
+            "float_precision",
+            &[
+                "table", "plain", "markdown", "org", "rst", "asciidoc", "typst", "vertical",
+                "html", "latex",
+            ],
+        ),
+        ("width", &["table", "plain"]),
+        ("max_cols", &["table", "plain"]),
+        ("all_cols", &["table", "plain"]),
+        ("chunk_cols", &["table", "plain"]),
+    ];
+    for (option, formats) in output_options {
+        let given = args_match.value_source(option) == Some(ValueSource::CommandLine);
+        if given && !output_format.is_some_and(|f| formats.contains(&f)) {
+            exit_with_error(&format!(
+                "--{} requires --output-format {}",
+                option.replace('_', "-"),
+                formats.join(" or ")
+            ));
+        }
+    }
+    // the binary stream would garble the terminal
+    if output_format == Some("arrow-stream")
+        && std::io::stdout().is_terminal()
+        && !args_match.get_flag("force_binary_stdout")
+    {
+        exit_with_error(
+            "Refusing to write an Arrow stream to the terminal, redirect stdout or pass --force-binary-stdout",
+        );
+    }
+    let output_delimiter = args_match
+        .get_one::<char>("output_delimiter")
+        .copied()
+        .unwrap_or(match output_format {
+            Some("tsv") => '\t',
+            _ => ',',
+        });
+
+    CliArgs {
+        filepaths: match (
+            args_match.get_flag("clipboard"),
+            args_match.contains_id("exec"),
+        ) {
+            (true, _) => vec![String::from(CLIPBOARD_PATH)],
+            (_, true) => vec![String::from(EXEC_PATH)],
+            _ => args_match
+                .get_many::<String>("filepath")
+                .expect("Filepath is required")
+                .cloned()
+                .collect(),
+        },
+        exec: args_match.get_one::<String>("exec").cloned(),
+        no_glob: args_match.get_flag("no_glob"),
+        http_headers: args_match
+            .get_many::<(String, String)>("http_header")
+            .map(|headers| headers.cloned().collect())
+            .unwrap_or_default(),
+        bearer_token: args_match
+            .get_one::<String>("bearer_token")
+            .cloned()
+            .or_else(|| env::var("PRINTBL_HTTP_TOKEN").ok()),
+        no_cache: args_match.get_flag("no_cache"),
+        no_progress: args_match.get_flag("no_progress"),
+        refresh: args_match.get_flag("refresh"),
+        cache_ttl: *args_match
+            .get_one::<u64>("cache_ttl")
+            .expect("--cache-ttl has a default value"),
+        format: args_match
+            .get_one::<String>("format")
+            .and_then(|name| get_format_from_name(name)),
+        with_source_column: args_match.get_flag("with_source_column"),
+        index: args_match.get_flag("index"),
+        stacked: args_match.get_flag("stacked"),
+        max_rows: args_match.get_one::<u32>("max_rows").copied(),
+        delimiter: args_match.get_one::<char>("delimiter").copied(),
+        ext_map: args_match
+            .get_one::<Vec<(String, char)>>("ext_map")
+            .cloned()
+            .unwrap_or_default(),
+        comment_char: args_match.get_one::<char>("comment_char").copied(),
+        decimal_comma: args_match.get_flag("decimal_comma"),
+        whitespace: args_match.get_flag("whitespace"),
+        fixed_width,
+        fwf_names,
+        selected_columns: args_match
+            .get_one::<String>("select_columns")
+            .map(|s| s.split(',').map(String::from).collect()),
+        excluded_columns: args_match
+            .get_one::<String>("excluded_columns")
+            .map(|s| s.split(',').map(String::from).collect()),
+        select_dtypes: args_match
+            .get_many::<String>("select_dtype")
+            .map(|kinds| kinds.cloned().collect()),
+        filters: args_match
+            .get_many::<String>("filter")
+            .map(|filters| filters.cloned().collect())
+            .unwrap_or_default(),
+        derivations: args_match
+            .get_many::<String>("derive")
+            .map(|derivations| derivations.cloned().collect())
+            .unwrap_or_default(),
+        renames: args_match
+            .get_many::<(String, String)>("rename")
+            .map(|renames| renames.cloned().collect())
+            .unwrap_or_default(),
+        snake_case_headers: args_match.get_flag("snake_case_headers"),
+        unique: args_match.get_flag("unique"),
+        unique_by: args_match
+            .get_many::<String>("unique_by")
+            .map(|columns| columns.cloned().collect()),
+        keep_last: args_match.get_one::<String>("keep").map(String::as_str) == Some("last"),
+        drop_nulls: args_match
+            .get_one::<String>("drop_nulls")
+            .map(|s| match s.is_empty() {
+                true => Vec::new(),
+                false => s.split(',').map(String::from).collect(),
+            }),
+        only_nulls: args_match
+            .get_many::<String>("only_nulls")
+            .map(|columns| columns.cloned().collect()),
+        grep: args_match.get_one::<String>("grep").cloned(),
+        grep_column: args_match
+            .get_one::<(String, String)>("grep_column")
+            .cloned(),
+        grep_regex: args_match.get_flag("grep_regex"),
+        ignore_case: args_match.get_flag("ignore_case"),
+        sort: args_match
+            .get_many::<(String, bool)>("sort")
+            .map(|keys| keys.cloned().collect()),
+        nulls_first: args_match.get_flag("nulls_first"),
+        top: get_top_rows(&args_match, "top"),
+        bottom: get_top_rows(&args_match, "bottom"),
+        reverse: args_match.get_flag("reverse"),
+        sql: args_match.get_one::<String>("sql").cloned(),
+        sheet: args_match.get_one::<String>("sheet").cloned(),
+        list_sheets: args_match.get_flag("list_sheets"),
+        row_groups: args_match.get_one::<Vec<usize>>("row_groups").cloned(),
+        table: args_match.get_one::<String>("table").cloned(),
+        table_index: *args_match.get_one::<usize>("table_index").unwrap_or(&0),
+        query: args_match.get_one::<String>("query").cloned(),
+        no_header: args_match.get_flag("no_header"),
+        skip_rows: *args_match.get_one::<usize>("skip_rows").unwrap(),
+        skip_rows_after_header: *args_match
+            .get_one::<usize>("skip_rows_after_header")
+            .unwrap(),
+        show_skipped: args_match.get_flag("show_skipped"),
+        infer_schema_length: args_match
+            .get_one::<Option<usize>>("infer_schema_length")
+            .copied()
+            .unwrap_or(Some(100)),
+        column_names_only: args_match.get_flag("column_names_only"),
+        number_columns: args_match.get_flag("number_columns"),
+        describe: args_match.get_flag("describe"),
+        value_counts: args_match.get_one::<String>("value_counts").cloned(),
+        group_by: args_match
+            .get_many::<String>("group_by")
+            .map(|columns| columns.cloned().collect()),
+        aggregates: args_match
+            .get_one::<Vec<AggregateSpec>>("agg")
+            .cloned()
+            .unwrap_or_default(),
+        head: args_match.get_flag("head"),
+        tail: args_match.get_flag("tail"),
+        sample: args_match.get_flag("sample"),
+        sample_frac: args_match.get_one::<f64>("sample_frac").copied(),
+        with_replacement: args_match.get_flag("with_replacement"),
+        shuffle: args_match.get_flag("shuffle"),
+        seed: args_match.get_one::<u64>("seed").copied(),
+        rows: args_match
+            .get_one::<(usize, Option<usize>)>("rows")
+            .copied(),
+        verbose: args_match.get_flag("verbose"),
+        output: args_match.get_one::<String>("output").cloned(),
+        force: args_match.get_flag("force"),
+        tee: args_match.get_flag("tee"),
+        compression: args_match.get_one::<String>("compression").cloned(),
+        row_group_size: args_match.get_one::<usize>("row_group_size").copied(),
+        sheet_name: args_match.get_one::<String>("sheet_name").cloned(),
+        output_format: match output_format {
+            Some("csv" | "tsv") => OutputFormat::Csv,
+            Some("json") => OutputFormat::Json,
+            Some("jsonl") => OutputFormat::JsonLines,
+            Some("plain") => OutputFormat::Plain,
+            Some("markdown") => OutputFormat::Markdown,
+            Some("org") => OutputFormat::Org,
+            Some("rst") => OutputFormat::Rst,
+            Some("asciidoc") => OutputFormat::AsciiDoc,
+            Some("typst") => OutputFormat::Typst,
+            Some("vertical") => OutputFormat::Vertical,
+            Some("html") => OutputFormat::Html,
+            Some("latex") => OutputFormat::Latex,
+            Some("sql") => OutputFormat::Sql,
+            Some("arrow-stream") => OutputFormat::ArrowStream,
+            _ => OutputFormat::Table,
+        },
+        output_delimiter,
+        no_shape: args_match.get_flag("no_shape"),
+        no_dtypes: args_match.get_flag("no_dtypes"),
+        show_dtypes: args_match.get_flag("show_dtypes"),
+        null_display: args_match
+            .get_one::<String>("null_display")
+            .cloned()
+            .unwrap_or_else(|| String::from("null")),
+        datetime_format: args_match.get_one::<String>("datetime_format").cloned(),
+        local_time: args_match.get_flag("local_time"),
+        humanize: args_match
+            .get_one::<Vec<(Humanize, String)>>("humanize")
+            .cloned()
+            .unwrap_or_default(),
+        // in nanoseconds
+        duration_unit: match args_match
+            .get_one::<String>("duration_unit")
+            .map(String::as_str)
+        {
+            Some("ms") => 1_000_000,
+            Some("us") => 1_000,
+            _ => 1_000_000_000,
+        },
+        totals: match args_match.get_one::<String>("totals").map(String::as_str) {
+            Some("sum") => Some(Aggregate::Sum),
+            Some("mean") => Some(Aggregate::Mean),
+            Some("min") => Some(Aggregate::Min),
+            Some("max") => Some(Aggregate::Max),
+            Some("count") => Some(Aggregate::Count),
+            _ => None,
+        },
+        null_map: args_match.get_flag("null_map"),
+        elide_repeats: args_match
+            .get_one::<String>("elide_repeats")
+            .map(|s| match s.is_empty() {
+                true => Vec::new(),
+                false => s.split(',').map(String::from).collect(),
+            }),
+        style: match args_match
+            .get_one::<String>("style")
+            .cloned()
+            .or_else(|| env::var("PRINTBL_STYLE").ok())
+            .as_deref()
+        {
+            None | Some("rounded") => TableStyle::Rounded,
+            Some("sharp") => TableStyle::Sharp,
+            Some("double") => TableStyle::Double,
+            Some("heavy") => TableStyle::Heavy,
+            Some("ascii") => TableStyle::Ascii,
+            Some("borderless") => TableStyle::Borderless,
+            Some(style) => exit_with_error(&format!(
+                "PRINTBL_STYLE: expected rounded, sharp, double, heavy, ascii, or borderless, got \"{}\"",
+                style
+            )),
+        },
+        binary: match args_match.get_one::<String>("binary").map(String::as_str) {
+            Some("full-hex") => BinaryDisplay::FullHex,
+            Some("base64") => BinaryDisplay::Base64,
+            Some("hidden") => BinaryDisplay::Hidden,
+            _ => BinaryDisplay::Hex,
+        },
+        highlight: match args_match.get_one::<String>("highlight") {
+            Some(text) => Some(Regex::new(&regex::escape(text)).expect("escaped text is a regex")),
+            None => args_match.get_one::<Regex>("highlight_regex").cloned(),
+        },
+        color: match args_match.get_one::<String>("color").map(String::as_str) {
+            Some("always") => true,
+            Some("never") => false,
+            _ => std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        },
+        // the copied output and the arrow stream are not paged
+        paging: match args_match.get_one::<String>("pager").map(String::as_str) {
+            _ if !std::io::stdout().is_terminal()
+                || args_match.get_flag("copy")
+                || output_format == Some("arrow-stream") =>
+            {
+                Paging::Never
+            }
+            Some("always") => Paging::Always,
+            Some("never") => Paging::Never,
+            _ => Paging::Auto,
+        },
+        copy: args_match.get_flag("copy"),
+        html_class: args_match.get_one::<String>("html_class").cloned(),
+        table_name: args_match
+            .get_one::<String>("table_name")
+            .cloned()
+            .expect("the table name has a default"),
+        sql_dialect: match args_match
+            .get_one::<String>("sql_dialect")
+            .map(String::as_str)
+        {
+            Some("sqlite") => SqlDialect::Sqlite,
+            Some("mysql") => SqlDialect::Mysql,
+            _ => SqlDialect::Postgres,
+        },
+        sql_batch: args_match
+            .get_one::<u32>("sql_batch")
+            .map(|&n| n as usize)
+            .expect("the batch size has a default"),
+        html_null: args_match
+            .get_one::<String>("html_null")
+            .cloned()
+            .unwrap_or_default(),
+        max_col_width: args_match
+            .get_one::<u16>("max_col_width")
+            .map(|width| *width as usize),
+        truncation: match args_match.get_one::<String>("truncate").map(String::as_str) {
+            Some("start") => Truncation::Start,
+            Some("middle") => Truncation::Middle,
+            _ => Truncation::End,
+        },
+        no_truncate: args_match.get_flag("no_truncate"),
+        width: args_match
+            .get_one::<u16>("width")
+            .map(|width| *width as usize)
+            .or_else(get_terminal_width)
+            .unwrap_or(DEFAULT_WIDTH),
+        max_cols: args_match.get_one::<u32>("max_cols").map(|n| *n as usize),
+        all_cols: args_match.get_flag("all_cols"),
+        chunk_cols: args_match.get_flag("chunk_cols"),
+        id_column: args_match.get_one::<String>("id_column").cloned(),
+        transpose: args_match.get_flag("transpose"),
+        transpose_header: args_match.get_one::<String>("transpose_header").cloned(),
+        flatten_structs: args_match.get_flag("flatten_structs"),
+        explode: args_match.get_one::<String>("explode").cloned(),
+        align: args_match.get_one::<Alignments>("align").cloned(),
+        float_precision: args_match.get_one::<usize>("float_precision").copied(),
+        thousands_sep: args_match.get_one::<char>("thousands_sep").copied(),
+        notation: match args_match.get_one::<f64>("scientific_above") {
+            Some(magnitude) => Notation::ScientificAbove(*magnitude),
+            None if args_match.get_flag("scientific") => Notation::Scientific,
+            None if args_match.get_flag("no_scientific") => Notation::Fixed,
+            None => Notation::Auto,
+        },
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --sample-frac, a fraction of the rows above 0 and up
+// to 1
+fn parse_sample_fraction(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(fraction),
+        _ => Err(String::from(
+            "expected a fraction of the rows above 0 and up to 1, e.g. 0.01",
+        )),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --infer-schema-length, where "all" infers the column
+// types from every row
+fn parse_infer_schema_length(value: &str) -> Result<Option<usize>, String> {
+    match value {
+        "all" => Ok(None),
+        _ => value
+            .parse::<usize>()
+            .map(Some)
+            .map_err(|_| String::from("expected a number of rows, or \"all\"")),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --thousands-sep, a single character other than a digit
+fn parse_thousands_separator(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(separator), None) if separator.is_ascii() && !separator.is_ascii_digit() => {
+            Ok(separator)
+        }
+        _ => Err(String::from("expected a single character, e.g. \",\"")),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --scientific-above, a magnitude greater than 1
+fn parse_magnitude(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(magnitude) if magnitude > 1.0 && magnitude.is_finite() => Ok(magnitude),
+        _ => Err(String::from("expected a number greater than 1, e.g. 1e9")),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --datetime-format, a strftime format
+fn parse_datetime_format(value: &str) -> Result<String, String> {
+    match StrftimeItems::new(value).any(|item| item == Item::Error) {
+        true => Err(String::from(
+            "expected a strftime format, e.g. \"%Y-%m-%d %H:%M\"",
+        )),
+        false => Ok(value.to_string()),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --humanize into the kinds of the columns and their names
+fn parse_humanize(value: &str) -> Result<Vec<(Humanize, String)>, String> {
+    value
+        .split(',')
+        .map(|mapping| {
+            let (kind, column) = mapping
+                .split_once(':')
+                .ok_or_else(|| format!("expected KIND:COLUMN, got \"{}\"", mapping))?;
+            let humanize = match kind.trim() {
+                "duration" => Humanize::Duration,
+                "bytes" => Humanize::Bytes,
+                _ => {
+                    return Err(format!(
+                        "expected duration or bytes, got \"{}\"",
+                        kind.trim()
+                    ))
+                }
+            };
+            match column.trim() {
+                "" => Err(format!("missing column in \"{}\"", mapping)),
+                column => Ok((humanize, column.to_string())),
+            }
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// parse the value of --highlight-regex
+fn parse_regex(value: &str) -> Result<Regex, String> {
+    Regex::new(value).map_err(|e| e.to_string())
+}
+
+// --------------------------------------------------
+// parse the value of --align, COLUMN:ALIGNMENT pairs or one of l, c, or r
+// per column
+fn parse_alignments(value: &str) -> Result<Alignments, String> {
+    let parse_alignment = |c: char| match c {
+        'l' => Some(Alignment::Left),
+        'c' => Some(Alignment::Center),
+        'r' => Some(Alignment::Right),
+        _ => None,
+    };
+    if !value.contains(':') {
+        return value
+            .chars()
+            .map(parse_alignment)
+            .collect::<Option<Vec<Alignment>>>()
+            .filter(|alignments| !alignments.is_empty())
+            .map(Alignments::Columns)
+            .ok_or_else(|| {
+                String::from("expected COLUMN:ALIGNMENT pairs, e.g. \"price:r,name:l\", or one of l, c, or r per column, e.g. \"lrr\"")
+            });
+    }
+
+    value
+        .split(',')
+        .map(|mapping| {
+            let (column, alignment) = mapping
+                .rsplit_once(':')
+                .ok_or_else(|| format!("expected COLUMN:ALIGNMENT, got \"{}\"", mapping))?;
+            let alignment = alignment
+                .trim()
+                .parse::<char>()
+                .ok()
+                .and_then(parse_alignment)
+                .ok_or_else(|| format!("expected l, c, or r, got \"{}\"", alignment.trim()))?;
+            match column.trim() {
+                "" => Err(format!("missing column in \"{}\"", mapping)),
+                column => Ok((column.to_string(), alignment)),
+            }
+        })
+        .collect::<Result<Vec<(String, Alignment)>, String>>()
+        .map(Alignments::Named)
+}
+
+// --------------------------------------------------
+// parse the value of --delimiter
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    let delimiter = match value {
+        "\\t" | "tab" => '\t',
+        "\\0" => '\0',
+        "comma" => ',',
+        "semicolon" => ';',
+        "pipe" => '|',
+        "space" => ' ',
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) if character.is_ascii() => character,
+                _ => {
+                    return Err(String::from(
+                        "expected a single ASCII character, \\t, \\0, \
+                         tab, comma, semicolon, pipe, or space",
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok(delimiter)
+}
+
+// --------------------------------------------------
+// parse the value of --http-header into a name and a value
+fn parse_http_header(value: &str) -> Result<(String, String), String> {
+    match value.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(String::from(
+            "expected a header of the form \"Name: value\"",
+        )),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --comment-char
+fn parse_comment_char(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(character), None) if character.is_ascii() => Ok(character),
+        _ => Err(String::from("expected a single ASCII character")),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --ext-map into extensions and their delimiters
+fn parse_ext_map(value: &str) -> Result<Vec<(String, char)>, String> {
+    value
+        .split(',')
+        .map(|mapping| {
+            let (extension, delimiter) = mapping
+                .split_once('=')
+                .ok_or_else(|| format!("expected EXT=DELIMITER, got \"{}\"", mapping))?;
+            let extension = extension.trim().trim_start_matches('.');
+            if extension.is_empty() {
+                return Err(format!("missing extension in \"{}\"", mapping));
+            }
+            Ok((extension.to_ascii_lowercase(), parse_delimiter(delimiter)?))
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// parse the value of --fixed-width into half-open byte ranges. each column
+// is either an inclusive range START-END or a width following the previous
+// column
+fn parse_fixed_width(value: &str) -> Result<Vec<(usize, usize)>, String> {
+    let parse_offset = |offset: &str| {
+        offset
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("expected a byte offset or width, got \"{}\"", offset))
+    };
+
+    let mut ranges = Vec::<(usize, usize)>::new();
+    for column in value.split(',') {
+        let previous_end = ranges.last().map_or(0, |&(_, end)| end);
+        let range = match column.split_once('-') {
+            Some((start, end)) => (parse_offset(start)?, parse_offset(end)? + 1),
+            None => (previous_end, previous_end + parse_offset(column)?),
+        };
+        if range.1 <= range.0 {
+            return Err(format!("empty column \"{}\"", column));
+        }
+        ranges.push(range);
+    }
+
+    Ok(ranges)
+}
+
+// --------------------------------------------------
+// parse the value of --row-groups into row group indices, expanding the
+// inclusive ranges
+fn parse_row_groups(value: &str) -> Result<Vec<usize>, String> {
+    let parse_index = |index: &str| {
+        index
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("expected a row group index, got \"{}\"", index))
+    };
+
+    let mut row_groups = Vec::<usize>::new();
+    for group in value.split(',') {
+        match group.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse_index(start)?, parse_index(end)?);
+                if end < start {
+                    return Err(format!("empty range \"{}\"", group));
+                }
+                row_groups.extend(start..=end);
+            }
+            None => row_groups.push(parse_index(group)?),
+        }
+    }
+
+    Ok(row_groups)
+}
+
+// --------------------------------------------------
+// get the number of rows and the column of --top or --bottom
+fn get_top_rows(args_match: &ArgMatches, id: &str) -> Option<(usize, String)> {
+    let mut values = args_match.get_many::<String>(id)?;
+    let (k, column) = (values.next()?, values.next()?);
+    match k.parse::<usize>() {
+        Ok(k) => Some((k, column.clone())),
+        Err(_) => exit_with_error(&format!(
+            "--{} expects the number of rows before the column, got {}",
+            id, k
+        )),
+    }
+}
+
+// --------------------------------------------------
+// parse a value of --rename into the old name and the new one, split at the
+// last =, so that the old name keeps any spaces of the file
+fn parse_rename(value: &str) -> Result<(String, String), String> {
+    match value.rsplit_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.trim().is_empty() => {
+            Ok((old.to_string(), new.trim().to_string()))
+        }
+        _ => Err(String::from(
+            "expected OLD=NEW, e.g. 'Total Sales (USD)=total_usd'",
+        )),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --grep-column into the column and the pattern
+fn parse_grep_column(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((column, pattern)) if !column.is_empty() => {
+            Ok((column.to_string(), pattern.to_string()))
+        }
+        _ => Err(String::from(
+            "expected COLUMN=PATTERN, e.g. message=timeout",
+        )),
+    }
+}
+
+// --------------------------------------------------
+// parse a key of --sort into its column and whether it is descending
+fn parse_sort_key(value: &str) -> Result<(String, bool), String> {
+    let key = match value.strip_prefix('-') {
+        Some(column) => (column.to_string(), true),
+        None => (value.to_string(), false),
+    };
+    match key.0.is_empty() {
+        true => Err(format!("expected a column to sort by, got \"{}\"", value)),
+        false => Ok(key),
+    }
+}
+
+// --------------------------------------------------
+// parse the value of --rows into the first row and, unless the range is
+// open-ended, the last row, both numbered from 1
+fn parse_row_range(value: &str) -> Result<(usize, Option<usize>), String> {
+    let Some((start, end)) = value.split_once(':') else {
+        return Err(String::from("expected START:END, e.g. 10000:10050"));
+    };
+    let parse_row = |row: &str| match row.trim().parse::<usize>() {
+        Ok(0) => Err(String::from("the rows are numbered from 1")),
+        Ok(row) => Ok(row),
+        Err(_) => Err(format!("expected a row number, got \"{}\"", row)),
+    };
+
+    let first = match start.trim().is_empty() {
+        true => 1,
+        false => parse_row(start)?,
+    };
+    let last = match end.trim().is_empty() {
+        true if start.trim().is_empty() => {
+            return Err(String::from("expected START:END, e.g. 10000:10050"))
+        }
+        true => None,
+        false => Some(parse_row(end)?),
+    };
+    if last.is_some_and(|last| last < first) {
+        return Err(format!("empty range \"{}\"", value));
+    }
+
+    Ok((first, last))
+}
+
+// --------------------------------------------------
+// whitespace separated and fixed-width text is split into columns in
+// memory, so it is read like stdin and parsed as tab-separated text
+fn splits_lines(cli_args: &CliArgs) -> bool {
+    cli_args.whitespace || cli_args.fixed_width.is_some()
+}
+
+// --------------------------------------------------
+// print an error message (once the pager is quit) and exit without a panic
+// backtrace
+fn exit_with_error(message: &str) -> ! {
+    exit_with_code(message, 1)
+}
+
+// --------------------------------------------------
+// exit like exit_with_error, with a status of its own, e.g. that of a
+// failed --exec command
+fn exit_with_code(message: &str, code: i32) -> ! {
+    finish_progress();
+    wait_for_pager();
+    eprintln!("Error: {}", message);
+    std::process::exit(code);
+}
+
+// --------------------------------------------------
+// expand glob patterns that do not name an existing file
+fn expand_filepaths(filepaths: Vec<String>, no_glob: bool) -> Vec<String> {
+    if no_glob {
+        return filepaths;
+    }
+
+    let mut expanded = Vec::<String>::new();
+    for filepath in filepaths {
+        let is_pattern = filepath.contains(['*', '?', '[']);
+        if !is_pattern
+            || is_url(&filepath)
+            || is_cloud_url(&filepath)
+            || Path::new(&filepath).exists()
+        {
+            expanded.push(filepath);
+            continue;
+        }
+
+        let paths = glob::glob(&filepath).unwrap_or_else(|e| {
+            exit_with_error(&format!("invalid glob pattern {}: {}", filepath, e))
+        });
+        let mut matches: Vec<String> = paths
+            .filter_map(Result::ok)
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        if matches.is_empty() {
+            exit_with_error(&format!("no files matched pattern {}", filepath));
+        }
+
+        matches.sort();
+        expanded.extend(matches);
+    }
+
+    expanded
+}
+
+// --------------------------------------------------
+// get extension from filepath, lowercased so that `.CSV` matches `.csv`
+// adapted from https://stackoverflow.com/a/45292067
+fn get_extension(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase)
+}
+
+// --------------------------------------------------
+// get the delimiter given to an extension by --ext-map
+fn get_mapped_delimiter(extension: &str, ext_map: &[(String, char)]) -> Option<char> {
+    ext_map
+        .iter()
+        .find(|(mapped_extension, _)| mapped_extension == extension)
+        .map(|(_, delimiter)| *delimiter)
+}
+
+// --------------------------------------------------
+// determine format based on the file extension, including those of --ext-map
+fn get_format_from_filename(filename: &str, ext_map: &[(String, char)]) -> Option<FileFormat> {
+    let file_extension = get_extension(filename);
+    if file_extension
+        .as_deref()
+        .is_some_and(|extension| get_mapped_delimiter(extension, ext_map).is_some())
+    {
+        return Some(FileFormat::Csv);
+    }
+
+    let file_extension = file_extension?;
+    EXTENSION_FORMATS
+        .iter()
+        .find(|(extension, _)| *extension == file_extension)
+        .map(|&(_, file_format)| file_format)
+}
+
+// --------------------------------------------------
+// get format from the name given to --format
+fn get_format_from_name(name: &str) -> Option<FileFormat> {
+    FORMAT_NAMES
+        .iter()
+        .find(|(format_name, _)| *format_name == name)
+        .map(|&(_, file_format)| file_format)
+}
+
+// --------------------------------------------------
+// print each format with the extensions it is recognized by
+fn list_formats() {
+    for (name, file_format) in FORMAT_NAMES {
+        let extensions: Vec<String> = EXTENSION_FORMATS
+            .iter()
+            .filter(|(_, extension_format)| *extension_format == file_format)
+            .map(|(extension, _)| format!(".{}", extension))
+            .collect();
+        println!("{:<14}{}", name, extensions.join(", "));
+    }
+}
+
+// --------------------------------------------------
+// determine format from --format, falling back to the file extension
+fn get_file_format(filepath: &str, cli_args: &CliArgs) -> Option<FileFormat> {
+    cli_args.format.or_else(|| {
+        get_format_from_filename(strip_compression_extension(filepath), &cli_args.ext_map)
+    })
+}
+
+// --------------------------------------------------
+// detect the compression and format of a file from its extensions, e.g.
+// `.jsonl.gz` or `.Parquet.GZ`. the format is None when the extension is
+// unknown, leaving it to be guessed from the (decompressed) content
+fn detect(filepath: &str, cli_args: &CliArgs) -> (Option<Compression>, Option<FileFormat>) {
+    (
+        get_compression_from_filename(filepath),
+        get_file_format(filepath, cli_args),
+    )
+}
+
+// --------------------------------------------------
+// determine delimiter based on --format or the file extension, or None when
+// it should be guessed from the content (.txt files and unknown formats)
+fn get_default_delimiter(
+    filepath: &str,
+    format: Option<FileFormat>,
+    cli_args: &CliArgs,
+) -> Option<char> {
+    if cli_args.format.is_none() {
+        let file_extension = get_extension(strip_compression_extension(filepath));
+        if let Some(delimiter) = file_extension
+            .as_deref()
+            .and_then(|extension| get_mapped_delimiter(extension, &cli_args.ext_map))
+        {
+            return Some(delimiter);
+        }
+
+        match file_extension.as_deref() {
+            Some("psv") => return Some('|'),
+            Some("txt") => return None,
+            _ => {}
+        }
+    }
+
+    match format {
+        Some(FileFormat::Tsv | FileFormat::Vcf | FileFormat::Gff) => Some('\t'),
+        Some(_) => Some(','),
+        None => None,
+    }
+}
+
+// --------------------------------------------------
+// get the number of rows to parse
+fn get_num_rows_to_parse(
+    max_rows: Option<u32>,
+    head: bool,
+    tail: bool,
+    sample: bool,
+    column_names_only: bool,
+    summarizes: bool,
+) -> Option<usize> {
+    if column_names_only {
+        return Some(1);
+    }
+
+    // the values counted, or the groups, are limited rather than the rows
+    if tail || sample || summarizes {
+        return None;
+    }
+
+    if let Some(n_rows) = max_rows {
+        return Some(n_rows as usize);
+    }
+
+    if head {
+        return Some(10);
+    }
+
+    None
+}
+
+// --------------------------------------------------
+// get the number of rows to parse for the options given
+fn get_num_rows_of_args(cli_args: &CliArgs) -> Option<usize> {
+    get_num_rows_to_parse(
+        cli_args.max_rows,
+        cli_args.head,
+        cli_args.tail,
+        cli_args.sample || cli_args.sample_frac.is_some() || cli_args.shuffle,
+        cli_args.column_names_only,
+        cli_args.value_counts.is_some() || cli_args.group_by.is_some(),
+    )
+}
+
+// --------------------------------------------------
+// get delimiter to use, or None when it should be guessed from the content.
+// numbers with decimal commas rule out the default comma, so it is guessed
+fn get_delimiter(
+    filepath: &str,
+    file_format: Option<FileFormat>,
+    cli_args: &CliArgs,
+) -> Option<char> {
+    cli_args.delimiter.or_else(|| {
+        get_default_delimiter(filepath, file_format, cli_args)
+            .filter(|&delimiter| !(cli_args.decimal_comma && delimiter == ','))
+    })
+}
+
+// --------------------------------------------------
+// guess the delimiter of delimited text from its leading bytes
+fn detect_delimiter(reader: &mut impl Read, source: &str, cli_args: &CliArgs) -> char {
+    let delimiter = sniff_delimiter(reader);
+    if cli_args.verbose {
+        eprintln!("Detected delimiter {:?} in {}", delimiter, source);
+    }
+    delimiter
+}
+
+// --------------------------------------------------
+// get the character that starts lines to skip, which vcf and gff files
+// use for their meta-information
+fn get_comment_char(file_format: Option<FileFormat>, cli_args: &CliArgs) -> Option<u8> {
+    match file_format {
+        Some(FileFormat::Vcf | FileFormat::Gff) => Some(b'#'),
+        _ => cli_args.comment_char.map(|character| character as u8),
+    }
+}
+
+// --------------------------------------------------
+// whether lines are skipped before or after the header of delimited text
+fn skips_rows(cli_args: &CliArgs) -> bool {
+    cli_args.skip_rows > 0 || cli_args.skip_rows_after_header > 0
+}
+
+// --------------------------------------------------
+// print the lines skipped from an input to stderr
+fn show_skipped_lines(lines: &[u8], source: &str) {
+    eprintln!("Skipped from {}:", source);
+    let lines = String::from_utf8_lossy(lines);
+    match lines.ends_with('\n') {
+        true => eprint!("{}", lines),
+        false => eprintln!("{}", lines),
+    }
+}
+
+// --------------------------------------------------
+// skip the --skip-rows lines before the header of delimited text read from
+// a stream, and the --skip-rows-after-header lines after it, keeping the
+// header. the skipped lines are shown with --show-skipped
+fn skip_leading_rows<'a>(
+    reader: impl Read + 'a,
+    source: &str,
+    cli_args: &CliArgs,
+) -> Box<dyn Read + 'a> {
+    if !skips_rows(cli_args) {
+        return Box::new(reader);
+    }
+
+    let read_error =
+        |e: std::io::Error| -> ! { exit_with_error(&format!("Unable to read {}: {}", source, e)) };
+    let mut reader = BufReader::new(reader);
+    let mut skipped = read_lines(&mut reader, cli_args.skip_rows).unwrap_or_else(|e| read_error(e));
+    let header = match cli_args.no_header {
+        true => Vec::new(),
+        false => read_lines(&mut reader, 1).unwrap_or_else(|e| read_error(e)),
+    };
+    skipped.extend(
+        read_lines(&mut reader, cli_args.skip_rows_after_header).unwrap_or_else(|e| read_error(e)),
+    );
+
+    if cli_args.show_skipped {
+        show_skipped_lines(&skipped, source);
+    }
+    Box::new(std::io::Cursor::new(header).chain(reader))
+}
+
+// --------------------------------------------------
+// open a file past the lines skipped before its header, e.g. to guess its
+// delimiter from the header and the rows
+fn open_file_after_skipped_lines(filepath: &str, cli_args: &CliArgs) -> BufReader<File> {
+    let mut reader = BufReader::new(open_file(filepath));
+    read_lines(&mut reader, cli_args.skip_rows).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to read the file {}: {}", filepath, e))
+    });
+    reader
+}
+
+// --------------------------------------------------
+// have the csv reader skip the lines before the header and the rows after
+// it. without a header, both are skipped from the first line
+fn skip_csv_rows<'a, R: MmapBytesReader + 'a>(
+    reader: CsvReader<'a, R>,
+    skip_rows: usize,
+    skip_rows_after_header: usize,
+    has_header: bool,
+) -> CsvReader<'a, R> {
+    match has_header {
+        true => reader
+            .with_skip_rows(skip_rows)
+            .with_skip_rows_after_header(skip_rows_after_header),
+        false => reader.with_skip_rows(skip_rows + skip_rows_after_header),
+    }
+}
+
+// --------------------------------------------------
+// parse csv and tsv content that is already in memory
+fn parse_csv_bytes(
+    bytes: Vec<u8>,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    delimiter: char,
+    has_header: bool,
+    comment_char: Option<u8>,
+    infer_schema_length: Option<usize>,
+) -> PolarsResult<DataFrame> {
+    let cursor = std::io::Cursor::new(bytes);
+    let file = Box::new(cursor) as Box<dyn MmapBytesReader>;
+
+    CsvReader::new(file)
+        .with_separator(delimiter as u8)
+        .has_header(has_header)
+        .with_comment_char(comment_char)
+        .infer_schema(infer_schema_length)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+}
+
+// --------------------------------------------------
+// get the number of csv records to read, including the header. only
+// delimited text can be cut short after the rows needed
+fn get_num_records_to_read(
+    file_format: Option<FileFormat>,
+    n_rows: Option<usize>,
+    has_header: bool,
+) -> Option<usize> {
+    match file_format {
+        Some(FileFormat::Csv | FileFormat::Tsv | FileFormat::Vcf | FileFormat::Gff) | None => {
+            n_rows.map(|n| n + has_header as usize)
+        }
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// parse a table read from a stream (stdin, a download, or an archive
+// member), reading only the rows needed from delimited text. the format
+// and compression come from the path when it has an extension, otherwise
+// from the content
+fn parse_stream(
+    reader: impl Read,
+    path: &str,
+    source: &str,
+    fallback_format: Option<FileFormat>,
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let read_error =
+        |e: std::io::Error| -> ! { exit_with_error(&format!("Unable to read {}: {}", source, e)) };
+
+    let mut reader = BufReader::new(track_progress(reader));
+    let (compression, known_format) = detect(path, cli_args);
+    let compression = compression.or_else(|| {
+        get_compression_from_magic(reader.fill_buf().unwrap_or_else(|e| read_error(e)))
+    });
+    let reader = match compression {
+        Some(compression) => decompress(reader, &compression).unwrap_or_else(|e| read_error(e)),
+        None => Box::new(reader),
+    };
+    let reader = decode_bom(reader).unwrap_or_else(|e| read_error(e));
+    let reader = skip_leading_rows(reader, source, cli_args);
+
+    // the sniffed prefix stays in the buffer, so nothing is lost
+    let mut reader = BufReader::with_capacity(SNIFF_LENGTH, reader);
+    let prefix = reader.fill_buf().unwrap_or_else(|e| read_error(e));
+    let known_format = known_format.or(fallback_format);
+    let file_format = match splits_lines(cli_args) {
+        true => Some(FileFormat::Csv),
+        false => known_format.or_else(|| Some(sniff_format(&mut &prefix[..]))),
+    };
+    let delimiter =
+        get_delimiter(path, known_format, cli_args).unwrap_or_else(|| match file_format {
+            Some(FileFormat::Csv) => detect_delimiter(&mut &prefix[..], source, cli_args),
+            _ => ',',
+        });
+
+    // ipc streams are decoded as they are read rather than buffered
+    if file_format == Some(FileFormat::IpcStream) {
+        let select_columns = cli_args.selected_columns.clone();
+        return parse_ipc_stream(reader, source, select_columns, n_rows);
+    }
+
+    // ndjson is cut short too, keeping the lines that the reader infers the
+    // schema from so that the types match those of a full read
+    let n_records = get_num_records_to_read(file_format, n_rows, !cli_args.no_header);
+    let comment_char = get_comment_char(file_format, cli_args);
+    let bytes = match (file_format, n_rows) {
+        (Some(FileFormat::NdJson), Some(n)) => {
+            read_ndjson_records(reader, n.max(NDJSON_SCHEMA_LENGTH))
+        }
+        _ => read_records(reader, n_records, comment_char),
+    }
+    .unwrap_or_else(|e| read_error(e));
+
+    parse_bytes(bytes, file_format, delimiter, source, cli_args, n_rows)
+}
+
+// --------------------------------------------------
+// adapted from https://stackoverflow.com/a/77156312/11392276
+fn parse_from_stdin(cli_args: &CliArgs, n_rows: Option<usize>) -> DataFrame {
+    parse_stream(
+        std::io::stdin().lock(),
+        "-",
+        "stdin",
+        None,
+        cli_args,
+        n_rows,
+    )
+}
+
+// --------------------------------------------------
+// exit once delimited text could not be parsed. a value that does not fit
+// the type inferred from the first rows of its column is the usual cause
+fn csv_error(source: &str, error: PolarsError) -> ! {
+    match error {
+        PolarsError::ComputeError(message) if message.to_lowercase().contains("could not parse") => {
+            exit_with_error(&format!(
+                "Unable to parse the file {}: {}\nThe column types are inferred from the first rows, use a larger --infer-schema-length (or \"all\") to infer them from more of the file",
+                source,
+                message.lines().next().unwrap_or_default()
+            ))
+        }
+        error => exit_with_error(&format!("Unable to parse the file {}: {}", source, error)),
+    }
+}
+
+// --------------------------------------------------
+// parse csv and tsv files, skipping the lines before the header and the
+// rows after it
+#[allow(clippy::too_many_arguments)]
+fn parse_csv_file(
+    filepath: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    delimiter: char,
+    has_header: bool,
+    skipped_rows: (usize, usize),
+    comment_char: Option<u8>,
+    infer_schema_length: Option<usize>,
+) -> DataFrame {
+    let reader = CsvReader::from_path(filepath).unwrap_or_else(|e| csv_error(filepath, e));
+    let (skip_rows, skip_rows_after_header) = skipped_rows;
+    skip_csv_rows(reader, skip_rows, skip_rows_after_header, has_header)
+        .with_separator(delimiter as u8)
+        .has_header(has_header)
+        .with_comment_char(comment_char)
+        .infer_schema(infer_schema_length)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+        .unwrap_or_else(|e| csv_error(filepath, e))
+}
+
+// --------------------------------------------------
+// parse compressed csv and tsv files, decompressing only what is needed
+#[allow(clippy::too_many_arguments)]
+fn parse_compressed_csv_file(
+    filepath: &str,
+    compression: &Compression,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    delimiter: char,
     has_header: bool,
+    comment_char: Option<u8>,
+    infer_schema_length: Option<usize>,
+) -> DataFrame {
+    let n_records = get_num_records_to_read(Some(FileFormat::Csv), n_rows, has_header);
+    let bytes = decompress(track_progress(open_file(filepath)), compression)
+        .and_then(|decoder| read_records(decoder, n_records, comment_char))
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!(
+                "Unable to decompress the file {}: {}",
+                filepath, e
+            ))
+        });
+
+    parse_csv_bytes(
+        bytes,
+        select_columns,
+        n_rows,
+        delimiter,
+        has_header,
+        comment_char,
+        infer_schema_length,
+    )
+    .unwrap_or_else(|e| csv_error(filepath, e))
+}
+
+// --------------------------------------------------
+// guess the format of a file from its content, along with its compression
+// when the filename does not give it away
+fn sniff_file(
+    filepath: &str,
+    compression: Option<Compression>,
+) -> (Option<FileFormat>, Option<Compression>) {
+    let mut file = open_file(filepath);
+    let compression = compression.or_else(|| {
+        let mut magic = Vec::<u8>::new();
+        let _ = file.by_ref().take(8).read_to_end(&mut magic);
+        get_compression_from_magic(&magic)
+    });
+
+    file.rewind().unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to read the file {}: {}", filepath, e))
+    });
+    (Some(sniff_format(&mut file)), compression)
+}
+
+// --------------------------------------------------
+fn open_file(filepath: &str) -> File {
+    File::open(filepath).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to open the file {}: {}", filepath, e))
+    })
+}
+
+// --------------------------------------------------
+fn read_file(filepath: &str) -> Vec<u8> {
+    let mut bytes = Vec::<u8>::new();
+    track_progress(open_file(filepath))
+        .read_to_end(&mut bytes)
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to read the file {}: {}", filepath, e))
+        });
+    bytes
+}
+
+// --------------------------------------------------
+// parse parquet content, or only the row groups given to --row-groups
+fn parse_parquet(
+    reader: impl MmapBytesReader,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    row_groups: Option<&[usize]>,
+) -> DataFrame {
+    if let Some(row_groups) = row_groups {
+        return parse_parquet_row_groups(reader, source, row_groups, select_columns, n_rows);
+    }
+
+    ParquetReader::new(reader)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!(
+                "Unable to parse the Parquet file {}: {}",
+                source, e
+            ))
+        })
+}
+
+// --------------------------------------------------
+// parse arrow ipc (feather v2) content
+fn parse_ipc(
+    reader: impl MmapBytesReader,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    IpcReader::new(reader)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to parse the IPC file {}: {}", source, e))
+        })
+}
+
+// --------------------------------------------------
+// parse arrow ipc stream content, decoding one record batch at a time so
+// that reading stops once n_rows rows have been decoded
+fn parse_ipc_stream(
+    reader: impl Read,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    IpcStreamReader::new(reader)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to parse the IPC stream {}: {}", source, e))
+        })
+}
+
+// --------------------------------------------------
+// get column names from the ipc footer without reading any data
+fn get_ipc_column_names(filepath: &str) -> Vec<String> {
+    IpcReader::new(open_file(filepath))
+        .schema()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!(
+                "Unable to read the schema of the IPC file {}: {}",
+                filepath, e
+            ))
+        })
+        .fields
+        .iter()
+        .map(|field| field.name.clone())
+        .collect()
+}
+
+// --------------------------------------------------
+// parse avro content
+#[cfg(feature = "avro")]
+fn parse_avro(
+    reader: impl Read + Seek,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    polars::io::avro::AvroReader::new(reader)
+        .with_columns(select_columns)
+        .with_n_rows(n_rows)
+        .finish()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to parse the Avro file {}: {}", source, e))
+        })
+}
+
+#[cfg(not(feature = "avro"))]
+fn parse_avro(
+    _reader: impl Read + Seek,
+    source: &str,
+    _select_columns: Option<Vec<String>>,
+    _n_rows: Option<usize>,
+) -> DataFrame {
+    exit_with_error(&format!(
+        "Unable to parse the Avro file {}: printbl was built without the `avro` feature",
+        source
+    ))
+}
+
+// --------------------------------------------------
+// check whether json content is line-delimited rather than a single array
+fn is_ndjson(bytes: &[u8]) -> bool {
+    let first_char = bytes.iter().find(|b| !b.is_ascii_whitespace());
+    !matches!(first_char, Some(b'['))
+}
+
+// --------------------------------------------------
+// parse json content (array of objects, or ndjson). only the parse can
+// fail, as the content may only look like json
+fn parse_json(
+    bytes: &[u8],
+    ndjson: bool,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> PolarsResult<DataFrame> {
+    let cursor = std::io::Cursor::new(bytes);
+
+    // a single pretty-printed object (as returned by many apis) also starts
+    // with {, so fall back to reading it as one json document
+    let df = if ndjson {
+        JsonLineReader::new(cursor)
+            .finish()
+            .or_else(|_| JsonReader::new(std::io::Cursor::new(bytes)).finish())
+    } else {
+        JsonReader::new(cursor).finish()
+    }?;
+
+    // the json reader cannot push these down, so apply them after parsing
+    let df = match select_columns {
+        Some(columns) => df.select(columns).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to select columns from {}: {}", source, e))
+        }),
+        None => df,
+    };
+
+    Ok(match n_rows {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    })
+}
+
+// --------------------------------------------------
+// whether the format of a source was guessed from its content rather than
+// given by --format or its extension
+fn is_sniffed(source: &str, cli_args: &CliArgs) -> bool {
+    get_file_format(source, cli_args).is_none()
+}
+
+// --------------------------------------------------
+// parse content that has already been read into memory
+fn parse_bytes(
+    bytes: Vec<u8>,
+    file_format: Option<FileFormat>,
+    delimiter: char,
+    source: &str,
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let select_columns = cli_args.selected_columns.clone();
+    let cursor = std::io::Cursor::new(strip_utf8_bom(bytes));
+
+    match file_format {
+        Some(FileFormat::Parquet) => parse_parquet(
+            cursor,
+            source,
+            select_columns,
+            n_rows,
+            cli_args.row_groups.as_deref(),
+        ),
+        Some(FileFormat::Ipc) => parse_ipc(cursor, source, select_columns, n_rows),
+        Some(FileFormat::IpcStream) => parse_ipc_stream(cursor, source, select_columns, n_rows),
+        Some(FileFormat::Avro) => parse_avro(cursor, source, select_columns, n_rows),
+        Some(FileFormat::Excel) => parse_excel(
+            cursor.into_inner(),
+            source,
+            cli_args.sheet.as_deref(),
+            select_columns,
+            n_rows,
+            !cli_args.no_header,
+        ),
+        Some(FileFormat::Json | FileFormat::NdJson) => {
+            let bytes = cursor.into_inner();
+            let ndjson = file_format == Some(FileFormat::NdJson) || is_ndjson(&bytes);
+            match parse_json(&bytes, ndjson, source, select_columns, n_rows) {
+                Ok(df) => df,
+                // content sniffed as json may be delimited text whose first
+                // field starts with [ or {, e.g. a header of [id],name
+                Err(_) if is_sniffed(source, cli_args) => {
+                    let delimiter = cli_args.delimiter.unwrap_or_else(|| {
+                        detect_delimiter(&mut bytes.as_slice(), source, cli_args)
+                    });
+                    parse_bytes(
+                        bytes,
+                        Some(FileFormat::Csv),
+                        delimiter,
+                        source,
+                        cli_args,
+                        n_rows,
+                    )
+                }
+                Err(e) => {
+                    exit_with_error(&format!("Unable to parse the JSON in {}: {}", source, e))
+                }
+            }
+        }
+        Some(FileFormat::Yaml) => parse_yaml(cursor.into_inner(), source, select_columns, n_rows),
+        Some(FileFormat::Html) => parse_csv_bytes(
+            html_table_to_tsv(cursor.get_ref(), source, cli_args.table_index),
+            select_columns,
+            n_rows,
+            '\t',
+            true,
+            None,
+            cli_args.infer_schema_length,
+        )
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!(
+                "Unable to parse the HTML table in {}: {}",
+                source, e
+            ))
+        }),
+        Some(FileFormat::Sqlite | FileFormat::DuckDb) => exit_with_error(&format!(
+            "Databases can only be read from local files: {}",
+            source
+        )),
+        Some(FileFormat::Vcf) => parse_csv_bytes(
+            strip_vcf_meta_lines(cursor.get_ref()).to_vec(),
+            select_columns,
+            n_rows,
+            delimiter,
+            !cli_args.no_header,
+            None,
+            cli_args.infer_schema_length,
+        )
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to parse the VCF file {}: {}", source, e))
+        }),
+        Some(FileFormat::Gff) => {
+            let df = parse_csv_bytes(
+                cursor.into_inner(),
+                None,
+                n_rows,
+                delimiter,
+                false,
+                get_comment_char(file_format, cli_args),
+                cli_args.infer_schema_length,
+            )
+            .unwrap_or_else(|e| {
+                exit_with_error(&format!("Unable to parse the GFF file {}: {}", source, e))
+            });
+
+            // the columns are only named once parsed, so select them afterwards
+            let df = name_gff_columns(df, source);
+            match select_columns {
+                Some(columns) => df.select(columns).unwrap_or_else(|e| {
+                    exit_with_error(&format!("Unable to select columns from {}: {}", source, e))
+                }),
+                None => df,
+            }
+        }
+        _ if cli_args.fixed_width.is_some() => parse_csv_bytes(
+            fixed_width_to_tsv(
+                cursor.get_ref(),
+                cli_args.fixed_width.as_deref().unwrap_or_default(),
+                cli_args.fwf_names.as_deref(),
+                !cli_args.no_header,
+            ),
+            select_columns,
+            n_rows,
+            '\t',
+            true,
+            get_comment_char(file_format, cli_args),
+            cli_args.infer_schema_length,
+        )
+        .unwrap_or_else(|e| csv_error(source, e)),
+        _ if cli_args.whitespace => parse_csv_bytes(
+            whitespace_to_tsv(cursor.get_ref(), !cli_args.no_header),
+            select_columns,
+            n_rows,
+            '\t',
+            !cli_args.no_header,
+            get_comment_char(file_format, cli_args),
+            cli_args.infer_schema_length,
+        )
+        .unwrap_or_else(|e| csv_error(source, e)),
+        _ => parse_csv_bytes(
+            cursor.into_inner(),
+            select_columns,
+            n_rows,
+            delimiter,
+            !cli_args.no_header,
+            get_comment_char(file_format, cli_args),
+            cli_args.infer_schema_length,
+        )
+        .unwrap_or_else(|e| csv_error(source, e)),
+    }
+}
+
+// --------------------------------------------------
+// get column names in dataframe
+fn get_column_names(df: DataFrame) -> Vec<String> {
+    df.get_column_names()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+// --------------------------------------------------
+// parse a table downloaded from a url, falling back to the content type
+// of the response when the url has no file extension
+fn parse_url(url: &str, cli_args: &CliArgs, n_rows: Option<usize>) -> DataFrame {
+    let cache = match cli_args.no_cache {
+        true => None,
+        false => Cache::new(Duration::from_secs(cli_args.cache_ttl), cli_args.refresh),
+    };
+    let response = download(
+        url,
+        &cli_args.http_headers,
+        cli_args.bearer_token.as_deref(),
+        cache.as_ref(),
+    );
+    let content_format = response
+        .content_type
+        .as_deref()
+        .and_then(get_format_from_content_type);
+
+    parse_stream(
+        response.reader,
+        get_url_path(url),
+        url,
+        content_format,
+        cli_args,
+        n_rows,
+    )
+}
+
+// --------------------------------------------------
+// parse a member of a zip archive, detecting its format from its own name
+fn parse_zip_member(
+    archive: &str,
+    member: Option<&str>,
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let mut zip = open_zip_archive(archive);
+    let member = get_zip_member(&zip, archive, member, |name| {
+        get_file_format(name, cli_args).is_some()
+    });
+    let reader = zip.by_name(&member).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to read {} from {}: {}",
+            member, archive, e
+        ))
+    });
+
+    let source = format!("{}::{}", archive, member);
+    parse_stream(reader, &member, &source, None, cli_args, n_rows)
+}
+
+// --------------------------------------------------
+// parse the tables of a tar archive, concatenating its members in order
+// (or reading only the member given) and extracting no more than the rows
+// needed
+fn parse_tar_members(
+    archive: &str,
+    member: Option<&str>,
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let read_error =
+        |e: std::io::Error| -> ! { exit_with_error(&format!("Unable to read {}: {}", archive, e)) };
+
+    let mut tar = open_tar_archive(archive);
+    let mut df: Option<DataFrame> = None;
+    let mut first_member = (String::new(), None);
+
+    for entry in tar.entries().unwrap_or_else(|e| read_error(e)) {
+        let entry = entry.unwrap_or_else(|e| read_error(e));
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let name = entry
+            .path()
+            .unwrap_or_else(|e| read_error(e))
+            .to_string_lossy()
+            .into_owned();
+        let file_format = get_file_format(&name, cli_args);
+        match member {
+            Some(member) if member != name => continue,
+            None if file_format.is_none() => continue,
+            _ => {}
+        }
+
+        let source = format!("{}::{}", archive, name);
+        match &df {
+            None => first_member = (source.clone(), file_format),
+            Some(_) if first_member.1 != file_format => exit_with_error(&format!(
+                "{} is not in the same format as {}",
+                source, first_member.0
+            )),
+            Some(_) => {}
+        }
+
+        let n_remaining = n_rows.map(|n| n - df.as_ref().map_or(0, DataFrame::height));
+        let next_df = parse_stream(entry, &name, &source, None, cli_args, n_remaining);
+        df = Some(match df {
+            None => next_df,
+            Some(mut df) => {
+                stack_frames(&mut df, next_df, &source, &first_member.0);
+                df
+            }
+        });
+
+        // stop extracting once the member or the rows needed are read
+        let height = df.as_ref().map_or(0, DataFrame::height);
+        if member.is_some() || n_rows.is_some_and(|n| height >= n) {
+            break;
+        }
+    }
+
+    df.unwrap_or_else(|| match member {
+        Some(member) => exit_with_error(&format!("{} not found in {}", member, archive)),
+        None => exit_with_error(&format!("No tables found in {}", archive)),
+    })
+}
+
+// --------------------------------------------------
+// only parquet objects can be scanned from cloud storage
+fn check_cloud_format(url: &str, cli_args: &CliArgs) {
+    if !matches!(get_file_format(url, cli_args), Some(FileFormat::Parquet)) {
+        exit_with_error(&format!(
+            "Only Parquet files can be read from cloud storage: {}",
+            url
+        ));
+    }
+}
+
+// --------------------------------------------------
+// append a table to the first one read, once their schemas agree
+fn stack_frames(df: &mut DataFrame, next_df: DataFrame, filepath: &str, first_filepath: &str) {
+    check_schemas_agree(&next_df.schema(), filepath, &df.schema(), first_filepath);
+
+    // line the columns up with the first file before stacking
+    let next_df = next_df.select(df.get_column_names()).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to align columns of {}: {}", filepath, e))
+    });
+    df.vstack_mut(&next_df)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to concatenate {}: {}", filepath, e)));
+}
+
+// --------------------------------------------------
+// parse a single input (a file, or stdin for "-")
+fn parse_input(filepath: &str, cli_args: &CliArgs, n_rows: Option<usize>) -> DataFrame {
+    if is_url(filepath) {
+        return parse_url(filepath, cli_args, n_rows);
+    }
+
+    if is_cloud_url(filepath) {
+        check_cloud_format(filepath, cli_args);
+        return parse_cloud_parquet(filepath, cli_args.selected_columns.clone(), n_rows);
+    }
+
+    if is_parquet_dataset(filepath) {
+        return parse_parquet_dataset(filepath, cli_args.selected_columns.clone(), n_rows);
+    }
+
+    if is_clipboard(filepath) {
+        let text = std::io::Cursor::new(read_clipboard());
+        return parse_stream(
+            text,
+            filepath,
+            "clipboard",
+            Some(FileFormat::Tsv),
+            cli_args,
+            n_rows,
+        );
+    }
+
+    if is_exec(filepath) {
+        let command = cli_args.exec.as_deref().unwrap_or_default();
+        return parse_stream(spawn_command(command), "-", command, None, cli_args, n_rows);
+    }
+
+    if let Some((archive, member)) = split_zip_path(filepath) {
+        return parse_zip_member(archive, member, cli_args, n_rows);
+    }
+
+    if let Some((archive, member)) = split_tar_path(filepath) {
+        return parse_tar_members(archive, member, cli_args, n_rows);
+    }
+
+    let (compression, file_format) = detect(filepath, cli_args);
+    let selected_columns = cli_args.selected_columns.clone();
+
+    if filepath == "-" {
+        parse_from_stdin(cli_args, n_rows)
+    } else {
+        let file = File::open(filepath)
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to open {}: {}", filepath, e)));
+
+        // named pipes and devices (e.g. process substitution) cannot be
+        // reopened or rewound, so read them once like stdin. whitespace
+        // separated and fixed-width text is split in memory the same way,
+        // utf-16 text is transcoded, and the skipped lines of compressed
+        // text are left out as it is decompressed
+        if splits_lines(cli_args)
+            || (compression.is_some() && skips_rows(cli_args))
+            || !file.metadata().is_ok_and(|metadata| metadata.is_file())
+            || sniff_utf16(&mut open_file(filepath))
+        {
+            return parse_stream(file, filepath, filepath, None, cli_args, n_rows);
+        }
+
+        let delimiter = get_delimiter(filepath, file_format, cli_args);
+        let (file_format, compression) = match file_format {
+            Some(file_format) => (Some(file_format), compression),
+            None => sniff_file(filepath, compression),
+        };
+        let delimiter = delimiter.unwrap_or_else(|| match file_format {
+            Some(FileFormat::Csv) => detect_delimiter(
+                &mut open_file_after_skipped_lines(filepath, cli_args),
+                filepath,
+                cli_args,
+            ),
+            _ => ',',
+        });
+
+        match (file_format, compression) {
+            (Some(FileFormat::Csv | FileFormat::Tsv), Some(compression)) => {
+                parse_compressed_csv_file(
+                    filepath,
+                    &compression,
+                    selected_columns,
+                    n_rows,
+                    delimiter,
+                    !cli_args.no_header,
+                    get_comment_char(file_format, cli_args),
+                    cli_args.infer_schema_length,
+                )
+            }
+            (_, Some(compression)) => {
+                let n_records = get_num_records_to_read(file_format, n_rows, !cli_args.no_header);
+                let bytes = decompress(track_progress(open_file(filepath)), &compression)
+                    .and_then(|decoder| {
+                        read_records(decoder, n_records, get_comment_char(file_format, cli_args))
+                    })
+                    .unwrap_or_else(|e| {
+                        exit_with_error(&format!(
+                            "Unable to decompress the file {}: {}",
+                            filepath, e
+                        ))
+                    });
+                parse_bytes(bytes, file_format, delimiter, filepath, cli_args, n_rows)
+            }
+            (Some(FileFormat::Vcf | FileFormat::Gff), None) => {
+                let n_records = get_num_records_to_read(file_format, n_rows, !cli_args.no_header);
+                let bytes = read_records(
+                    track_progress(open_file(filepath)),
+                    n_records,
+                    get_comment_char(file_format, cli_args),
+                )
+                .unwrap_or_else(|e| {
+                    exit_with_error(&format!("Unable to read the file {}: {}", filepath, e))
+                });
+                parse_bytes(bytes, file_format, delimiter, filepath, cli_args, n_rows)
+            }
+            (Some(FileFormat::Parquet), None) => match get_sorted_rows(cli_args) {
+                Some(keep_rows) if cli_args.row_groups.is_none() => parse_parquet_sorted_rows(
+                    open_file(filepath),
+                    filepath,
+                    selected_columns,
+                    keep_rows,
+                ),
+                _ => parse_parquet(
+                    open_file(filepath),
+                    filepath,
+                    selected_columns,
+                    n_rows,
+                    cli_args.row_groups.as_deref(),
+                ),
+            },
+            (Some(FileFormat::Ipc), None) => {
+                parse_ipc(open_file(filepath), filepath, selected_columns, n_rows)
+            }
+            (Some(FileFormat::IpcStream), None) => {
+                parse_ipc_stream(open_file(filepath), filepath, selected_columns, n_rows)
+            }
+            (Some(FileFormat::Avro), None) => {
+                parse_avro(open_file(filepath), filepath, selected_columns, n_rows)
+            }
+            (
+                Some(
+                    FileFormat::Excel
+                    | FileFormat::Json
+                    | FileFormat::NdJson
+                    | FileFormat::Yaml
+                    | FileFormat::Html,
+                ),
+                None,
+            ) => parse_bytes(
+                read_file(filepath),
+                file_format,
+                delimiter,
+                filepath,
+                cli_args,
+                n_rows,
+            ),
+            (Some(FileFormat::Sqlite), None) => parse_sqlite_table(
+                filepath,
+                cli_args.table.as_deref().unwrap_or_default(),
+                cli_args.query.as_deref(),
+                selected_columns,
+                n_rows,
+            ),
+            (Some(FileFormat::DuckDb), None) => parse_duckdb(
+                filepath,
+                cli_args.table.as_deref().unwrap_or_default(),
+                cli_args.query.as_deref(),
+                selected_columns,
+                n_rows,
+            ),
+            _ => {
+                // the reader skips the lines itself, so they are read again
+                // to be shown
+                if cli_args.show_skipped {
+                    let _ = skip_leading_rows(open_file(filepath), filepath, cli_args);
+                }
+                parse_csv_file(
+                    filepath,
+                    selected_columns,
+                    n_rows,
+                    delimiter,
+                    !cli_args.no_header,
+                    (cli_args.skip_rows, cli_args.skip_rows_after_header),
+                    get_comment_char(file_format, cli_args),
+                    cli_args.infer_schema_length,
+                )
+            }
+        }
+    }
+}
+
+// --------------------------------------------------
+// parse the --rows range of an input, and no more than n_rows of its rows.
+// the reader of a csv or tsv file skips the rows before the range, and the
+// range is pushed down into the scan of a parquet file, so those rows are
+// never kept. the other inputs are parsed up to the end of the range
+fn parse_row_range_of_input(
+    filepath: &str,
+    cli_args: &CliArgs,
+    rows: (usize, Option<usize>),
+    n_rows: Option<usize>,
 ) -> DataFrame {
-    CsvReader::from_path(filepath)
-        .expect(&format!("Unable to parse the file {}", filepath))
-        .with_separator(delimiter as u8)
-        .has_header(has_header)
-        .with_columns(select_columns)
-        .with_n_rows(n_rows)
-        .finish()
-        .expect(&format!("Unable to parse the file {}", filepath))
+    let (first, last) = rows;
+    let skipped = first - 1;
+    let length = match (last.map(|last| last + 1 - first), n_rows) {
+        (Some(length), Some(n)) => Some(length.min(n)),
+        (length, n) => length.or(n),
+    };
+
+    let is_plain_file = !is_clipboard(filepath)
+        && !is_exec(filepath)
+        && PathBuf::from(filepath).is_file()
+        && !splits_lines(cli_args)
+        && !sniff_utf16(&mut open_file(filepath));
+    let (compression, file_format) = match (is_plain_file, detect(filepath, cli_args)) {
+        (false, _) => (None, None),
+        (true, (compression, Some(file_format))) => (compression, Some(file_format)),
+        (true, (compression, None)) => {
+            let (file_format, compression) = sniff_file(filepath, compression);
+            (compression, file_format)
+        }
+    };
+
+    match (file_format, compression) {
+        (Some(FileFormat::Csv | FileFormat::Tsv), None) => {
+            let delimiter = get_delimiter(filepath, file_format, cli_args).unwrap_or_else(|| {
+                detect_delimiter(
+                    &mut open_file_after_skipped_lines(filepath, cli_args),
+                    filepath,
+                    cli_args,
+                )
+            });
+            let reader = CsvReader::from_path(filepath).unwrap_or_else(|e| csv_error(filepath, e));
+            if cli_args.show_skipped {
+                let _ = skip_leading_rows(open_file(filepath), filepath, cli_args);
+            }
+            skip_csv_rows(
+                reader,
+                cli_args.skip_rows,
+                cli_args.skip_rows_after_header + skipped,
+                !cli_args.no_header,
+            )
+            .with_separator(delimiter as u8)
+            .has_header(!cli_args.no_header)
+            .with_comment_char(get_comment_char(file_format, cli_args))
+            .infer_schema(cli_args.infer_schema_length)
+            .with_columns(cli_args.selected_columns.clone())
+            .with_n_rows(length)
+            .finish()
+            .unwrap_or_else(|e| csv_error(filepath, e))
+        }
+        (Some(FileFormat::Parquet), None) if cli_args.row_groups.is_none() => {
+            let lf =
+                LazyFrame::scan_parquet(filepath, ScanArgsParquet::default()).unwrap_or_else(|e| {
+                    exit_with_error(&format!("Unable to read {}: {}", filepath, e))
+                });
+            let length = length.map_or(IdxSize::MAX, |length| length as IdxSize);
+            collect_scan(
+                lf.slice(skipped as i64, length),
+                filepath,
+                cli_args.selected_columns.clone(),
+                None,
+            )
+        }
+        _ => {
+            let df = parse_input(filepath, cli_args, length.map(|length| skipped + length));
+            df.slice(skipped as i64, length.unwrap_or(usize::MAX))
+        }
+    }
+}
+
+// --------------------------------------------------
+// get the names and types of the columns of a single input, from its first
+// row, or from every record of yaml, whose records need not share their keys
+fn get_input_dtypes(filepath: &str, cli_args: &CliArgs) -> Vec<(String, DataType)> {
+    let n_rows = match is_cloud_url(filepath) || is_parquet_dataset(filepath) {
+        true => Some(1),
+        false => match detect(filepath, cli_args).1 {
+            Some(FileFormat::Yaml) => None,
+            _ => Some(1),
+        },
+    };
+    parse_input(filepath, cli_args, n_rows)
+        .get_columns()
+        .iter()
+        .map(|series| (series.name().to_string(), series.dtype().clone()))
+        .collect()
+}
+
+// --------------------------------------------------
+// get the column names of a single input
+fn get_input_column_names(filepath: &str, cli_args: &CliArgs) -> Vec<String> {
+    if is_cloud_url(filepath) {
+        check_cloud_format(filepath, cli_args);
+        return get_cloud_parquet_column_names(filepath);
+    }
+
+    if is_parquet_dataset(filepath) {
+        return get_parquet_dataset_column_names(filepath);
+    }
+
+    let (compression, file_format) = detect(filepath, cli_args);
+
+    // ipc files carry their schema in the footer, so skip reading the data
+    if compression.is_none()
+        && matches!(file_format, Some(FileFormat::Ipc))
+        && Path::new(filepath).is_file()
+    {
+        return get_ipc_column_names(filepath);
+    }
+
+    // yaml records need not share their keys, so every record is read
+    if matches!(file_format, Some(FileFormat::Yaml)) {
+        return get_column_names(parse_input(filepath, cli_args, None));
+    }
+
+    get_column_names(parse_input(filepath, cli_args, Some(1)))
+}
+
+// --------------------------------------------------
+// the format of a local SQLite or DuckDB database, or none for other inputs
+fn get_database_format(filepath: &str, cli_args: &CliArgs) -> Option<FileFormat> {
+    match !is_url(filepath) && PathBuf::from(filepath).is_file() {
+        true => get_file_format(filepath, cli_args)
+            .or_else(|| sniff_file(filepath, None).0)
+            .filter(|f| matches!(f, FileFormat::Sqlite | FileFormat::DuckDb)),
+        false => None,
+    }
+}
+
+// --------------------------------------------------
+// --table and --query choose what to read from a database, so other inputs
+// are an error rather than read whole
+fn check_table_inputs(cli_args: &CliArgs) {
+    let option = match (&cli_args.table, &cli_args.query) {
+        (_, Some(_)) => "--query",
+        (Some(_), None) => "--table",
+        (None, None) => return,
+    };
+    for filepath in &cli_args.filepaths {
+        if get_database_format(filepath, cli_args).is_none() {
+            exit_with_error(&format!(
+                "{} requires a SQLite or DuckDB input, but {} is not a database",
+                option, filepath
+            ));
+        }
+    }
+}
+
+// --------------------------------------------------
+// check that the columns of a file match those of the first file
+fn check_column_names_agree(
+    column_names: &[String],
+    filepath: &str,
+    expected_column_names: &[String],
+    expected_filepath: &str,
+) {
+    for column_name in expected_column_names {
+        if !column_names.contains(column_name) {
+            exit_with_error(&format!(
+                "Column {} of {} is missing from {}",
+                column_name, expected_filepath, filepath
+            ));
+        }
+    }
+
+    for column_name in column_names {
+        if !expected_column_names.contains(column_name) {
+            exit_with_error(&format!(
+                "Column {} of {} is not present in {}",
+                column_name, filepath, expected_filepath
+            ));
+        }
+    }
+}
+
+// --------------------------------------------------
+// check that the schema of a file matches that of the first file
+fn check_schemas_agree(
+    schema: &Schema,
+    filepath: &str,
+    expected_schema: &Schema,
+    expected_filepath: &str,
+) {
+    check_column_names_agree(
+        &schema_column_names(schema),
+        filepath,
+        &schema_column_names(expected_schema),
+        expected_filepath,
+    );
+
+    for (column_name, expected_dtype) in expected_schema.iter() {
+        let dtype = schema.get(column_name).expect("Column names agree");
+        if dtype != expected_dtype {
+            exit_with_error(&format!(
+                "Column {} has type {} in {} but type {} in {}",
+                column_name, dtype, filepath, expected_dtype, expected_filepath
+            ));
+        }
+    }
+}
+
+// --------------------------------------------------
+fn schema_column_names(schema: &Schema) -> Vec<String> {
+    schema.iter_names().map(|name| name.to_string()).collect()
+}
+
+// --------------------------------------------------
+// add a column holding the path of the file each row came from
+fn with_source_column(df: DataFrame, filepath: &str) -> DataFrame {
+    let source = Series::new("source_file", vec![filepath; df.height()]);
+    let mut columns = vec![source];
+    columns.extend(df.get_columns().iter().cloned());
+
+    DataFrame::new(columns).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to add source column to {}: {}",
+            filepath, e
+        ))
+    })
+}
+
+// --------------------------------------------------
+// the lines that the rows of a csv or tsv file start on, read from the file
+// again. stdin, downloads, and the other inputs that cannot be read again
+// have none
+fn get_row_lines(filepath: &str, cli_args: &CliArgs, n_rows: usize) -> Option<Vec<usize>> {
+    if is_clipboard(filepath)
+        || is_exec(filepath)
+        || !PathBuf::from(filepath).is_file()
+        || splits_lines(cli_args)
+        || sniff_utf16(&mut open_file(filepath))
+    {
+        return None;
+    }
+
+    let (compression, file_format) = match detect(filepath, cli_args) {
+        (compression, Some(file_format)) => (compression, Some(file_format)),
+        (compression, None) => {
+            let (file_format, compression) = sniff_file(filepath, compression);
+            (compression, file_format)
+        }
+    };
+    if !matches!(file_format, Some(FileFormat::Csv | FileFormat::Tsv)) {
+        return None;
+    }
+
+    // the lines skipped before the header are still counted
+    let reader: Box<dyn Read> = match compression {
+        Some(compression) => decompress(open_file(filepath), &compression).ok()?,
+        None => Box::new(open_file(filepath)),
+    };
+    let mut reader = BufReader::new(reader);
+    let skipped = read_lines(&mut reader, cli_args.skip_rows).ok()?;
+    let n_skipped_lines = skipped.iter().filter(|&&b| b == b'\n').count();
+
+    let n_leading = !cli_args.no_header as usize + cli_args.skip_rows_after_header;
+    let comment_char = get_comment_char(file_format, cli_args);
+    let lines = get_record_lines(reader, n_rows + n_leading, comment_char).ok()?;
+
+    // the lines are only trusted when they agree with the rows parsed
+    let lines: Vec<usize> = lines
+        .get(n_leading..)?
+        .iter()
+        .map(|line| line + n_skipped_lines)
+        .collect();
+    (lines.len() == n_rows).then_some(lines)
+}
+
+// --------------------------------------------------
+// add a row column numbering the rows of an input from 1, before any rows
+// are left out, other than those skipped before the --rows range. the rows
+// of csv and tsv files are numbered by the lines they start on, counting
+// the header and the comment lines
+fn with_row_index(df: DataFrame, filepath: &str, cli_args: &CliArgs, skipped: usize) -> DataFrame {
+    if df.get_column_names().contains(&"row") {
+        exit_with_error(&format!("--index: {} already has a row column", filepath));
+    }
+
+    let rows: Vec<u64> = match get_row_lines(filepath, cli_args, skipped + df.height()) {
+        Some(mut lines) => lines
+            .split_off(skipped)
+            .into_iter()
+            .map(|line| line as u64)
+            .collect(),
+        None => (skipped as u64 + 1..=(skipped + df.height()) as u64).collect(),
+    };
+    let mut columns = vec![Series::new("row", rows)];
+    columns.extend(df.get_columns().iter().cloned());
+
+    DataFrame::new(columns).unwrap_or_else(|e| {
+        exit_with_error(&format!(
+            "Unable to add the row column to {}: {}",
+            filepath, e
+        ))
+    })
+}
+
+// --------------------------------------------------
+// parse the inputs and stack them into a single table
+fn parse_inputs(filepaths: &[String], cli_args: &CliArgs, n_rows: Option<usize>) -> DataFrame {
+    let mut df: Option<DataFrame> = None;
+    let mut first_filepath = "";
+
+    for filepath in filepaths {
+        // the progress is known from the size of a file, and only counted
+        // for the others
+        if !cli_args.no_progress {
+            let size = std::fs::metadata(filepath)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len());
+            start_progress(filepath, size);
+        }
+        let mut next_df = match cli_args.rows {
+            Some(rows) => parse_row_range_of_input(filepath, cli_args, rows, n_rows),
+            None => parse_input(filepath, cli_args, n_rows),
+        };
+        finish_progress();
+        if cli_args.decimal_comma {
+            next_df = parse_decimal_commas(next_df, filepath);
+        }
+        if cli_args.with_source_column {
+            next_df = with_source_column(next_df, filepath);
+        }
+        if cli_args.index {
+            let skipped = cli_args.rows.map_or(0, |(first, _)| first - 1);
+            next_df = with_row_index(next_df, filepath, cli_args, skipped);
+        }
+
+        df = Some(match df {
+            None => {
+                first_filepath = filepath;
+                next_df
+            }
+            Some(mut df) => {
+                stack_frames(&mut df, next_df, filepath, first_filepath);
+                df
+            }
+        });
+    }
+
+    let df = df.expect("At least one filepath is required");
+
+    // the row limit applies to the concatenated table
+    match n_rows {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    }
+}
+
+// --------------------------------------------------
+/// Configure Polars with ENV vars
+fn configure_the_environment(no_dtypes: bool) {
+    env::set_var("POLARS_FMT_TABLE_DATAFRAME_SHAPE_BELOW", "1"); // print shape information below the table.
+    env::set_var("POLARS_FMT_MAX_ROWS", "-1"); // maximum number of rows shown when formatting DataFrames.
+    env::set_var("POLARS_FMT_MAX_COLS", "-1"); // the columns are left out before formatting, to fit the table to the terminal.
+    env::set_var("POLARS_FMT_TABLE_ROUNDED_CORNERS", "1"); // apply rounded corners to UTF8-styled tables.
+    env::set_var("POLARS_FMT_TABLE_HIDE_DATAFRAME_SHAPE_INFORMATION", "1"); // the shape is printed separately, as that of the table before it is fitted.
+    if no_dtypes {
+        env::set_var("POLARS_FMT_TABLE_HIDE_COLUMN_DATA_TYPES", "1"); // hide the data types of the columns.
+    }
+    env::set_var("POLARS_FMT_STR_LEN", (u16::MAX - 3).to_string()); // values are truncated before they are formatted, and polars fits the widths in a u16.
+}
+
+// --------------------------------------------------
+// prefix the names of the columns of a table with their 1-based numbers in
+// the file, e.g. 3:price, leaving those that are not in the file as they are
+fn number_columns(mut df: DataFrame, numbers: &[Option<usize>]) -> DataFrame {
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .zip(numbers)
+        .map(|(name, number)| match number {
+            Some(i) => format!("{}:{}", i + 1, name),
+            None => name.to_string(),
+        })
+        .collect();
+    df.set_column_names(&names)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to number the columns: {}", e)));
+    df
+}
+
+// --------------------------------------------------
+// the 1-based indices of a column or a range of columns of --select, e.g. 3
+// or 5-8, where negative indices count from the end, e.g. -1 for the last
+fn parse_column_range(column: &str) -> Option<(i64, i64)> {
+    if let Ok(index) = column.parse::<i64>() {
+        return Some((index, index));
+    }
+    // the dash between the indices follows the minus sign of the first
+    let (dash, _) = column.char_indices().skip(1).find(|&(_, c)| c == '-')?;
+    let start = column[..dash].parse::<i64>().ok()?;
+    let end = column[dash + 1..].parse::<i64>().ok()?;
+    Some((start, end))
+}
+
+// --------------------------------------------------
+// whether --select gives columns by their index or by a pattern rather than
+// by their name
+fn is_column_pattern(column: &str) -> bool {
+    parse_column_range(column).is_some()
+        || column.starts_with("re:")
+        || column.contains(['*', '?', '['])
+}
+
+// --------------------------------------------------
+// the number of characters to insert, delete, or replace to turn one name
+// into another
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = distances[0];
+        distances[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let replaced = previous + usize::from(a != b);
+            previous = distances[j + 1];
+            distances[j + 1] = replaced.min(previous + 1).min(distances[j] + 1);
+        }
+    }
+    distances[b.len()]
+}
+
+// --------------------------------------------------
+// the columns of a table matching a glob pattern, e.g. sample_*, or a regex
+// after re:, in the order of its file. a pattern matching nothing is
+// refused, with the columns closest to it
+fn match_columns(pattern: &str, file_columns: &[String], option: &str) -> Vec<String> {
+    let matches: Vec<String> = match pattern.strip_prefix("re:") {
+        Some(regex) => {
+            let regex = Regex::new(regex).unwrap_or_else(|e| {
+                exit_with_error(&format!("Invalid regex {} of --{}: {}", regex, option, e))
+            });
+            file_columns
+                .iter()
+                .filter(|column| regex.is_match(column))
+                .cloned()
+                .collect()
+        }
+        None => {
+            let glob = glob::Pattern::new(pattern).unwrap_or_else(|e| {
+                exit_with_error(&format!(
+                    "Invalid pattern {} of --{}: {}",
+                    pattern, option, e
+                ))
+            });
+            file_columns
+                .iter()
+                .filter(|column| glob.matches(column))
+                .cloned()
+                .collect()
+        }
+    };
+
+    if matches.is_empty() {
+        let mut closest: Vec<&String> = file_columns.iter().collect();
+        closest.sort_by_key(|column| get_edit_distance(pattern, column));
+        closest.truncate(3);
+        exit_with_error(&format!(
+            "No column matches {} of --{}; the closest columns are {}",
+            pattern,
+            option,
+            closest
+                .iter()
+                .map(|column| column.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ));
+    }
+    matches
+}
+
+// --------------------------------------------------
+// the names of the columns of a table selected (or excluded) by the given
+// option, with its columns in the order of its file. a column named like an
+// index or a pattern is selected by its name
+fn resolve_selected_columns(
+    selected_columns: &[String],
+    file_columns: &[String],
+    option: &str,
+) -> Vec<String> {
+    let n_columns = file_columns.len() as i64;
+    let get_position = |index: i64| -> usize {
+        match index {
+            1.. if index <= n_columns => (index - 1) as usize,
+            ..=-1 if -index <= n_columns => (n_columns + index) as usize,
+            _ => exit_with_error(&format!(
+                "column {} of --{} is out of range, the table has {} columns",
+                index, option, n_columns
+            )),
+        }
+    };
+
+    let mut names = Vec::<String>::new();
+    for column in selected_columns {
+        if file_columns.contains(column) || !is_column_pattern(column) {
+            names.push(column.clone());
+            continue;
+        }
+        let Some((start, end)) = parse_column_range(column) else {
+            names.extend(match_columns(column, file_columns, option));
+            continue;
+        };
+        let (start, end) = (get_position(start), get_position(end));
+        if start > end {
+            exit_with_error(&format!(
+                "the columns {} of --{} are in reverse order",
+                column, option
+            ));
+        }
+        names.extend_from_slice(&file_columns[start..=end]);
+    }
+    names
+}
+
+// --------------------------------------------------
+// the columns of a table other than the excluded ones, which must be there
+fn get_remaining_columns(excluded_columns: &[String], file_columns: &[String]) -> Vec<String> {
+    let excluded = resolve_selected_columns(excluded_columns, file_columns, "exclude");
+    if let Some(column) = excluded
+        .iter()
+        .find(|column| !file_columns.contains(column))
+    {
+        exit_with_error(&format!(
+            "Unable to exclude the column {}: not found",
+            column
+        ));
+    }
+    let remaining: Vec<String> = file_columns
+        .iter()
+        .filter(|column| !excluded.contains(column))
+        .cloned()
+        .collect();
+    if remaining.is_empty() {
+        exit_with_error("--exclude leaves out all of the columns");
+    }
+    remaining
+}
+
+// --------------------------------------------------
+// whether a column type is of a kind of --select-dtype
+fn is_dtype_kind(dtype: &DataType, kind: &str) -> bool {
+    match kind {
+        "numeric" => dtype.is_numeric(),
+        "string" => dtype == &DataType::Utf8,
+        "temporal" => dtype.is_temporal(),
+        "boolean" => dtype == &DataType::Boolean,
+        _ => false,
+    }
+}
+
+// --------------------------------------------------
+// the columns of a table of any of the kinds of --select-dtype, in the order
+// of its file, other than the excluded ones. the columns matched are printed
+// to stderr with --verbose
+fn resolve_dtype_columns(
+    kinds: &[String],
+    file_dtypes: &[(String, DataType)],
+    excluded_columns: Option<&[String]>,
+    cli_args: &CliArgs,
+) -> Vec<String> {
+    let file_columns: Vec<String> = file_dtypes.iter().map(|(name, _)| name.clone()).collect();
+    let remaining = match excluded_columns {
+        Some(columns) => get_remaining_columns(columns, &file_columns),
+        None => file_columns,
+    };
+    let columns: Vec<String> = file_dtypes
+        .iter()
+        .filter(|(name, dtype)| {
+            remaining.contains(name) && kinds.iter().any(|kind| is_dtype_kind(dtype, kind))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    if columns.is_empty() {
+        let dtypes: Vec<String> = file_dtypes
+            .iter()
+            .filter(|(name, _)| remaining.contains(name))
+            .map(|(name, dtype)| format!("{} ({})", name, dtype))
+            .collect();
+        exit_with_error(&format!(
+            "No columns of --select-dtype {}; the columns are {}",
+            kinds.join(","),
+            dtypes.join(", ")
+        ));
+    }
+    if cli_args.verbose {
+        eprintln!(
+            "Selected the {} columns: {}",
+            kinds.join(","),
+            columns.join(", ")
+        );
+    }
+    columns
+}
+
+// --------------------------------------------------
+// the names of the columns of a table in the order of its file, which
+// --number-columns needs, and the columns selected from it by their index
+// or name, or left once the excluded ones are dropped. a selection by names
+// alone is left to the parsers otherwise
+fn select_numbered_columns(
+    df: DataFrame,
+    selected_columns: Option<&[String]>,
+    cli_args: &CliArgs,
+) -> (DataFrame, Option<Vec<String>>) {
+    let excluded_columns = cli_args.excluded_columns.as_deref();
+    let select_dtypes = cli_args.select_dtypes.as_deref();
+    if selected_columns.is_none()
+        && excluded_columns.is_none()
+        && select_dtypes.is_none()
+        && !cli_args.number_columns
+    {
+        return (df, None);
+    }
+
+    let file_columns = get_column_names(df.clone());
+    let columns = match (selected_columns, excluded_columns, select_dtypes) {
+        (Some(columns), _, _) => Some(resolve_selected_columns(columns, &file_columns, "select")),
+        (None, _, Some(kinds)) => {
+            let file_dtypes: Vec<(String, DataType)> = df
+                .get_columns()
+                .iter()
+                .map(|series| (series.name().to_string(), series.dtype().clone()))
+                .collect();
+            Some(resolve_dtype_columns(
+                kinds,
+                &file_dtypes,
+                excluded_columns,
+                cli_args,
+            ))
+        }
+        (None, Some(columns), None) => Some(get_remaining_columns(columns, &file_columns)),
+        (None, None, None) => None,
+    };
+    let df = match columns {
+        Some(columns) => df
+            .select(columns)
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to select the columns: {}", e))),
+        None => df,
+    };
+    (df, cli_args.number_columns.then_some(file_columns))
+}
+
+// --------------------------------------------------
+// a column name in snake case: trimmed, in lowercase, and with a single
+// underscore for each run of other characters than letters and digits
+fn to_snake_case(name: &str) -> String {
+    let mut snake_case = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        match c.is_alphanumeric() {
+            true => snake_case.push(c),
+            false if !snake_case.is_empty() && !snake_case.ends_with('_') => snake_case.push('_'),
+            false => {}
+        }
+    }
+    snake_case.trim_end_matches('_').to_string()
+}
+
+// --------------------------------------------------
+// the names of the columns of a table once the columns of --rename are
+// renamed and the others put in snake case with --snake-case-headers. the
+// names that snake case gives more than one column get a numeric suffix
+fn get_renamed_columns(columns: &[String], cli_args: &CliArgs) -> Vec<String> {
+    // an old name of --rename is also found without its spaces
+    let find_column = |old: &str| {
+        columns.iter().position(|column| column == old).or_else(|| {
+            columns
+                .iter()
+                .position(|column| column.trim() == old.trim())
+        })
+    };
+    let mut renamed = vec![None::<String>; columns.len()];
+    for (old, new) in &cli_args.renames {
+        match find_column(old) {
+            Some(i) => renamed[i] = Some(new.clone()),
+            None => exit_with_error(&format!(
+                "Unable to rename the column {}: not found; the columns are {}",
+                old,
+                columns.join(", ")
+            )),
+        }
+    }
+
+    // the names given with --rename, or left as they are, are kept
+    let kept: Vec<Option<String>> = columns
+        .iter()
+        .zip(&renamed)
+        .map(|(column, new)| match (new, cli_args.snake_case_headers) {
+            (Some(new), _) => Some(new.clone()),
+            (None, false) => Some(column.clone()),
+            (None, true) => None,
+        })
+        .collect();
+    let mut names = Vec::<String>::new();
+    for (i, (column, name)) in columns.iter().zip(&kept).enumerate() {
+        let name = match name {
+            Some(name) => name.clone(),
+            None => {
+                let snake_case = Some(to_snake_case(column))
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("column_{}", i + 1));
+                let is_taken = |name: &String| {
+                    names.contains(name) || kept.iter().flatten().any(|kept| kept == name)
+                };
+                let mut unique_name = snake_case.clone();
+                let mut suffix = 1;
+                while is_taken(&unique_name) {
+                    unique_name = format!("{}_{}", snake_case, suffix);
+                    suffix += 1;
+                }
+                if unique_name != snake_case {
+                    eprintln!(
+                        "Warning: the column {:?} is renamed {}, as {} is the name of another column",
+                        column, unique_name, snake_case
+                    );
+                }
+                unique_name
+            }
+        };
+        if names.contains(&name) {
+            exit_with_error(&format!("--rename names more than one column {}", name));
+        }
+        names.push(name);
+    }
+    names
+}
+
+// --------------------------------------------------
+// rename the columns of a table with --rename and --snake-case-headers
+fn rename_columns(mut df: DataFrame, cli_args: &CliArgs) -> DataFrame {
+    if cli_args.renames.is_empty() && !cli_args.snake_case_headers {
+        return df;
+    }
+    let names = get_renamed_columns(&get_column_names(df.clone()), cli_args);
+    df.set_column_names(&names)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to rename the columns: {}", e)));
+    df
+}
+
+// --------------------------------------------------
+// whether the columns printed are not all those of the files, as some are
+// renamed or derived once the inputs are parsed
+fn has_new_columns(cli_args: &CliArgs) -> bool {
+    !cli_args.renames.is_empty() || cli_args.snake_case_headers || !cli_args.derivations.is_empty()
+}
+
+// --------------------------------------------------
+// parse the inputs, rename their columns, add the columns of the --derive
+// expressions, and keep the rows that meet the --filter expressions. the
+// rows are only counted out once filtered, so all of them are parsed
+fn parse_filtered_inputs(
+    filepaths: &[String],
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let n_parsed_rows = match cli_args.filters.is_empty() {
+        true => n_rows,
+        false => None,
+    };
+    let df = rename_columns(parse_inputs(filepaths, cli_args, n_parsed_rows), cli_args);
+    let df = match cli_args.derivations.is_empty() {
+        true => df,
+        false => derive_columns(df, &cli_args.derivations),
+    };
+    match cli_args.filters.is_empty() {
+        true => df,
+        false => filter_table(df, &cli_args.filters, n_rows),
+    }
+}
+
+// --------------------------------------------------
+// find the columns given to a row option among the columns of the printed
+// table, by name or by pattern
+fn find_columns(columns: &[String], df: &DataFrame, option: &str) -> Vec<String> {
+    let df_columns: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    columns
+        .iter()
+        .flat_map(|column| match df_columns.contains(column) {
+            true => vec![column.clone()],
+            false => match_columns(column, &df_columns, option),
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// print the number of rows that an option left out to stderr
+fn report_removed_rows(n_rows: usize, reason: &str) {
+    match n_rows {
+        1 => eprintln!("Removed 1 row {}", reason),
+        n => eprintln!("Removed {} rows {}", n, reason),
+    }
+}
+
+// --------------------------------------------------
+// leave out the rows with a null in any of the --drop-nulls columns, or in
+// any column but the row numbers when none are given
+fn drop_null_rows(df: DataFrame, columns: &[String], cli_args: &CliArgs) -> DataFrame {
+    let subset: Vec<String> = match columns.is_empty() {
+        true => df
+            .get_column_names()
+            .into_iter()
+            .filter(|&column| !(cli_args.index && column == "row"))
+            .map(String::from)
+            .collect(),
+        false => find_columns(columns, &df, "drop-nulls"),
+    };
+
+    let kept = df
+        .drop_nulls(Some(&subset))
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to drop the rows with nulls: {}", e)));
+    report_removed_rows(df.height() - kept.height(), "with nulls");
+    kept
+}
+
+// --------------------------------------------------
+// keep only the rows with a null in any of the --only-nulls columns
+fn keep_null_rows(df: DataFrame, columns: &[String]) -> DataFrame {
+    let has_null = find_columns(columns, &df, "only-nulls")
+        .iter()
+        .map(|column| col(column).is_null())
+        .reduce(|a, b| a.or(b))
+        .unwrap_or(lit(true));
+
+    let height = df.height();
+    let kept =
+        df.lazy().filter(has_null).collect().unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to keep the rows with nulls: {}", e))
+        });
+    report_removed_rows(height - kept.height(), "without nulls");
+    kept
+}
+
+// --------------------------------------------------
+// leave out the rows that repeat an earlier row in all of its columns but
+// the row numbers, or in the --unique-by columns
+fn drop_duplicate_rows(df: DataFrame, cli_args: &CliArgs) -> DataFrame {
+    let subset: Vec<String> = match &cli_args.unique_by {
+        Some(unique_by) => find_columns(unique_by, &df, "unique-by"),
+        None => df
+            .get_column_names()
+            .into_iter()
+            .filter(|&column| !(cli_args.index && column == "row"))
+            .map(String::from)
+            .collect(),
+    };
+    let keep = match cli_args.keep_last {
+        true => UniqueKeepStrategy::Last,
+        false => UniqueKeepStrategy::First,
+    };
+
+    let unique = df
+        .unique_stable(Some(&subset), keep, None)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to drop the duplicate rows: {}", e)));
+    report_removed_rows(df.height() - unique.height(), "of duplicates");
+    unique
+}
+
+// --------------------------------------------------
+// exit unless the columns to sort by are columns of the table whose values
+// have an order
+fn check_sort_columns<'a>(df: &DataFrame, sort_columns: impl IntoIterator<Item = &'a str>) {
+    let columns: Vec<&str> = df.get_column_names();
+    for column in sort_columns {
+        match df.column(column).map(|series| series.dtype()) {
+            Err(_) => exit_with_error(&format!(
+                "No column {} to sort by; the columns are {}",
+                column,
+                columns.join(", ")
+            )),
+            Ok(DataType::List(_) | DataType::Struct(_)) => exit_with_error(&format!(
+                "Unable to sort by {}, as its values are lists or structs, which have no order",
+                column
+            )),
+            Ok(_) => {}
+        }
+    }
 }
 
 // --------------------------------------------------
-// parse parquet file
-fn parse_parquet_file(
-    filepath: &str,
-    select_columns: Option<Vec<String>>,
+// keep the k rows with the largest values of a column, or the smallest,
+// with its nulls last. the rows are selected without sorting the others,
+// and rows that tie keep their order
+fn top_rows(df: DataFrame, column: &str, k: usize, largest: bool) -> DataFrame {
+    check_sort_columns(&df, [column]);
+
+    let lf = df.lazy();
+    let lf = match largest {
+        true => lf.top_k(k as IdxSize, [col(column)], [false], true, true),
+        false => lf.bottom_k(k as IdxSize, [col(column)], [false], true, true),
+    };
+    lf.collect()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to sort the rows: {}", e)))
+}
+
+// --------------------------------------------------
+// sort the rows by the keys, columns paired with whether they are
+// descending, and keep the first n_rows rows. the sort is lazy, so that
+// only those rows are sorted in full, and rows that tie keep their order
+fn sort_rows(
+    df: DataFrame,
+    keys: &[(String, bool)],
+    nulls_first: bool,
     n_rows: Option<usize>,
 ) -> DataFrame {
-    let f = File::open(filepath).expect(&format!("Unable to open the file {}", filepath));
+    check_sort_columns(&df, keys.iter().map(|(column, _)| column.as_str()));
 
-    ParquetReader::new(f)
-        .with_columns(select_columns)
-        .with_n_rows(n_rows)
-        .finish()
-        .expect(&format!("Unable to parse the Parquet file {}", filepath))
+    // each key is preceded by whether it is null, as the order of the nulls
+    // of the later keys is not kept by a sort on several columns
+    let mut exprs = Vec::<Expr>::new();
+    let mut descending = Vec::<bool>::new();
+    for (column, is_descending) in keys {
+        exprs.extend([col(column).is_null(), col(column)]);
+        descending.extend([nulls_first, *is_descending]);
+    }
+    let mut lf = df
+        .lazy()
+        .sort_by_exprs(exprs, descending, !nulls_first, true);
+    if let Some(n) = n_rows {
+        lf = lf.limit(n as IdxSize);
+    }
+    lf.collect()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to sort the rows: {}", e)))
 }
 
 // --------------------------------------------------
-// get column names in dataframe
-fn get_column_names(df: DataFrame) -> Vec<String> {
-    df.get_column_names()
+// keep the rows of --top or --bottom, or the first rows sorted by --sort, of
+// a part of an input, when they are the rows that arrange_rows keeps of it,
+// as nothing before them leaves out, numbers, or changes the rows. the rows
+// kept of each part of a large file are then all that is held of it
+fn get_sorted_rows(cli_args: &CliArgs) -> Option<Box<dyn Fn(DataFrame) -> DataFrame + '_>> {
+    let changes_rows = cli_args.drop_nulls.is_some()
+        || cli_args.only_nulls.is_some()
+        || cli_args.grep.is_some()
+        || cli_args.grep_column.is_some()
+        || cli_args.unique
+        || cli_args.unique_by.is_some()
+        || cli_args.reverse
+        || cli_args.index
+        || cli_args.decimal_comma
+        || cli_args.rows.is_some()
+        || cli_args.sql.is_some()
+        || !cli_args.filters.is_empty()
+        || has_new_columns(cli_args);
+    if changes_rows {
+        return None;
+    }
+
+    let n_rows = get_num_rows_of_args(cli_args);
+    match (&cli_args.top, &cli_args.bottom, &cli_args.sort) {
+        (Some((k, column)), _, _) | (None, Some((k, column)), _) => {
+            let k = n_rows.map_or(*k, |n| n.min(*k));
+            let largest = cli_args.top.is_some();
+            Some(Box::new(move |df| top_rows(df, column, k, largest)))
+        }
+        // the groups of --group-by are sorted rather than the rows
+        (None, None, Some(keys)) if cli_args.group_by.is_none() => {
+            let n_rows = n_rows?;
+            Some(Box::new(move |df| {
+                sort_rows(df, keys, cli_args.nulls_first, Some(n_rows))
+            }))
+        }
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// whether the rows of the printed table are left out or sorted by the
+// values of its columns, which needs all of the rows
+fn needs_all_rows(cli_args: &CliArgs) -> bool {
+    cli_args.drop_nulls.is_some()
+        || cli_args.only_nulls.is_some()
+        || cli_args.grep.is_some()
+        || cli_args.grep_column.is_some()
+        || cli_args.sort.is_some()
+        || cli_args.top.is_some()
+        || cli_args.bottom.is_some()
+        || cli_args.reverse
+        || cli_args.unique
+        || cli_args.unique_by.is_some()
+}
+
+// --------------------------------------------------
+// keep only the rows with a value that matches the --grep pattern in any
+// printed column but the row numbers, or the --grep-column pattern in its
+// column. the values are matched as text, so the number 404 matches "404"
+fn grep_rows(df: DataFrame, cli_args: &CliArgs) -> DataFrame {
+    let (columns, pattern, option) = match (&cli_args.grep, &cli_args.grep_column) {
+        (Some(pattern), _) => {
+            let columns: Vec<String> = df
+                .get_columns()
+                .iter()
+                .filter(|series| !(cli_args.index && series.name() == "row"))
+                .filter(|series| {
+                    !matches!(
+                        series.dtype(),
+                        DataType::List(_) | DataType::Struct(_) | DataType::Binary
+                    )
+                })
+                .map(|series| series.name().to_string())
+                .collect();
+            (columns, pattern, "grep")
+        }
+        (None, Some((column, pattern))) => (
+            find_columns(std::slice::from_ref(column), &df, "grep-column"),
+            pattern,
+            "grep-column",
+        ),
+        (None, None) => return df,
+    };
+
+    let pattern = match cli_args.grep_regex {
+        true => pattern.clone(),
+        false => regex::escape(pattern),
+    };
+    let pattern = match cli_args.ignore_case {
+        true => format!("(?i){}", pattern),
+        false => pattern,
+    };
+    if let Err(e) = Regex::new(&pattern) {
+        exit_with_error(&format!("Invalid regex {} of --{}: {}", pattern, option, e));
+    }
+
+    let matches = columns
+        .iter()
+        .map(|column| {
+            col(column)
+                .cast(DataType::Utf8)
+                .str()
+                .contains(lit(pattern.as_str()), false)
+        })
+        .reduce(|a, b| a.or(b))
+        .unwrap_or(lit(false));
+    df.lazy()
+        .filter(matches)
+        .collect()
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to search the rows: {}", e)))
+}
+
+// --------------------------------------------------
+// leave out the rows with nulls or without them and those that --grep does
+// not match, sort the rows, and leave out the duplicate rows, so that the
+// first of each in the sorted order is kept. then reverse the rows left with
+// --reverse, and keep the first n_rows of them. the number of rows that
+// --grep matched is returned with them
+fn arrange_rows(
+    df: DataFrame,
+    cli_args: &CliArgs,
+    n_rows: Option<usize>,
+) -> (DataFrame, Option<usize>) {
+    let mut df = df;
+    if let Some(columns) = &cli_args.drop_nulls {
+        df = drop_null_rows(df, columns, cli_args);
+    }
+    if let Some(columns) = &cli_args.only_nulls {
+        df = keep_null_rows(df, columns);
+    }
+
+    let n_matches = match cli_args.grep.is_some() || cli_args.grep_column.is_some() {
+        true => {
+            df = grep_rows(df, cli_args);
+            Some(df.height())
+        }
+        false => None,
+    };
+
+    // the rows of --top and --bottom are the first in the order of their
+    // column, with no more of them kept than -n, and the groups of
+    // --group-by are sorted rather than the rows. the rows are only counted
+    // out once the duplicates are dropped, and once they are reversed
+    let drops_duplicates = cli_args.unique || cli_args.unique_by.is_some();
+    let counts_sorted_rows = !(drops_duplicates || cli_args.reverse);
+    let top_or_bottom = cli_args.top.as_ref().or(cli_args.bottom.as_ref());
+    let n_rows = match top_or_bottom {
+        Some((k, _)) => Some(n_rows.map_or(*k, |n| n.min(*k))),
+        None => n_rows,
+    };
+    match (top_or_bottom, &cli_args.sort) {
+        (Some((_, column)), _) => {
+            let largest = cli_args.top.is_some();
+            df = match (counts_sorted_rows, n_rows) {
+                (true, Some(k)) => top_rows(df, column, k, largest),
+                _ => sort_rows(df, &[(column.clone(), largest)], false, None),
+            };
+        }
+        (None, Some(keys)) if cli_args.group_by.is_none() => {
+            let n_sorted_rows = n_rows.filter(|_| counts_sorted_rows);
+            df = sort_rows(df, keys, cli_args.nulls_first, n_sorted_rows);
+        }
+        _ => {}
+    }
+    if drops_duplicates {
+        df = drop_duplicate_rows(df, cli_args);
+    }
+    if cli_args.reverse {
+        df = df.reverse();
+    }
+
+    let df = match n_rows {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    };
+    (df, n_matches)
+}
+
+// --------------------------------------------------
+// count how often each value of a column occurs, nulls included, from the
+// most common value, with the share of the rows of each. the first -n (or
+// 10 with --head) values are kept, and the number of values left out is
+// returned with them
+fn count_values(df: DataFrame, column: &str, cli_args: &CliArgs) -> (DataFrame, usize) {
+    let column = find_columns(&[column.to_string()], &df, "value-counts").swap_remove(0);
+    let n_rows = df.height().max(1) as f64;
+
+    let counts = df
+        .lazy()
+        .group_by([col(&column)])
+        .agg([count().alias("count")])
+        .with_column(
+            (col("count").cast(DataType::Float64) * lit(100.0) / lit(n_rows)).alias("percent"),
+        )
+        .sort_by_exprs([col("count"), col(&column)], [true, false], true, true)
+        .collect()
+        .unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to count the values of {}: {}", column, e))
+        });
+
+    let n_values = get_num_summary_rows(cli_args).unwrap_or(counts.height());
+    let n_omitted = counts.height().saturating_sub(n_values);
+    (counts.head(Some(n_values)), n_omitted)
+}
+
+// --------------------------------------------------
+// aggregate the groups of rows of --group-by with --agg, sorted by the
+// --sort keys or else from the largest first aggregate, and keep the first
+// -n (or 10 with --head) groups
+fn group_rows(df: DataFrame, group_by: &[String], cli_args: &CliArgs) -> DataFrame {
+    let columns: Vec<String> = df
+        .get_column_names()
         .into_iter()
         .map(String::from)
-        .collect()
+        .collect();
+    check_aggregate_columns(group_by, &cli_args.aggregates, &columns);
+
+    let df = aggregate_table(df, group_by, &cli_args.aggregates);
+    let df = match &cli_args.sort {
+        Some(keys) => sort_rows(df, keys, cli_args.nulls_first, None),
+        None => df,
+    };
+    match get_num_summary_rows(cli_args) {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    }
 }
 
 // --------------------------------------------------
-/// Configure Polars with ENV vars
-fn configure_the_environment(for_markdown: &bool) {
-    env::set_var("POLARS_FMT_TABLE_DATAFRAME_SHAPE_BELOW", "1"); // print shape information below the table.
-    env::set_var("POLARS_FMT_MAX_ROWS", "-1"); // maximum number of rows shown when formatting DataFrames.
-    if *for_markdown {
-        env::set_var("POLARS_FMT_TABLE_FORMATTING", "ASCII_MARKDOWN"); // define styling of tables using any of the following options.
-        env::set_var("POLARS_FMT_MAX_COLS", "-1"); // maximum number of columns shown when formatting DataFrames.
-    } else {
-        env::set_var("POLARS_FMT_TABLE_ROUNDED_CORNERS", "1"); // apply rounded corners to UTF8-styled tables.
+// the number of values counted or groups to print, which -n and --head
+// limit in place of the rows
+fn get_num_summary_rows(cli_args: &CliArgs) -> Option<usize> {
+    match (cli_args.max_rows, cli_args.head) {
+        (Some(n), _) => Some(n as usize),
+        (None, true) => Some(10),
+        (None, false) => None,
     }
 }
 
 // --------------------------------------------------
-fn main() -> () {
-    let cli_args: CliArgs = get_args();
+// run the --sql query against the inputs, each parsed in full as its own
+// table, and keep the first rows of the result
+fn query_inputs(query: &str, cli_args: &CliArgs, n_rows: Option<usize>) -> DataFrame {
+    let tables: Vec<(String, DataFrame)> = cli_args
+        .filepaths
+        .iter()
+        .map(|filepath| {
+            let df = parse_inputs(std::slice::from_ref(filepath), cli_args, None);
+            (get_query_table_name(filepath), rename_columns(df, cli_args))
+        })
+        .collect();
+    let df = run_query(tables, query);
+    match n_rows {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    }
+}
 
-    configure_the_environment(&cli_args.markdown);
+// --------------------------------------------------
+// whether the columns of an input can be read before the input itself,
+// which streams such as stdin and urls cannot
+fn has_readable_columns(filepath: &str) -> bool {
+    is_cloud_url(filepath) || is_parquet_dataset(filepath) || Path::new(filepath).is_file()
+}
 
-    let n_rows = get_num_rows_to_parse(
-        cli_args.max_rows,
-        cli_args.head,
-        cli_args.tail,
-        cli_args.sample,
-        cli_args.column_names_only,
-    );
+// --------------------------------------------------
+// replace the struct columns of a table with a column per field, named e.g.
+// address.city, down to the fields that are not structs
+fn flatten_structs(df: DataFrame) -> DataFrame {
+    fn flatten(series: &Series, name: &str, columns: &mut Vec<Series>) {
+        match series.struct_() {
+            Ok(fields) => {
+                for field in fields.fields() {
+                    flatten(field, &format!("{}.{}", name, field.name()), columns);
+                }
+            }
+            Err(_) => columns.push(series.clone().with_name(name)),
+        }
+    }
 
-    let file_format = get_format_from_filename(&cli_args.filepath);
-    let delimiter = get_delimiter(file_format, cli_args.delimiter);
+    let mut columns = Vec::<Series>::new();
+    for series in df.get_columns() {
+        flatten(series, series.name(), &mut columns);
+    }
+    DataFrame::new(columns)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to flatten the structs: {}", e)))
+}
 
-    let df = {
-        if cli_args.filepath == String::from("-") {
-            parse_from_stdin(
-                cli_args.selected_columns,
-                n_rows,
-                delimiter,
-                !cli_args.no_header,
+// --------------------------------------------------
+// turn each element of a list column into a row, repeating the other values
+// of its row. empty and null lists become a row with a null
+fn explode_column(df: DataFrame, column: &str) -> DataFrame {
+    match df.column(column).map(|series| series.dtype()) {
+        Ok(DataType::List(_)) => df
+            .explode([column])
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to explode {}: {}", column, e))),
+        Ok(dtype) => exit_with_error(&format!(
+            "--explode: the column {} holds {} values, not lists",
+            column, dtype
+        )),
+        Err(_) => exit_with_error(&format!("--explode: no column named {}", column)),
+    }
+}
+
+// --------------------------------------------------
+// the table to print or write: its summary statistics, its tail, its head
+// and tail, a sample of it, its rows shuffled, or the whole table, after its
+// structs are flattened and its list column exploded. the head and tail are
+// returned with the gap between them: the first row of the tail, and the
+// number of rows of the whole table
+fn get_table_view(mut df: DataFrame, cli_args: &CliArgs) -> (DataFrame, Option<(usize, usize)>) {
+    if cli_args.flatten_structs {
+        df = flatten_structs(df);
+    }
+    if let Some(column) = &cli_args.explode {
+        df = explode_column(df, column);
+    }
+
+    // describe the table, though not its row numbers or its nested columns,
+    // which have no statistics
+    if cli_args.describe {
+        let columns: Vec<Series> = df
+            .get_columns()
+            .iter()
+            .filter(|series| !(cli_args.index && series.name() == "row"))
+            .filter(|series| !matches!(series.dtype(), DataType::List(_) | DataType::Struct(_)))
+            .cloned()
+            .collect();
+        let df = DataFrame::new(columns)
+            .and_then(|df| df.describe(None))
+            .expect("Unable to get summary statistics");
+        return (df, None);
+    }
+
+    // keep the head and the tail, or the whole table when they meet
+    let n_rows = cli_args.max_rows.map_or(10, |n| n as usize);
+    if cli_args.head && cli_args.tail {
+        let n_total_rows = df.height();
+        if n_total_rows <= 2 * n_rows {
+            return (df, None);
+        }
+        let df = df
+            .head(Some(n_rows))
+            .vstack(&df.tail(Some(n_rows)))
+            .expect("the head and the tail share the columns");
+        return (df, Some((n_rows, n_total_rows)));
+    }
+
+    // keep the tail
+    if cli_args.tail {
+        return (df.tail(Some(n_rows)), None);
+    }
+
+    // keep a sample, of n rows or of a fraction of them. without
+    // replacement, a sample of more rows than the table is the whole table
+    if cli_args.sample || cli_args.sample_frac.is_some() {
+        let sample_size = match (cli_args.sample_frac, cli_args.max_rows) {
+            (Some(fraction), _) => (df.height() as f64 * fraction).round() as usize,
+            (None, Some(n)) => n as usize,
+            (None, None) => df.height(),
+        };
+        let sample_size = match cli_args.with_replacement {
+            true => sample_size,
+            false => sample_size.min(df.height()),
+        };
+        let df = df
+            .sample_n_literal(sample_size, cli_args.with_replacement, false, cli_args.seed)
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to sample the rows: {}", e)));
+        return (df, None);
+    }
+
+    // shuffle the rows, and keep the first n of them
+    if cli_args.shuffle {
+        let df = df
+            .sample_n_literal(df.height(), false, true, cli_args.seed)
+            .unwrap_or_else(|e| exit_with_error(&format!("Unable to shuffle the rows: {}", e)));
+        return match cli_args.max_rows {
+            Some(n) => (df.head(Some(n as usize)), None),
+            None => (df, None),
+        };
+    }
+
+    (df, None)
+}
+
+// --------------------------------------------------
+// turn the columns of a table into rows, with the former column names as the
+// first column. the values of the header column, if any, name the new
+// columns. columns of different types (other than numbers, which share a
+// type) are transposed as text
+fn transpose_table(
+    df: DataFrame,
+    header: Option<&str>,
+    float_precision: Option<usize>,
+) -> DataFrame {
+    let names: Option<Vec<String>> = header.map(|header| {
+        let column = df.column(header).unwrap_or_else(|_| {
+            exit_with_error(&format!("--transpose-header: no column named {}", header))
+        });
+        format_column(column, float_precision)
+            .into_iter()
+            .map(|name| name.unwrap_or_else(|| String::from("null")))
+            .collect()
+    });
+    let df = match header {
+        Some(header) => df.drop(header).expect("the header column exists"),
+        None => df,
+    };
+
+    let dtypes = df.dtypes();
+    let same_type =
+        dtypes.windows(2).all(|pair| pair[0] == pair[1]) || dtypes.iter().all(DataType::is_numeric);
+    let df = match same_type {
+        true => df,
+        false => DataFrame::new(
+            df.get_columns()
+                .iter()
+                .map(|series| Series::new(series.name(), format_column(series, float_precision)))
+                .collect(),
+        )
+        .expect("Unable to transpose the table"),
+    };
+
+    let mut transposed = df
+        .transpose(Some("column"), None)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to transpose the table: {}", e)));
+    if let Some(names) = names {
+        let names: Vec<String> = std::iter::once(String::from("column"))
+            .chain(names)
+            .collect();
+        transposed.set_column_names(&names).unwrap_or_else(|_| {
+            exit_with_error(
+                "Unable to transpose the table: the values of the --transpose-header column are not unique",
             )
-        } else {
-            if !PathBuf::from(cli_args.filepath.clone()).is_file() {
-                panic!("File not found at {}", cli_args.filepath);
+        });
+    }
+    transposed
+}
+
+// --------------------------------------------------
+// print a table (as json with --output-format), or write it to the --output
+// file and print it only with --tee
+fn print_table(
+    df: DataFrame,
+    n_matches: Option<usize>,
+    file_columns: Option<&[String]>,
+    cli_args: &CliArgs,
+) {
+    // the values of a column are counted, or the groups of rows
+    // aggregated, in place of the table
+    let (df, n_omitted_values) = match (&cli_args.value_counts, &cli_args.group_by) {
+        (Some(column), _) => count_values(df, column, cli_args),
+        (None, Some(group_by)) => (group_rows(df, group_by, cli_args), 0),
+        (None, None) => (df, 0),
+    };
+    let (mut df, gap) = get_table_view(df, cli_args);
+    if cli_args.transpose {
+        let header = match (&cli_args.transpose_header, cli_args.describe) {
+            (Some(column), _) => Some(column.as_str()),
+            (None, true) => Some("describe"),
+            (None, false) => None,
+        };
+        df = transpose_table(df, header, cli_args.float_precision);
+    }
+
+    if let Some(output) = &cli_args.output {
+        write_table(
+            &mut df,
+            output,
+            &cli_args.ext_map,
+            cli_args.force,
+            cli_args.compression.as_deref(),
+            cli_args.row_group_size,
+            cli_args.sheet_name.as_deref(),
+        );
+        if !cli_args.tee {
+            return;
+        }
+    }
+
+    // the column that leads each chunk, found before its name is truncated
+    let id_column = cli_args.id_column.as_ref().map(|name| {
+        df.get_column_index(name)
+            .unwrap_or_else(|| exit_with_error(&format!("--id-column: no column named {}", name)))
+    });
+
+    // the footer of the printed table, aggregating the rows that it shows,
+    // or counting the nulls of the null map
+    let mut footer = cli_args.totals.map(|aggregate| get_totals(&df, aggregate));
+    if cli_args.null_map {
+        let null_counts;
+        (df, null_counts) = get_null_map(&df);
+        footer = Some(null_counts);
+    }
+
+    // the values equal to the one above them, found before they are
+    // truncated, by the column that holds them
+    let repeats: Vec<Vec<bool>> = match &cli_args.elide_repeats {
+        None => Vec::new(),
+        Some(names) => {
+            for name in names {
+                if df.get_column_index(name).is_none() {
+                    exit_with_error(&format!("--elide-repeats: no column named {}", name));
+                }
             }
+            df.get_columns()
+                .iter()
+                .map(|series| {
+                    match names.is_empty() || names.contains(&series.name().to_string()) {
+                        true => get_repeats(series),
+                        false => Vec::new(),
+                    }
+                })
+                .collect()
+        }
+    };
 
-            match file_format {
-                Some(&FileFormat::Parquet) => {
-                    parse_parquet_file(&cli_args.filepath, cli_args.selected_columns, n_rows)
+    // the alignments of the columns, decided by their types before they are
+    // printed as text
+    let alignments: Vec<Alignment> = match &cli_args.align {
+        Some(Alignments::Columns(alignments)) if alignments.len() != df.width() => {
+            exit_with_error(&format!(
+                "--align gives {} alignments for {} columns",
+                alignments.len(),
+                df.width()
+            ))
+        }
+        Some(Alignments::Columns(alignments)) => alignments.clone(),
+        Some(Alignments::Named(alignments)) => {
+            for (name, _) in alignments {
+                if df.get_column_index(name).is_none() {
+                    exit_with_error(&format!("--align: no column named {}", name));
                 }
-                None => panic!(),
-                _ => parse_csv_file(
-                    &cli_args.filepath,
-                    cli_args.selected_columns,
-                    n_rows,
-                    delimiter,
-                    !cli_args.no_header,
-                ),
             }
+            df.get_columns()
+                .iter()
+                .map(|series| {
+                    alignments
+                        .iter()
+                        .rev()
+                        .find(|(name, _)| name == series.name())
+                        .map_or_else(
+                            || get_alignment(series.dtype()),
+                            |(_, alignment)| *alignment,
+                        )
+                })
+                .collect()
+        }
+        None => df.dtypes().iter().map(get_alignment).collect(),
+    };
+
+    // the humanized columns, likewise found before truncation
+    let humanized: Vec<(Humanize, usize)> = cli_args
+        .humanize
+        .iter()
+        .map(|(humanize, name)| match df.get_column_index(name) {
+            Some(index) => (*humanize, index),
+            None => exit_with_error(&format!("--humanize: no column named {}", name)),
+        })
+        .collect();
+
+    // lists and structs are printed as text, which is truncated like any
+    // other. the json output and the arrow stream keep them nested
+    if !matches!(
+        cli_args.output_format,
+        OutputFormat::Csv
+            | OutputFormat::Json
+            | OutputFormat::JsonLines
+            | OutputFormat::ArrowStream
+    ) {
+        df = format_nested(df);
+    }
+
+    // long values are truncated in the tables meant to be read on screen,
+    // and only on request in records
+    let max_col_width = match cli_args.output_format {
+        _ if cli_args.no_truncate => None,
+        OutputFormat::Table | OutputFormat::Plain => {
+            Some(cli_args.max_col_width.unwrap_or(DEFAULT_MAX_COL_WIDTH))
         }
+        OutputFormat::Vertical => cli_args.max_col_width,
+        _ => None,
+    };
+    // the columns of the file keep their numbers, found before their names
+    // are truncated
+    let numbers: Vec<Option<usize>> = match (file_columns, cli_args.output_format) {
+        (Some(file_columns), OutputFormat::Table | OutputFormat::Plain) => df
+            .get_column_names()
+            .iter()
+            .map(|name| file_columns.iter().position(|column| column == name))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Some(max_col_width) = max_col_width {
+        df = truncate_table(df, max_col_width, cli_args.truncation);
+    }
+    if !numbers.is_empty() {
+        df = number_columns(df, &numbers);
+        footer = footer.map(|footer| number_columns(footer, &numbers));
+    }
+    let truncated_names: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    // the text columns that are shrunk to fit the table, known before the
+    // floats become text in scientific notation
+    let text_columns: Vec<String> = df
+        .get_columns()
+        .iter()
+        .filter(|series| series.dtype() == &DataType::Utf8)
+        .map(|series| series.name().to_string())
+        .collect();
+
+    // binary values are printed as text, and the csv, json, and sql output
+    // write them as hex, base64, and blob literals
+    if !matches!(
+        cli_args.output_format,
+        OutputFormat::Csv
+            | OutputFormat::Json
+            | OutputFormat::JsonLines
+            | OutputFormat::Sql
+            | OutputFormat::ArrowStream
+    ) {
+        let max_width = cli_args.max_col_width.unwrap_or(DEFAULT_MAX_COL_WIDTH);
+        df = format_binary(df, cli_args.binary, max_width);
+    }
+
+    // polars only writes floats in scientific notation past a fixed bound
+    let magnitude = match cli_args.notation {
+        Notation::Scientific => Some(None),
+        Notation::ScientificAbove(magnitude) => Some(Some(magnitude)),
+        Notation::Auto | Notation::Fixed => None,
+    };
+    if let (Some(magnitude), OutputFormat::Table | OutputFormat::Plain) =
+        (magnitude, cli_args.output_format)
+    {
+        df = format_scientific(df, magnitude, cli_args.float_precision);
+        footer =
+            footer.map(|footer| format_scientific(footer, magnitude, cli_args.float_precision));
+    }
+
+    // the dates and times of the printed views are written as asked
+    let is_view = !matches!(
+        cli_args.output_format,
+        OutputFormat::Csv
+            | OutputFormat::Json
+            | OutputFormat::JsonLines
+            | OutputFormat::Html
+            | OutputFormat::Latex
+            | OutputFormat::Sql
+            | OutputFormat::ArrowStream
+    );
+    if is_view && (cli_args.datetime_format.is_some() || cli_args.local_time) {
+        df = format_datetimes(df, cli_args.datetime_format.as_deref(), cli_args.local_time);
+    }
+    if is_view && !humanized.is_empty() {
+        df = humanize_columns(df, &humanized, cli_args.duration_unit);
+        footer = footer.map(|footer| humanize_columns(footer, &humanized, cli_args.duration_unit));
+    }
+
+    // the pretty and plain tables are fitted to the terminal, unless all of
+    // their columns are to be printed, or split into chunks of the columns
+    // that fit. the shape of a head and tail is that of the whole table
+    let shape = match gap {
+        Some((_, n_total_rows)) => (n_total_rows, df.width()),
+        None => df.shape(),
+    };
+    let gap = gap.map(|(row, _)| row);
+    let mut omitted = Vec::<String>::new();
+    let mut chunks = Vec::<DataFrame>::new();
+    if let OutputFormat::Table | OutputFormat::Plain = cli_args.output_format {
+        let bordered = cli_args.output_format == OutputFormat::Table;
+        let table_width = match (cli_args.all_cols, cli_args.chunk_cols) {
+            (true, _) => u16::MAX as usize,
+            (false, true) => {
+                chunks = chunk_table(
+                    &df,
+                    cli_args.width,
+                    id_column,
+                    bordered,
+                    &text_columns,
+                    &cli_args.null_display,
+                    cli_args.truncation,
+                );
+                cli_args.width
+            }
+            (false, false) => {
+                (df, omitted) = fit_table(
+                    df,
+                    cli_args.width,
+                    cli_args.max_cols,
+                    bordered,
+                    &text_columns,
+                    &cli_args.null_display,
+                    cli_args.truncation,
+                );
+                cli_args.width
+            }
+        };
+        env::set_var("POLARS_TABLE_WIDTH", table_width.to_string());
+    }
+
+    // tables taller than the terminal are paged, records by their lines
+    let n_lines = match cli_args.output_format {
+        OutputFormat::Vertical => df.height() * (df.width() + 1),
+        _ => df.height() + gap.map_or(0, |_| 1),
+    } + footer.as_ref().map_or(0, |_| 2);
+    let is_tall = || get_terminal_height().is_some_and(|height| n_lines > height);
+    if cli_args.paging == Paging::Always || (cli_args.paging == Paging::Auto && is_tall()) {
+        start_pager();
+    }
+
+    // the footer keeps the columns of the fitted table or of each chunk
+    let select_footer = |df: &DataFrame| {
+        footer.as_ref().map(|footer| {
+            footer
+                .select(df.get_column_names())
+                .expect("the footer has the columns of the table")
+        })
+    };
+    // and so do the repeated values and the alignments
+    let select_layout = |df: &DataFrame| -> (Vec<Vec<bool>>, Vec<Alignment>) {
+        let positions: Vec<usize> = df
+            .get_column_names()
+            .iter()
+            .map(|name| {
+                truncated_names
+                    .iter()
+                    .position(|column| column == name)
+                    .expect("the columns are those of the table")
+            })
+            .collect();
+        let chunk_repeats = match repeats.is_empty() {
+            true => Vec::new(),
+            false => positions.iter().map(|&i| repeats[i].clone()).collect(),
+        };
+        (
+            chunk_repeats,
+            positions.iter().map(|&i| alignments[i]).collect(),
+        )
     };
+    match chunks.is_empty() {
+        true => {
+            let (repeats, alignments) = select_layout(&df);
+            print_view(
+                df.clone(),
+                select_footer(&df).as_ref(),
+                gap,
+                &repeats,
+                &alignments,
+                cli_args,
+            )
+        }
+        false => {
+            for (i, chunk) in chunks.into_iter().enumerate() {
+                if i > 0 {
+                    print_line("");
+                }
+                let chunk_footer = select_footer(&chunk);
+                let (chunk_repeats, chunk_alignments) = select_layout(&chunk);
+                print_view(
+                    chunk,
+                    chunk_footer.as_ref(),
+                    gap,
+                    &chunk_repeats,
+                    &chunk_alignments,
+                    cli_args,
+                );
+            }
+        }
+    }
+
+    if cli_args.output_format == OutputFormat::Table && !cli_args.no_shape {
+        match n_matches {
+            Some(n) => print_line(format!(
+                "shape: {}, {}",
+                format_shape(shape),
+                format_matches(n)
+            )),
+            None => print_line(format!("shape: {}", format_shape(shape))),
+        }
+    }
+    if !omitted.is_empty() {
+        print_line(format_omitted_columns(&omitted, cli_args.width));
+    }
+    // the values left out of the counts are noted under every printed view,
+    // after a blank line under the markup tables, which would otherwise take
+    // the note for one of their rows
+    if is_view && n_omitted_values > 0 {
+        if !matches!(
+            cli_args.output_format,
+            OutputFormat::Table | OutputFormat::Plain | OutputFormat::Vertical
+        ) {
+            print_line("");
+        }
+        print_line(format_omitted_values(n_omitted_values));
+    }
+}
+
+// --------------------------------------------------
+// print a table to stdout in the chosen format, with a footer in the
+// printed views, the columns of the tables aligned as given, and without
+// the repeated values of the pretty and plain tables, which mark the gap
+// between a head and a tail
+fn print_view(
+    df: DataFrame,
+    footer: Option<&DataFrame>,
+    gap: Option<usize>,
+    repeats: &[Vec<bool>],
+    alignments: &[Alignment],
+    cli_args: &CliArgs,
+) {
+    match cli_args.output_format {
+        OutputFormat::Table => print_pretty(
+            &df,
+            footer,
+            gap,
+            repeats,
+            alignments,
+            &cli_args.null_display,
+            cli_args.color,
+            cli_args.style,
+        ),
+        OutputFormat::Plain => print_plain(
+            &df,
+            footer,
+            gap,
+            repeats,
+            alignments,
+            &cli_args.null_display,
+        ),
+        OutputFormat::Markdown => print_markdown(
+            &df,
+            footer,
+            alignments,
+            cli_args.float_precision,
+            &cli_args.null_display,
+            cli_args.show_dtypes,
+        ),
+        OutputFormat::Org => print_org(
+            &df,
+            footer,
+            alignments,
+            cli_args.float_precision,
+            &cli_args.null_display,
+        ),
+        OutputFormat::Rst => print_rst(
+            &df,
+            footer,
+            alignments,
+            cli_args.float_precision,
+            &cli_args.null_display,
+        ),
+        OutputFormat::AsciiDoc => print_asciidoc(
+            &df,
+            footer,
+            alignments,
+            cli_args.float_precision,
+            &cli_args.null_display,
+        ),
+        OutputFormat::Typst => print_typst(
+            &df,
+            footer,
+            alignments,
+            cli_args.float_precision,
+            &cli_args.null_display,
+        ),
+        OutputFormat::Vertical => print_vertical(
+            &df,
+            footer,
+            cli_args.float_precision,
+            &cli_args.null_display,
+            cli_args.color,
+        ),
+        OutputFormat::Csv => print_delimited(&df, cli_args.output_delimiter as u8),
+        OutputFormat::Json => print_json(&df, false),
+        OutputFormat::JsonLines => print_json(&df, true),
+        OutputFormat::Html => print_html(
+            &df,
+            cli_args.html_class.as_deref(),
+            &cli_args.html_null,
+            cli_args.float_precision,
+        ),
+        OutputFormat::Latex => print_latex(&df, alignments, cli_args.float_precision),
+        OutputFormat::Sql => print_sql(
+            &df,
+            &cli_args.table_name,
+            cli_args.sql_dialect,
+            cli_args.sql_batch,
+        ),
+        OutputFormat::ArrowStream => print_arrow_stream(&df),
+    }
+}
+
+// --------------------------------------------------
+fn main() {
+    let mut cli_args: CliArgs = get_args();
+    cli_args.filepaths = expand_filepaths(cli_args.filepaths, cli_args.no_glob);
+    cli_args.filepaths = expand_directories(cli_args.filepaths);
+
+    configure_the_environment(cli_args.no_dtypes || cli_args.null_map);
+    // cells are only highlighted in color
+    if let (Some(regex), true) = (&cli_args.highlight, cli_args.color) {
+        set_highlight(regex.clone());
+    }
+    polars_core::fmt::set_float_precision(cli_args.float_precision);
+    polars_core::fmt::set_thousands_separator(cli_args.thousands_sep);
+    if cli_args.notation != Notation::Auto {
+        polars_core::fmt::set_float_fmt(polars_core::fmt::FloatFmt::Full);
+    }
+
+    let n_rows = get_num_rows_of_args(&cli_args);
+
+    // list the sheets of spreadsheets
+    if cli_args.list_sheets {
+        for filepath in &cli_args.filepaths {
+            if cli_args.filepaths.len() > 1 {
+                println!("{}:", filepath);
+            }
+            list_sheets(read_file(filepath), filepath);
+        }
+        return;
+    }
+
+    // list the tables when no table of the database was chosen
+    check_table_inputs(&cli_args);
+    if cli_args.table.is_none() && cli_args.query.is_none() {
+        let database = cli_args
+            .filepaths
+            .iter()
+            .find_map(|filepath| get_database_format(filepath, &cli_args).map(|f| (filepath, f)));
+        match database {
+            Some((filepath, FileFormat::DuckDb)) => {
+                println!("{:#?}", get_duckdb_table_names(filepath));
+                return;
+            }
+            Some((filepath, _)) => {
+                println!("{:#?}", get_table_names(filepath));
+                return;
+            }
+            None => {}
+        }
+    }
 
     // print column names
     if cli_args.column_names_only {
-        println!("{:#?}", get_column_names(df.clone()));
-        return ();
+        let first_filepath = &cli_args.filepaths[0];
+        let column_names = get_input_column_names(first_filepath, &cli_args);
+        for filepath in &cli_args.filepaths[1..] {
+            check_column_names_agree(
+                &get_input_column_names(filepath, &cli_args),
+                filepath,
+                &column_names,
+                first_filepath,
+            );
+        }
+        let column_names = get_renamed_columns(&column_names, &cli_args);
+        let column_names: Vec<String> = match cli_args.number_columns {
+            true => column_names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{}:{}", i + 1, name))
+                .collect(),
+            false => column_names,
+        };
+        println!("{:#?}", column_names);
+        return;
     }
 
-    // describe the table
-    if cli_args.describe {
-        println!(
-            "{}",
-            df.describe(None).expect("Unable to get summary statistics")
-        );
-        return ();
+    // the columns given by their index or a pattern, the excluded columns,
+    // and the columns of the types of --select-dtype, are found among the
+    // columns of the first file, read once, so that only the others are
+    // parsed. the columns of streams, and of stacked files that need not
+    // agree, are found once they are parsed, as are those of tables with
+    // renamed or derived columns
+    let by_pattern = |columns: &Option<Vec<String>>| {
+        columns
+            .as_ref()
+            .is_some_and(|columns| columns.iter().any(|column| is_column_pattern(column)))
+    };
+    let first_filepath = &cli_args.filepaths[0];
+    if (by_pattern(&cli_args.selected_columns)
+        || cli_args.excluded_columns.is_some()
+        || cli_args.select_dtypes.is_some())
+        && !cli_args.stacked
+        && !has_new_columns(&cli_args)
+        && has_readable_columns(first_filepath)
+    {
+        // the columns of the file are read without the selection
+        let selected_columns = cli_args.selected_columns.take();
+        let excluded_columns = cli_args.excluded_columns.take();
+        let select_dtypes = cli_args.select_dtypes.take();
+        cli_args.selected_columns = Some(match (selected_columns, select_dtypes) {
+            (Some(columns), _) => resolve_selected_columns(
+                &columns,
+                &get_input_column_names(first_filepath, &cli_args),
+                "select",
+            ),
+            (None, Some(kinds)) => resolve_dtype_columns(
+                &kinds,
+                &get_input_dtypes(first_filepath, &cli_args),
+                excluded_columns.as_deref(),
+                &cli_args,
+            ),
+            (None, None) => get_remaining_columns(
+                &excluded_columns.unwrap_or_default(),
+                &get_input_column_names(first_filepath, &cli_args),
+            ),
+        });
     }
 
-    // print tail
-    if cli_args.tail {
-        println!("{}", df.tail(None));
-        return ();
+    // the columns grouped by and aggregated are checked before the data is
+    // read, where the columns of the file can be and are those printed
+    if let (Some(group_by), false, true, true) = (
+        &cli_args.group_by,
+        cli_args.stacked,
+        !has_new_columns(&cli_args),
+        has_readable_columns(first_filepath),
+    ) {
+        let file_columns = get_input_column_names(first_filepath, &cli_args);
+        check_aggregate_columns(group_by, &cli_args.aggregates, &file_columns);
+    }
+
+    // the columns are numbered in the order of the file, so they are only
+    // selected once all of them are parsed, as are the columns left to find,
+    // those that the rows are filtered by, and those renamed or derived
+    let selected_columns = match cli_args.number_columns
+        || by_pattern(&cli_args.selected_columns)
+        || !cli_args.filters.is_empty()
+        || has_new_columns(&cli_args)
+    {
+        true => cli_args.selected_columns.take(),
+        false => None,
+    };
+
+    // the rows with nulls, and the duplicate rows, are left out of the
+    // printed columns, and the rows sorted, once all the rows are parsed, so
+    // that the rows are counted out of those left
+    let n_parsed_rows = match needs_all_rows(&cli_args) {
+        true => None,
+        false => n_rows,
+    };
+    let arrange = |df: DataFrame| match needs_all_rows(&cli_args) {
+        true => arrange_rows(df, &cli_args, n_rows),
+        false => (df, None),
+    };
+
+    if cli_args.copy {
+        start_copy();
     }
 
-    // print sample
-    if cli_args.sample {
-        let sample_size = {
-            if let Some(s_size) = cli_args.max_rows {
-                s_size as usize
-            } else {
-                df.height()
+    // print each input as its own table, as the schemas need not agree
+    if cli_args.stacked {
+        for (i, filepath) in cli_args.filepaths.iter().enumerate() {
+            if i > 0 {
+                print_line("");
             }
-        };
-        println!("{}", sample_size);
-        println!(
-            "{}",
-            df.sample_n_literal(sample_size, false, false, None)
-                .expect("Unable to get summary statistics")
-        );
-        return ();
+            match cli_args.output_format == OutputFormat::Markdown {
+                true => print_line(format!("### {}\n", filepath)),
+                false => print_line(format!("==> {} <==", filepath)),
+            }
+            let df =
+                parse_filtered_inputs(std::slice::from_ref(filepath), &cli_args, n_parsed_rows);
+            let (df, file_columns) =
+                select_numbered_columns(df, selected_columns.as_deref(), &cli_args);
+            let (df, n_matches) = arrange(df);
+            print_table(df, n_matches, file_columns.as_deref(), &cli_args);
+        }
+        finish_copy();
+        wait_for_pager();
+        return;
+    }
+
+    let df = match &cli_args.sql {
+        Some(query) => query_inputs(query, &cli_args, n_parsed_rows),
+        None => parse_filtered_inputs(&cli_args.filepaths, &cli_args, n_parsed_rows),
+    };
+    let (df, file_columns) = select_numbered_columns(df, selected_columns.as_deref(), &cli_args);
+    let (df, n_matches) = arrange(df);
+    print_table(df, n_matches, file_columns.as_deref(), &cli_args);
+    finish_copy();
+    wait_for_pager();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn cli_args(args: &[&str]) -> CliArgs {
+        get_args_from([&["printbl"], args].concat())
+    }
+
+    #[test]
+    fn detects_compression_and_format_from_extensions() {
+        let cases = [
+            ("data.csv", None, Some(FileFormat::Csv)),
+            ("data.CSV", None, Some(FileFormat::Csv)),
+            (
+                "data.tsv.bz2",
+                Some(Compression::Bzip2),
+                Some(FileFormat::Tsv),
+            ),
+            (
+                "data.csv.zst",
+                Some(Compression::Zstd),
+                Some(FileFormat::Csv),
+            ),
+            ("data.csv.xz", Some(Compression::Xz), Some(FileFormat::Csv)),
+            (
+                "data.jsonl.gz",
+                Some(Compression::Gzip),
+                Some(FileFormat::NdJson),
+            ),
+            (
+                "data.ndjson.zst",
+                Some(Compression::Zstd),
+                Some(FileFormat::NdJson),
+            ),
+            (
+                "data.json.gz",
+                Some(Compression::Gzip),
+                Some(FileFormat::Json),
+            ),
+            (
+                "data.Parquet.GZ",
+                Some(Compression::Gzip),
+                Some(FileFormat::Parquet),
+            ),
+            (
+                "data.feather.gz",
+                Some(Compression::Gzip),
+                Some(FileFormat::Ipc),
+            ),
+            ("data.arrows", None, Some(FileFormat::IpcStream)),
+            (
+                "data.psv.gz",
+                Some(Compression::Gzip),
+                Some(FileFormat::Csv),
+            ),
+            ("data.TXT.XZ", Some(Compression::Xz), Some(FileFormat::Csv)),
+            (
+                "dir/data.vcf.gz",
+                Some(Compression::Gzip),
+                Some(FileFormat::Vcf),
+            ),
+            ("data.weird.gz", Some(Compression::Gzip), None),
+            ("data.gz", Some(Compression::Gzip), None),
+            ("data", None, None),
+        ];
+        let args = cli_args(&[]);
+        for (path, compression, file_format) in cases {
+            assert_eq!(detect(path, &args), (compression, file_format), "{}", path);
+        }
+    }
+
+    #[test]
+    fn maps_every_extension_alias_to_its_format() {
+        for &(extension, file_format) in EXTENSION_FORMATS {
+            for filename in [
+                format!("data.{}", extension),
+                format!("data.{}", extension.to_ascii_uppercase()),
+                format!("data.{}.gz", extension),
+            ] {
+                let detected =
+                    get_format_from_filename(strip_compression_extension(&filename), &[]);
+                assert_eq!(detected, Some(file_format), "{}", filename);
+            }
+        }
+        assert_eq!(get_format_from_filename("data.unknown", &[]), None);
+    }
+
+    #[test]
+    fn lists_a_name_for_every_aliased_format() {
+        for &(extension, file_format) in EXTENSION_FORMATS {
+            assert!(
+                FORMAT_NAMES.iter().any(|&(_, named)| named == file_format),
+                "{}",
+                extension
+            );
+        }
+        for (name, file_format) in FORMAT_NAMES {
+            assert_eq!(get_format_from_name(name), Some(file_format));
+        }
+    }
+
+    #[test]
+    fn keeps_the_head_and_tail_or_the_whole_table_when_they_meet() {
+        let args = cli_args(&["data.csv", "--head", "--tail", "-n", "3"]);
+        let cases = [
+            (3, vec![1, 2, 3], None),
+            (4, vec![1, 2, 3, 4], None),
+            (6, vec![1, 2, 3, 4, 5, 6], None),
+            (7, vec![1, 2, 3, 5, 6, 7], Some((3, 7))),
+        ];
+        for (height, rows, gap) in cases {
+            let df = df!("i" => (1..=height).collect::<Vec<i64>>()).unwrap();
+            let (view, view_gap) = get_table_view(df, &args);
+            let view_rows: Vec<i64> = view
+                .column("i")
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect();
+            assert_eq!((view_rows, view_gap), (rows, gap), "{} rows", height);
+        }
     }
 
-    // print entire df
-    println!("{}", df);
+    #[test]
+    fn sniffs_the_delimiter_of_unknown_compressed_formats() {
+        // tab and comma both split each line in two, and tab is preferred
+        let cases = [
+            ("data.weird.gz", "a\tb,c\n1\t2,3\n", ["a", "b,c"]),
+            ("data.weird.gz", "a;b\n1;2\n", ["a", "b"]),
+            ("data.gz", "a|b\n1|2\n", ["a", "b"]),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        for (name, text, column_names) in cases {
+            let path = dir.path().join(name);
+            let mut encoder =
+                flate2::write::GzEncoder::new(File::create(&path).unwrap(), Default::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+
+            let path = path.to_str().unwrap();
+            let df = parse_input(path, &cli_args(&[path]), None);
+            assert_eq!(df.get_column_names(), column_names, "{:?}", text);
+        }
+    }
 }
@@ -0,0 +1,2422 @@
+use crate::excel::{write_excel, EXCEL_MAX_COLUMNS, EXCEL_MAX_ROWS};
+use crate::{
+    exit_with_error, get_extension, get_format_from_filename, get_mapped_delimiter, FileFormat,
+    FORMAT_NAMES,
+};
+use base64::Engine;
+use chrono::{Local, TimeZone, Utc};
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{
+    Attribute, Cell, CellAlignment, Color, ColumnConstraint, ContentArrangement, Row, Table, Width,
+};
+use polars::export::arrow::temporal_conversions::{
+    date32_to_date, time64ns_to_time, timestamp_ms_to_datetime, timestamp_ns_to_datetime,
+    timestamp_us_to_datetime,
+};
+use polars::prelude::*;
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::sync::OnceLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// the narrowest that the text columns are shrunk to when fitting a table
+const MIN_FIT_WIDTH: usize = 8;
+// the number of elements of a list that are printed
+const MAX_LIST_ITEMS: usize = 3;
+// the markers of the present and null values of the null map
+const PRESENT_MARKER: &str = "·";
+const NULL_MARKER: &str = "✗";
+// the row of the pretty and plain tables between the head and the tail
+const GAP_MARKER: &str = "⋯";
+
+// the pattern of the cells that are highlighted in the colored tables
+static HIGHLIGHT: OnceLock<Regex> = OnceLock::new();
+
+// --------------------------------------------------
+// how a table is printed to stdout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Plain,
+    Markdown,
+    Org,
+    Rst,
+    AsciiDoc,
+    Typst,
+    Vertical,
+    Csv,
+    Json,
+    JsonLines,
+    Html,
+    Latex,
+    Sql,
+    ArrowStream,
+}
+
+// the number of rows written at a time as json lines
+const JSON_LINES_BATCH: usize = 1024;
+
+// --------------------------------------------------
+// get the delimiter of delimited output from its extension
+fn get_output_delimiter(filepath: &str, file_format: FileFormat, ext_map: &[(String, char)]) -> u8 {
+    let extension = get_extension(filepath);
+    if let Some(delimiter) = extension
+        .as_deref()
+        .and_then(|extension| get_mapped_delimiter(extension, ext_map))
+    {
+        return delimiter as u8;
+    }
+
+    match (file_format, extension.as_deref()) {
+        (FileFormat::Tsv, _) => b'\t',
+        (_, Some("psv")) => b'|',
+        _ => b',',
+    }
+}
+
+// --------------------------------------------------
+// create the output file, which only replaces an existing file when forced
+fn create_output_file(filepath: &str, force: bool) -> File {
+    let file = match force {
+        true => File::create(filepath),
+        false => File::create_new(filepath),
+    };
+
+    file.unwrap_or_else(|e| match e.kind() {
+        ErrorKind::AlreadyExists => exit_with_error(&format!(
+            "{} already exists, use --force to overwrite it",
+            filepath
+        )),
+        _ => exit_with_error(&format!("Unable to create {}: {}", filepath, e)),
+    })
+}
+
+// --------------------------------------------------
+// the parquet compression named by --compression
+fn get_parquet_compression(name: &str) -> ParquetCompression {
+    match name {
+        "snappy" => ParquetCompression::Snappy,
+        "lz4" => ParquetCompression::Lz4Raw,
+        "gzip" => ParquetCompression::Gzip(None),
+        "brotli" => ParquetCompression::Brotli(None),
+        "uncompressed" => ParquetCompression::Uncompressed,
+        _ => ParquetCompression::Zstd(None),
+    }
+}
+
+// --------------------------------------------------
+// write a table to a file, in the format given by its extension. a file
+// that could not be written in full is removed
+pub fn write_table(
+    df: &mut DataFrame,
+    filepath: &str,
+    ext_map: &[(String, char)],
+    force: bool,
+    compression: Option<&str>,
+    row_group_size: Option<usize>,
+    sheet_name: Option<&str>,
+) {
+    let file_format = get_format_from_filename(filepath, ext_map).unwrap_or_else(|| {
+        exit_with_error(&format!(
+            "Unable to infer the output format from the extension of {}",
+            filepath
+        ))
+    });
+
+    // check the format before anything is created
+    let writable = matches!(
+        file_format,
+        FileFormat::Csv
+            | FileFormat::Tsv
+            | FileFormat::Parquet
+            | FileFormat::Json
+            | FileFormat::NdJson
+            | FileFormat::Ipc
+            | FileFormat::IpcStream
+    ) || (cfg!(feature = "avro") && file_format == FileFormat::Avro)
+        || (file_format == FileFormat::Excel && get_extension(filepath).as_deref() == Some("xlsx"));
+    if !writable {
+        let name = FORMAT_NAMES
+            .iter()
+            .find(|(_, format)| *format == file_format)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        exit_with_error(&format!(
+            "Unable to write {}: {} output is not supported",
+            filepath, name
+        ));
+    }
+    if file_format != FileFormat::Parquet {
+        for (option, given) in [
+            ("compression", compression.is_some()),
+            ("row-group-size", row_group_size.is_some()),
+        ] {
+            if given {
+                exit_with_error(&format!("--{} only applies to parquet output", option));
+            }
+        }
+    }
+    if file_format != FileFormat::Excel && sheet_name.is_some() {
+        exit_with_error("--sheet-name only applies to xlsx output");
+    }
+    // excel sheets cannot hold more, and would be cut short
+    if file_format == FileFormat::Excel
+        && (df.height() >= EXCEL_MAX_ROWS || df.width() > EXCEL_MAX_COLUMNS)
+    {
+        exit_with_error(&format!(
+            "Unable to write {}: the table has {} rows and {} columns, and an Excel sheet holds at most {} rows below the header and {} columns (select fewer with -n or --select)",
+            filepath,
+            df.height(),
+            df.width(),
+            EXCEL_MAX_ROWS - 1,
+            EXCEL_MAX_COLUMNS
+        ));
+    }
+
+    // text formats have no binary values, so they are written as text
+    let mut encoded = match file_format {
+        FileFormat::Json | FileFormat::NdJson => {
+            Some(format_binary(df.clone(), BinaryDisplay::Base64, 0))
+        }
+        FileFormat::Csv | FileFormat::Tsv | FileFormat::Excel => {
+            Some(format_binary(df.clone(), BinaryDisplay::FullHex, 0))
+        }
+        _ => None,
+    };
+    let df = encoded.as_mut().unwrap_or(df);
+
+    let file = create_output_file(filepath, force);
+    let result = match file_format {
+        FileFormat::Parquet => ParquetWriter::new(file)
+            .with_compression(get_parquet_compression(compression.unwrap_or_default()))
+            .with_row_group_size(row_group_size)
+            .finish(df)
+            .map(|_| ()),
+        FileFormat::Json => JsonWriter::new(file)
+            .with_json_format(JsonFormat::Json)
+            .finish(df),
+        FileFormat::NdJson => JsonWriter::new(file)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(df),
+        FileFormat::Ipc => IpcWriter::new(file).finish(df),
+        FileFormat::IpcStream => IpcStreamWriter::new(file).finish(df),
+        FileFormat::Excel => write_excel(df, file, sheet_name)
+            .map_err(|e| PolarsError::ComputeError(e.to_string().into())),
+        #[cfg(feature = "avro")]
+        FileFormat::Avro => polars::io::avro::AvroWriter::new(file).finish(df),
+        _ => CsvWriter::new(file)
+            .with_separator(get_output_delimiter(filepath, file_format, ext_map))
+            .finish(df),
+    };
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(filepath);
+        exit_with_error(&format!("Unable to write {}: {}", filepath, e));
+    }
+}
+
+// --------------------------------------------------
+// format the dates and times of a table as iso 8601 strings, which the json
+// writer would separate with a space. datetimes with a time zone are stored
+// as utc, so they are marked as such
+fn format_iso_dates(df: &DataFrame) -> DataFrame {
+    let format_error =
+        |name: &str| -> ! { exit_with_error(&format!("Unable to format the column {}", name)) };
+
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            let formatted = match series.dtype() {
+                DataType::Date => series
+                    .date()
+                    .map(|dates| dates.to_string("%Y-%m-%d").into_series()),
+                DataType::Datetime(_, time_zone) => {
+                    let format = match time_zone {
+                        Some(_) => "%Y-%m-%dT%H:%M:%S%.fZ",
+                        None => "%Y-%m-%dT%H:%M:%S%.f",
+                    };
+                    series
+                        .datetime()
+                        .and_then(|datetimes| datetimes.to_string(format))
+                        .map(IntoSeries::into_series)
+                }
+                DataType::Time => series.cast(&DataType::Utf8),
+                _ => Ok(series.clone()),
+            };
+            formatted.unwrap_or_else(|_| format_error(series.name()))
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to format the dates of the table")
+}
+
+// --------------------------------------------------
+// exit once a table could not be printed. a closed pipe (e.g. into head)
+// only means that no more output is wanted
+fn check_printed(result: PolarsResult<()>, format_name: &str) {
+    match result {
+        Ok(()) => {}
+        Err(PolarsError::Io(e)) if e.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(e) => exit_with_error(&format!(
+            "Unable to print the table as {}: {}",
+            format_name, e
+        )),
+    }
+}
+
+// --------------------------------------------------
+// print a line to stdout, exiting quietly once stdout is closed, e.g. when
+// the pager is quit early
+pub fn print_line(line: impl std::fmt::Display) {
+    let result = writeln!(std::io::stdout().lock(), "{}", line).map_err(PolarsError::from);
+    check_printed(result, "a table");
+}
+
+// --------------------------------------------------
+// warn about the floats that json has no representation for, which are
+// written as null
+fn warn_non_finite(df: &DataFrame) {
+    for series in df.get_columns() {
+        if !series.dtype().is_float() {
+            continue;
+        }
+        let n_non_finite = series
+            .cast(&DataType::Float64)
+            .expect("floats can be cast to f64")
+            .f64()
+            .expect("the column was cast to f64")
+            .into_iter()
+            .flatten()
+            .filter(|value| !value.is_finite())
+            .count();
+        if n_non_finite > 0 {
+            eprintln!(
+                "Warning: {} NaN or infinite values of the column {} are written as null",
+                n_non_finite,
+                series.name()
+            );
+        }
+    }
+}
+
+// --------------------------------------------------
+// print a table as a json array of row objects, or as one object per line.
+// the lines are written a batch of rows at a time, so that a reader that
+// stops early (e.g. head) does not wait for the rest
+pub fn print_json(df: &DataFrame, json_lines: bool) {
+    let mut df = format_binary(format_iso_dates(df), BinaryDisplay::Base64, 0);
+    warn_non_finite(&df);
+
+    let mut stdout = std::io::stdout().lock();
+    let result = match json_lines {
+        true => (0..df.height())
+            .step_by(JSON_LINES_BATCH)
+            .try_for_each(|offset| {
+                JsonWriter::new(&mut stdout)
+                    .with_json_format(JsonFormat::JsonLines)
+                    .finish(&mut df.slice(offset as i64, JSON_LINES_BATCH))
+            }),
+        false => JsonWriter::new(&mut stdout)
+            .with_json_format(JsonFormat::Json)
+            .finish(&mut df)
+            .and_then(|_| writeln!(stdout).map_err(PolarsError::from)),
+    };
+    check_printed(result, "JSON");
+}
+
+// --------------------------------------------------
+// write a table as an arrow ipc stream, which keeps its types, for another
+// tool to read
+pub fn print_arrow_stream(df: &DataFrame) {
+    let mut df = df.clone();
+    let result = IpcStreamWriter::new(std::io::stdout().lock()).finish(&mut df);
+    check_printed(result, "an Arrow stream");
+}
+
+// --------------------------------------------------
+// print a table as delimited text, quoting the fields that hold the
+// delimiter, quotes, or line breaks. binary values are written in hex
+pub fn print_delimited(df: &DataFrame, delimiter: u8) {
+    let mut df = format_binary(df.clone(), BinaryDisplay::FullHex, 0);
+    let result = CsvWriter::new(std::io::stdout().lock())
+        .with_separator(delimiter)
+        .finish(&mut df);
+    check_printed(result, "delimited text");
+}
+
+// --------------------------------------------------
+// escape text for an html element or a quoted attribute, keeping its line
+// breaks
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+// --------------------------------------------------
+// the values of a column as text, with nulls left out and floats rounded to
+// the given number of decimals
+pub fn format_column(series: &Series, float_precision: Option<usize>) -> Vec<Option<String>> {
+    if let (Some(precision), true) = (float_precision, series.dtype().is_float()) {
+        let values = series
+            .cast(&DataType::Float64)
+            .expect("floats can be cast to f64");
+        return values
+            .f64()
+            .expect("the column was cast to f64")
+            .into_iter()
+            .map(|value| value.map(|value| format!("{:.*}", precision, value)))
+            .collect();
+    }
+
+    match series.cast(&DataType::Utf8) {
+        Ok(values) => values
+            .utf8()
+            .expect("the column was cast to text")
+            .into_iter()
+            .map(|value| value.map(String::from))
+            .collect(),
+        // nested values have no cast to text, so show them as printed
+        Err(_) => series
+            .iter()
+            .map(|value| match value {
+                AnyValue::Null => None,
+                value => Some(value.to_string()),
+            })
+            .collect(),
+    }
+}
+
+// --------------------------------------------------
+// print a table as an html <table>, aligning numeric columns to the right
+// with an inline style so that it survives being pasted into wikis
+pub fn print_html(
+    df: &DataFrame,
+    class: Option<&str>,
+    null_value: &str,
+    float_precision: Option<usize>,
+) {
+    let styles: Vec<&str> = df
+        .dtypes()
+        .iter()
+        .map(|dtype| match dtype.is_numeric() {
+            true => " style=\"text-align: right\"",
+            false => "",
+        })
+        .collect();
+    let columns: Vec<Vec<Option<String>>> = df
+        .get_columns()
+        .iter()
+        .map(|series| format_column(series, float_precision))
+        .collect();
+
+    let mut html = match class {
+        Some(class) => format!("<table class=\"{}\">\n", escape_html(class)),
+        None => String::from("<table>\n"),
+    };
+
+    html.push_str("<thead>\n<tr>");
+    for (name, style) in df.get_column_names().iter().zip(&styles) {
+        html.push_str(&format!("<th{}>{}</th>", style, escape_html(name)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for i in 0..df.height() {
+        html.push_str("<tr>");
+        for (column, style) in columns.iter().zip(&styles) {
+            let value = column[i].as_deref().unwrap_or(null_value);
+            html.push_str(&format!("<td{}>{}</td>", style, escape_html(value)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>");
+
+    let result = writeln!(std::io::stdout().lock(), "{}", html).map_err(PolarsError::from);
+    check_printed(result, "HTML");
+}
+
+// --------------------------------------------------
+// escape the characters that latex treats specially
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// --------------------------------------------------
+// print a table as a booktabs tabular environment, with the columns aligned
+// as given
+pub fn print_latex(df: &DataFrame, alignments: &[Alignment], float_precision: Option<usize>) {
+    let align: String = alignments
+        .iter()
+        .map(|alignment| match alignment {
+            Alignment::Left => 'l',
+            Alignment::Center => 'c',
+            Alignment::Right => 'r',
+        })
+        .collect();
+    let columns: Vec<Vec<Option<String>>> = df
+        .get_columns()
+        .iter()
+        .map(|series| format_column(series, float_precision))
+        .collect();
+
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| escape_latex(name))
+        .collect();
+    let mut latex = format!(
+        "\\begin{{tabular}}{{{}}}\n\\toprule\n{} \\\\\n\\midrule\n",
+        align,
+        names.join(" & ")
+    );
+    for i in 0..df.height() {
+        let values: Vec<String> = columns
+            .iter()
+            .map(|column| escape_latex(column[i].as_deref().unwrap_or_default()))
+            .collect();
+        latex.push_str(&format!("{} \\\\\n", values.join(" & ")));
+    }
+    latex.push_str("\\bottomrule\n\\end{tabular}");
+
+    let result = writeln!(std::io::stdout().lock(), "{}", latex).map_err(PolarsError::from);
+    check_printed(result, "LaTeX");
+}
+
+// --------------------------------------------------
+// the database that the printed insert statements are meant for, which
+// decides how identifiers are quoted and how booleans are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqlDialect {
+    Postgres,
+    Sqlite,
+    Mysql,
+}
+
+// --------------------------------------------------
+// quote a table or column name
+fn quote_sql_identifier(name: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Mysql => format!("`{}`", name.replace('`', "``")),
+        SqlDialect::Postgres | SqlDialect::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+// --------------------------------------------------
+// quote a string literal. mysql also treats backslashes as escapes
+fn quote_sql_string(text: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Mysql => format!("'{}'", text.replace('\\', "\\\\").replace('\'', "''")),
+        SqlDialect::Postgres | SqlDialect::Sqlite => format!("'{}'", text.replace('\'', "''")),
+    }
+}
+
+// --------------------------------------------------
+// the values of a column as sql literals, with nulls left out. floats keep
+// all of their digits, the floats that are not finite are only kept by
+// postgres, and dates are written as iso strings
+fn get_sql_literals(series: &Series, dialect: SqlDialect) -> Vec<Option<String>> {
+    match series.dtype() {
+        DataType::Boolean => series
+            .bool()
+            .expect("the column is boolean")
+            .into_iter()
+            .map(|value| {
+                value.map(|value| match (dialect, value) {
+                    (SqlDialect::Sqlite, true) => String::from("1"),
+                    (SqlDialect::Sqlite, false) => String::from("0"),
+                    (_, true) => String::from("TRUE"),
+                    (_, false) => String::from("FALSE"),
+                })
+            })
+            .collect(),
+        DataType::Binary => series
+            .binary()
+            .expect("the column is binary")
+            .into_iter()
+            .map(|value| {
+                value.map(|bytes| match dialect {
+                    SqlDialect::Postgres => format!("'\\x{}'", encode_hex(bytes)),
+                    SqlDialect::Sqlite | SqlDialect::Mysql => format!("X'{}'", encode_hex(bytes)),
+                })
+            })
+            .collect(),
+        dtype if dtype.is_float() => format_column(series, None)
+            .into_iter()
+            .map(|value| {
+                value.and_then(|value| match value.parse::<f64>() {
+                    Ok(float) if !float.is_finite() => match dialect {
+                        SqlDialect::Postgres if float.is_nan() => Some(String::from("'NaN'")),
+                        SqlDialect::Postgres if float > 0.0 => Some(String::from("'Infinity'")),
+                        SqlDialect::Postgres => Some(String::from("'-Infinity'")),
+                        SqlDialect::Sqlite | SqlDialect::Mysql => None,
+                    },
+                    _ => Some(value),
+                })
+            })
+            .collect(),
+        dtype if dtype.is_numeric() => format_column(series, None),
+        _ => format_column(series, None)
+            .into_iter()
+            .map(|value| value.map(|value| quote_sql_string(&value, dialect)))
+            .collect(),
+    }
+}
+
+// --------------------------------------------------
+// print a table as insert statements into the named table, each inserting
+// up to the given number of rows
+pub fn print_sql(df: &DataFrame, table_name: &str, dialect: SqlDialect, batch_size: usize) {
+    let df = format_iso_dates(df);
+    let columns: Vec<Vec<Option<String>>> = df
+        .get_columns()
+        .iter()
+        .map(|series| get_sql_literals(series, dialect))
+        .collect();
+
+    // a table qualified by its schema has each part quoted
+    let table_name: Vec<String> = table_name
+        .split('.')
+        .map(|name| quote_sql_identifier(name, dialect))
+        .collect();
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| quote_sql_identifier(name, dialect))
+        .collect();
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES\n",
+        table_name.join("."),
+        names.join(", ")
+    );
+
+    let mut stdout = std::io::stdout().lock();
+    for start in (0..df.height()).step_by(batch_size) {
+        let end = (start + batch_size).min(df.height());
+        let mut sql = insert.clone();
+        for i in start..end {
+            let values: Vec<&str> = columns
+                .iter()
+                .map(|column| column[i].as_deref().unwrap_or("NULL"))
+                .collect();
+            let separator = match i + 1 == end {
+                true => ";",
+                false => ",",
+            };
+            sql.push_str(&format!("  ({}){}\n", values.join(", "), separator));
+        }
+
+        let result = stdout.write_all(sql.as_bytes()).map_err(PolarsError::from);
+        check_printed(result, "SQL");
+    }
+}
+
+// --------------------------------------------------
+// how the values of a column are aligned in the printed tables
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+// --------------------------------------------------
+// the alignment of a column unless --align gives another: numbers to the
+// right, where fixed decimals line up, booleans centered, and others left
+pub fn get_alignment(dtype: &DataType) -> Alignment {
+    match dtype {
+        DataType::Boolean => Alignment::Center,
+        dtype if dtype.is_numeric() => Alignment::Right,
+        _ => Alignment::Left,
+    }
+}
+
+// --------------------------------------------------
+// pad text with spaces to the given display width
+fn pad_text(text: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(text.width());
+    match alignment {
+        Alignment::Left => format!("{}{}", text, " ".repeat(padding)),
+        Alignment::Center => format!(
+            "{}{}{}",
+            " ".repeat(padding / 2),
+            text,
+            " ".repeat(padding - padding / 2)
+        ),
+        Alignment::Right => format!("{}{}", " ".repeat(padding), text),
+    }
+}
+
+// --------------------------------------------------
+// the column names and rows of a table as escaped text, each cell padded to
+// the display width of its column and aligned as given, and the widths of
+// the columns. nulls are shown as the given text and line
+// breaks become spaces. the types of the columns can lead the rows, and a
+// footer row (with its cells between the given strong markup) can end them
+#[allow(clippy::too_many_arguments)]
+fn get_padded_cells(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+    with_dtypes: bool,
+    escape: fn(&str) -> String,
+    strong: &str,
+) -> (Vec<String>, Vec<Vec<String>>, Vec<usize>) {
+    let escape_cell = |text: &str| escape(&text.replace(['\r', '\n'], " "));
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| escape_cell(name))
+        .collect();
+    let mut columns: Vec<Vec<String>> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            format_column(series, float_precision)
+                .iter()
+                .map(|value| escape_cell(value.as_deref().unwrap_or(null_value)))
+                .collect()
+        })
+        .collect();
+    let mut height = df.height();
+    if let Some(footer) = footer {
+        for (column, series) in columns.iter_mut().zip(footer.get_columns()) {
+            let value = format_column(series, float_precision).swap_remove(0);
+            column.push(match escape_cell(value.as_deref().unwrap_or(null_value)) {
+                value if value.is_empty() => value,
+                value => format!("{}{}{}", strong, value, strong),
+            });
+        }
+        height += 1;
+    }
+    let dtypes: Vec<String> = match with_dtypes {
+        true => df.dtypes().iter().map(ToString::to_string).collect(),
+        false => Vec::new(),
+    };
+    let widths: Vec<usize> = names
+        .iter()
+        .zip(&columns)
+        .enumerate()
+        .map(|(i, (name, column))| {
+            column
+                .iter()
+                .chain([name])
+                .chain(dtypes.get(i))
+                .map(|text| text.width())
+                .max()
+                .unwrap_or_default()
+                .max(1)
+        })
+        .collect();
+
+    let names = names
+        .iter()
+        .zip(&widths)
+        .zip(alignments)
+        .map(|((name, width), alignment)| pad_text(name, *width, *alignment))
+        .collect();
+    let dtype_row = match with_dtypes {
+        true => Some(
+            dtypes
+                .iter()
+                .zip(&widths)
+                .zip(alignments)
+                .map(|((dtype, width), alignment)| pad_text(dtype, *width, *alignment))
+                .collect(),
+        ),
+        false => None,
+    };
+    let rows = dtype_row
+        .into_iter()
+        .chain((0..height).map(|i| {
+            columns
+                .iter()
+                .zip(&widths)
+                .zip(alignments)
+                .map(|((column, width), alignment)| pad_text(&column[i], *width, *alignment))
+                .collect()
+        }))
+        .collect();
+
+    (names, rows, widths)
+}
+
+// --------------------------------------------------
+// a row of padded cells between the given vertical bars
+fn format_text_row(cells: &[String], bar: &str) -> String {
+    format!("{} {} {}\n", bar, cells.join(&format!(" {} ", bar)), bar)
+}
+
+// --------------------------------------------------
+// a horizontal rule across columns of the given widths
+fn format_text_rule(widths: &[usize], line: char, edge: char, cross: char) -> String {
+    let lines: Vec<String> = widths
+        .iter()
+        .map(|width| line.to_string().repeat(width + 2))
+        .collect();
+    format!("{}{}{}\n", edge, lines.join(&cross.to_string()), edge)
+}
+
+// --------------------------------------------------
+// escape the vertical bars of a markdown table cell, and its leading and
+// trailing spaces, which would otherwise be trimmed
+fn escape_markdown(text: &str) -> String {
+    let trimmed = text.trim_start_matches(' ');
+    let leading = text.len() - trimmed.len();
+    let trimmed = trimmed.trim_end_matches(' ');
+    let trailing = text.len() - leading - trimmed.len();
+    format!(
+        "{}{}{}",
+        "&nbsp;".repeat(leading),
+        trimmed.replace('|', "\\|"),
+        "&nbsp;".repeat(trailing)
+    )
+}
+
+// --------------------------------------------------
+// print a table as a github-flavored markdown table, padded so that the raw
+// text lines up, with the columns aligned as given. the types of the
+// columns are left out unless asked for, as a first row, and a footer is a
+// last row in bold
+pub fn print_markdown(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+    show_dtypes: bool,
+) {
+    let (names, rows, widths) = get_padded_cells(
+        df,
+        footer,
+        alignments,
+        float_precision,
+        null_value,
+        show_dtypes,
+        escape_markdown,
+        "**",
+    );
+    let rules: Vec<String> = widths
+        .iter()
+        .zip(alignments)
+        .map(|(width, alignment)| match alignment {
+            Alignment::Left => format!(":{}", "-".repeat(width + 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(*width)),
+            Alignment::Right => format!("{}:", "-".repeat(width + 1)),
+        })
+        .collect();
+
+    let mut markdown = format_text_row(&names, "|");
+    markdown.push_str(&format!("|{}|\n", rules.join("|")));
+    for row in &rows {
+        markdown.push_str(&format_text_row(row, "|"));
+    }
+
+    let result = write!(std::io::stdout().lock(), "{}", markdown).map_err(PolarsError::from);
+    check_printed(result, "a markdown table");
+}
+
+// --------------------------------------------------
+// escape the vertical bars of an org table cell
+fn escape_org(text: &str) -> String {
+    text.replace('|', "\\vert{}")
+}
+
+// --------------------------------------------------
+// print a table as an org-mode table, aligned as emacs would align it, with
+// a footer below a rule
+pub fn print_org(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+) {
+    let (names, rows, widths) = get_padded_cells(
+        df,
+        footer,
+        alignments,
+        float_precision,
+        null_value,
+        false,
+        escape_org,
+        "",
+    );
+    let rule = format_text_rule(&widths, '-', '|', '+');
+
+    let mut org = format_text_row(&names, "|");
+    org.push_str(&rule);
+    for (i, row) in rows.iter().enumerate() {
+        if footer.is_some() && i + 1 == rows.len() {
+            org.push_str(&rule);
+        }
+        org.push_str(&format_text_row(row, "|"));
+    }
+
+    let result = write!(std::io::stdout().lock(), "{}", org).map_err(PolarsError::from);
+    check_printed(result, "an org table");
+}
+
+// --------------------------------------------------
+// escape the characters of a restructuredtext cell that start inline markup
+fn escape_rst(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '`' | '|' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// --------------------------------------------------
+// print a table as a restructuredtext grid table, which needs a rule after
+// each row. a footer is a last row in bold
+pub fn print_rst(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+) {
+    let (names, rows, widths) = get_padded_cells(
+        df,
+        footer,
+        alignments,
+        float_precision,
+        null_value,
+        false,
+        escape_rst,
+        "**",
+    );
+    let rule = format_text_rule(&widths, '-', '+', '+');
+
+    let mut rst = rule.clone();
+    rst.push_str(&format_text_row(&names, "|"));
+    rst.push_str(&format_text_rule(&widths, '=', '+', '+'));
+    for row in &rows {
+        rst.push_str(&format_text_row(row, "|"));
+        rst.push_str(&rule);
+    }
+
+    let result = write!(std::io::stdout().lock(), "{}", rst).map_err(PolarsError::from);
+    check_printed(result, "an RST grid table");
+}
+
+// --------------------------------------------------
+// the values of a table as text, by column, with nulls shown as the given
+// text and line breaks as spaces
+fn get_text_columns(
+    df: &DataFrame,
+    float_precision: Option<usize>,
+    null_value: &str,
+) -> Vec<Vec<String>> {
+    df.get_columns()
+        .iter()
+        .map(|series| {
+            format_column(series, float_precision)
+                .iter()
+                .map(|value| {
+                    value
+                        .as_deref()
+                        .unwrap_or(null_value)
+                        .replace(['\r', '\n'], " ")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// escape an asciidoc table cell, writing the characters of cell separators,
+// markup, macros, attributes, and escapes as character references so that
+// the cell is shown as it is. line breaks and control characters become
+// spaces
+fn escape_asciidoc(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '|' | '\\' | '{' | '}' | '[' | ']' | '&' | '<' | '#' | '*' | '_' | '`' | '+' | '^'
+            | '~' => escaped.push_str(&format!("&#{};", c as u32)),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// --------------------------------------------------
+// print a table as an asciidoc table with a header row, and the columns
+// aligned as given. a footer is the footer row of the table
+pub fn print_asciidoc(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+) {
+    let cols: Vec<&str> = alignments
+        .iter()
+        .map(|alignment| match alignment {
+            Alignment::Left => "<1",
+            Alignment::Center => "^1",
+            Alignment::Right => ">1",
+        })
+        .collect();
+    let options = match footer {
+        Some(_) => "header,footer",
+        None => "header",
+    };
+    let format_row = |cells: Vec<String>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .map(|cell| format!("|{}", escape_asciidoc(cell)))
+            .collect();
+        cells.join(" ") + "\n"
+    };
+
+    let columns = get_text_columns(df, float_precision, null_value);
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let mut asciidoc = format!(
+        "[cols=\"{}\",options=\"{}\"]\n|===\n",
+        cols.join(","),
+        options
+    );
+    asciidoc.push_str(&format_row(names));
+    asciidoc.push('\n');
+    for i in 0..df.height() {
+        asciidoc.push_str(&format_row(
+            columns.iter().map(|column| column[i].clone()).collect(),
+        ));
+    }
+    if let Some(footer) = footer {
+        let columns = get_text_columns(footer, float_precision, null_value);
+        asciidoc.push_str(&format_row(
+            columns
+                .into_iter()
+                .map(|mut column| column.swap_remove(0))
+                .collect(),
+        ));
+    }
+    asciidoc.push_str("|===\n");
+
+    let result = write!(std::io::stdout().lock(), "{}", asciidoc).map_err(PolarsError::from);
+    check_printed(result, "an AsciiDoc table");
+}
+
+// --------------------------------------------------
+// quote text as a typst string, escaping its backslashes, quotes, and
+// control characters
+fn quote_typst(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// --------------------------------------------------
+// print a table as a typst #table call, with the column names in its
+// header, a string for each cell, and the columns aligned as given. a
+// footer is the footer of the table
+pub fn print_typst(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    alignments: &[Alignment],
+    float_precision: Option<usize>,
+    null_value: &str,
+) {
+    let align: Vec<&str> = alignments
+        .iter()
+        .map(|alignment| match alignment {
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+        })
+        .collect();
+    let format_cells = |cells: Vec<String>| {
+        let cells: Vec<String> = cells.iter().map(|cell| quote_typst(cell)).collect();
+        cells.join(", ")
+    };
+
+    let columns = get_text_columns(df, float_precision, null_value);
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let mut typst = format!(
+        "#table(\n  columns: {},\n  align: ({},),\n  table.header({}),\n",
+        df.width(),
+        align.join(", "),
+        format_cells(names)
+    );
+    for i in 0..df.height() {
+        let cells = format_cells(columns.iter().map(|column| column[i].clone()).collect());
+        typst.push_str(&format!("  {},\n", cells));
+    }
+    if let Some(footer) = footer {
+        let columns = get_text_columns(footer, float_precision, null_value);
+        let cells = format_cells(
+            columns
+                .into_iter()
+                .map(|mut column| column.swap_remove(0))
+                .collect(),
+        );
+        typst.push_str(&format!("  table.footer({}),\n", cells));
+    }
+    typst.push_str(")\n");
+
+    let result = write!(std::io::stdout().lock(), "{}", typst).map_err(PolarsError::from);
+    check_printed(result, "a Typst table");
+}
+
+// --------------------------------------------------
+// print each row of a table as a block of `name: value` lines, with the
+// colons aligned and the field names in bold (nulls dimmed, and highlighted
+// values in inverse red) when colored. values are not truncated, and their
+// line breaks are indented to stay clear of the names. a footer is a last
+// record of the values that it has
+pub fn print_vertical(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    float_precision: Option<usize>,
+    null_value: &str,
+    color: bool,
+) {
+    let names = df.get_column_names();
+    let name_width = names
+        .iter()
+        .map(|name| name.width())
+        .max()
+        .unwrap_or_default();
+    let columns: Vec<Vec<Option<String>>> = df
+        .get_columns()
+        .iter()
+        .map(|series| format_column(series, float_precision))
+        .collect();
+    let (bold, dim, reset) = match color {
+        true => ("\x1b[1m", "\x1b[2m", "\x1b[0m"),
+        false => ("", "", ""),
+    };
+    let highlight = match color {
+        true => "\x1b[7;31m",
+        false => "",
+    };
+    let indent = format!("\n{}", " ".repeat(name_width + 2));
+
+    let mut text = String::new();
+    for i in 0..df.height() {
+        text.push_str(&format!("{}--- row {} ---{}\n", dim, i + 1, reset));
+        for (name, column) in names.iter().zip(&columns) {
+            let value = match column[i].as_deref() {
+                Some(value) if color && is_highlighted(value) => format!(
+                    "{}{}{}",
+                    highlight,
+                    value.replace("\r\n", "\n").replace('\n', &indent),
+                    reset
+                ),
+                Some(value) => value.replace("\r\n", "\n").replace('\n', &indent),
+                None => format!("{}{}{}", dim, null_value, reset),
+            };
+            text.push_str(&format!(
+                "{}{}{}{}: {}\n",
+                bold,
+                name,
+                reset,
+                " ".repeat(name_width - name.width()),
+                value
+            ));
+        }
+    }
+
+    if let Some(footer) = footer {
+        text.push_str(&format!("{}--- totals ---{}\n", dim, reset));
+        for (name, series) in names.iter().zip(footer.get_columns()) {
+            let value = match format_column(series, float_precision).swap_remove(0) {
+                Some(value) if value.is_empty() => continue,
+                Some(value) => value,
+                None => format!("{}{}{}", dim, null_value, reset),
+            };
+            text.push_str(&format!(
+                "{}{}{}{}: {}\n",
+                bold,
+                name,
+                reset,
+                " ".repeat(name_width - name.width()),
+                value
+            ));
+        }
+    }
+
+    let result = write!(std::io::stdout().lock(), "{}", text).map_err(PolarsError::from);
+    check_printed(result, "records");
+}
+
+// --------------------------------------------------
+// where the ellipsis of a truncated value goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Truncation {
+    Start,
+    Middle,
+    End,
+}
+
+// --------------------------------------------------
+// the longest run of characters that fits in the given display width
+fn take_width(chars: impl Iterator<Item = char>, width: usize) -> Vec<char> {
+    let mut taken_width = 0;
+    chars
+        .take_while(|c| {
+            taken_width += c.width().unwrap_or_default();
+            taken_width <= width
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// the start and the end of a text that fit in the given display width
+fn get_head(text: &str, width: usize) -> String {
+    String::from_iter(take_width(text.chars(), width))
+}
+
+fn get_tail(text: &str, width: usize) -> String {
+    String::from_iter(take_width(text.chars().rev(), width).iter().rev())
+}
+
+// --------------------------------------------------
+// shorten a value to the given display width, ellipsis included. a value
+// with a highlighted match keeps the match, and as much of its context as
+// fits
+fn truncate_text(text: &str, max_width: usize, truncation: Truncation) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+
+    let width = max_width.saturating_sub(1);
+    let truncated = match truncation {
+        Truncation::Start => format!("…{}", get_tail(text, width)),
+        Truncation::Middle => format!(
+            "{}…{}",
+            get_head(text, width - width / 2),
+            get_tail(text, width / 2)
+        ),
+        Truncation::End => format!("{}…", get_head(text, width)),
+    };
+
+    match get_highlight().and_then(|regex| regex.find(text).map(|found| (regex, found))) {
+        Some((regex, found)) if !regex.is_match(&truncated) => {
+            truncate_around(text, found.start(), found.end(), max_width)
+        }
+        _ => truncated,
+    }
+}
+
+// --------------------------------------------------
+// shorten a value to the given display width around the bytes start..end,
+// splitting the room left between the text before and after them
+fn truncate_around(text: &str, start: usize, end: usize, max_width: usize) -> String {
+    let (before, found, after) = (&text[..start], &text[start..end], &text[end..]);
+    let room = max_width.saturating_sub(found.width());
+    if room < 2 {
+        return format!("{}…", get_head(found, max_width.saturating_sub(1)));
+    }
+
+    let (mut before_room, mut after_room) = (room - room / 2, room / 2);
+    if before.width() < before_room {
+        after_room += before_room - before.width();
+        before_room = before.width();
+    } else if after.width() < after_room {
+        before_room += after_room - after.width();
+        after_room = after.width();
+    }
+
+    let before = match before.width() <= before_room {
+        true => before.to_string(),
+        false => format!("…{}", get_tail(before, before_room - 1)),
+    };
+    let after = match after.width() <= after_room {
+        true => after.to_string(),
+        false => format!("{}…", get_head(after, after_room - 1)),
+    };
+    format!("{}{}{}", before, found, after)
+}
+
+// --------------------------------------------------
+// highlight the cells matching a pattern in the colored tables
+pub fn set_highlight(regex: Regex) {
+    let _ = HIGHLIGHT.set(regex);
+}
+
+// --------------------------------------------------
+fn get_highlight() -> Option<&'static Regex> {
+    HIGHLIGHT.get()
+}
+
+// --------------------------------------------------
+fn is_highlighted(text: &str) -> bool {
+    get_highlight().is_some_and(|regex| regex.is_match(text))
+}
+
+// --------------------------------------------------
+// shorten the text values and the column names of a table to the given
+// display width
+pub fn truncate_table(df: DataFrame, max_width: usize, truncation: Truncation) -> DataFrame {
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| truncate_series(series, max_width, truncation))
+        .collect();
+
+    get_truncated_table(columns)
+}
+
+// --------------------------------------------------
+// shorten the text values and the name of a column to the given display
+// width
+fn truncate_series(series: &Series, max_width: usize, truncation: Truncation) -> Series {
+    let mut truncated = match series.utf8() {
+        Ok(values) => values
+            .into_iter()
+            .map(|value| value.map(|value| truncate_text(value, max_width, truncation)))
+            .collect::<Utf8Chunked>()
+            .into_series(),
+        Err(_) => series.clone(),
+    };
+    truncated.rename(&truncate_text(series.name(), max_width, truncation));
+    truncated
+}
+
+// --------------------------------------------------
+fn get_truncated_table(columns: Vec<Series>) -> DataFrame {
+    DataFrame::new(columns).unwrap_or_else(|_| {
+        exit_with_error("Unable to truncate the table: the truncated column names are not unique")
+    })
+}
+
+// --------------------------------------------------
+// the display width of a column as printed in the pretty table, under its
+// name and type, or in the plain table, whose values are kept on one line
+fn get_printed_width(series: &Series, null_value: &str, bordered: bool) -> usize {
+    let format_value = |text: &str| match bordered {
+        true => text
+            .lines()
+            .map(|line| line.width())
+            .max()
+            .unwrap_or_default(),
+        false => text.replace(['\r', '\n'], " ").width(),
+    };
+    let header_width =
+        match bordered && std::env::var_os("POLARS_FMT_TABLE_HIDE_COLUMN_DATA_TYPES").is_none() {
+            true => format_value(series.name()).max(series.dtype().to_string().width()),
+            false => format_value(series.name()),
+        };
+
+    let nulls = series.is_null();
+    (0..series.len())
+        .map(|i| match nulls.get(i) {
+            Some(true) => null_value.width(),
+            _ => format_value(&series.str_value(i).unwrap_or_default()),
+        })
+        .fold(header_width, usize::max)
+}
+
+// --------------------------------------------------
+// the display width of a pretty table (with borders and padding) or of a
+// plain one (with two spaces between its columns)
+fn get_table_width(widths: &[usize], bordered: bool) -> usize {
+    let width: usize = widths.iter().sum();
+    match bordered {
+        true => width + 3 * widths.len() + 1,
+        false => width + 2 * widths.len().saturating_sub(1),
+    }
+}
+
+// --------------------------------------------------
+// a column of a table being fitted to a display width, with its printed
+// width and whether it is text that may be shrunk
+#[derive(Clone, Copy)]
+struct FitColumn<'a> {
+    series: &'a Series,
+    width: usize,
+    shrinkable: bool,
+}
+
+// --------------------------------------------------
+fn get_fit_columns<'a>(
+    df: &'a DataFrame,
+    bordered: bool,
+    text_columns: &[String],
+    null_value: &str,
+) -> Vec<FitColumn<'a>> {
+    df.get_columns()
+        .iter()
+        .map(|series| FitColumn {
+            series,
+            width: get_printed_width(series, null_value, bordered),
+            shrinkable: text_columns.iter().any(|name| name == series.name()),
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// take as many of the first columns as fit the given display width once
+// their text is shrunk, and at least min_shown of them. the text columns are
+// then shrunk, the widest first and none below MIN_FIT_WIDTH, until they fit
+fn fit_columns(
+    columns: &[FitColumn],
+    min_shown: usize,
+    width: usize,
+    bordered: bool,
+    truncation: Truncation,
+) -> Vec<Series> {
+    let min_widths: Vec<usize> = columns
+        .iter()
+        .map(|column| match column.shrinkable {
+            true => column.width.min(MIN_FIT_WIDTH),
+            false => column.width,
+        })
+        .collect();
+    let mut n_shown = columns.len();
+    while n_shown > min_shown && get_table_width(&min_widths[..n_shown], bordered) > width {
+        n_shown -= 1;
+    }
+
+    let columns = &columns[..n_shown];
+    let mut widths: Vec<usize> = columns.iter().map(|column| column.width).collect();
+    while get_table_width(&widths, bordered) > width {
+        let widest = (0..n_shown)
+            .filter(|&i| columns[i].shrinkable && widths[i] > MIN_FIT_WIDTH)
+            .max_by_key(|&i| widths[i]);
+        match widest {
+            Some(i) => widths[i] -= 1,
+            None => break,
+        }
+    }
+
+    columns
+        .iter()
+        .zip(widths)
+        .map(|(column, width)| match width < column.width {
+            true => truncate_series(column.series, width, truncation),
+            false => column.series.clone(),
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// fit a table to the given display width, shrinking its text columns before
+// leaving out the columns on the right that still do not fit (as well as
+// those past max_cols). returns the fitted table and the names of the
+// columns left out
+pub fn fit_table(
+    df: DataFrame,
+    width: usize,
+    max_cols: Option<usize>,
+    bordered: bool,
+    text_columns: &[String],
+    null_value: &str,
+    truncation: Truncation,
+) -> (DataFrame, Vec<String>) {
+    let mut columns = get_fit_columns(&df, bordered, text_columns, null_value);
+    columns.truncate(max_cols.unwrap_or(columns.len()));
+
+    let shown = fit_columns(&columns, 1, width, bordered, truncation);
+    let omitted: Vec<String> = df.get_columns()[shown.len()..]
+        .iter()
+        .map(|series| series.name().to_string())
+        .collect();
+
+    (get_truncated_table(shown), omitted)
+}
+
+// --------------------------------------------------
+// split a table into tables of the consecutive columns that fit the given
+// display width, each led by the id column if one is given. their text
+// columns are shrunk as when fitting a single table
+pub fn chunk_table(
+    df: &DataFrame,
+    width: usize,
+    id_column: Option<usize>,
+    bordered: bool,
+    text_columns: &[String],
+    null_value: &str,
+    truncation: Truncation,
+) -> Vec<DataFrame> {
+    let mut columns = get_fit_columns(df, bordered, text_columns, null_value);
+    let id_column = id_column.map(|i| columns.remove(i));
+
+    let mut chunks = Vec::<DataFrame>::new();
+    let mut start = 0;
+    while start < columns.len() {
+        let chunk: Vec<FitColumn> = id_column.iter().chain(&columns[start..]).copied().collect();
+        let n_ids = id_column.iter().len();
+        let shown = fit_columns(&chunk, n_ids + 1, width, bordered, truncation);
+        start += shown.len() - n_ids;
+        chunks.push(get_truncated_table(shown));
+    }
+    chunks
+}
+
+// --------------------------------------------------
+// a note of the columns left out of a table, e.g. `… 2 more columns: a, b`,
+// whose list of names is cut short to fit the given display width
+pub fn format_omitted_columns(names: &[String], width: usize) -> String {
+    let mut note = match names.len() {
+        1 => String::from("… 1 more column:"),
+        n => format!("… {} more columns:", n),
+    };
+    for (i, name) in names.iter().enumerate() {
+        let separator = match i {
+            0 => " ",
+            _ => ", ",
+        };
+        // leave room for the ", …" that ends a list cut short
+        let room = match i + 1 == names.len() {
+            true => 0,
+            false => 3,
+        };
+        if note.width() + separator.len() + name.width() + room > width {
+            note.push_str(separator);
+            note.push('…');
+            break;
+        }
+        note.push_str(separator);
+        note.push_str(name);
+    }
+    note
+}
+
+// --------------------------------------------------
+// the width of the terminal that stdout prints to, if it does
+pub fn get_terminal_width() -> Option<usize> {
+    Table::new().width().map(usize::from)
+}
+
+// --------------------------------------------------
+// group the digits of a count with underscores, as in the shape of polars
+fn group_digits(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('_');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// --------------------------------------------------
+// the shape of a table as polars prints it under a table, e.g. (1_000, 4)
+pub fn format_shape((height, width): (usize, usize)) -> String {
+    format!("({}, {})", group_digits(height), group_digits(width))
+}
+
+// --------------------------------------------------
+// the note under the counts of --value-counts on the values left out
+pub fn format_omitted_values(n_values: usize) -> String {
+    match n_values {
+        1 => String::from("… 1 more distinct value"),
+        n => format!("… {} more distinct values", group_digits(n)),
+    }
+}
+
+// --------------------------------------------------
+// the number of rows that --grep matched, for the shape footer
+pub fn format_matches(n_matches: usize) -> String {
+    match n_matches {
+        1 => String::from("1 row matches"),
+        n => format!("{} rows match", group_digits(n)),
+    }
+}
+
+// --------------------------------------------------
+// the box-drawing characters of the pretty table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableStyle {
+    Rounded,
+    Sharp,
+    Double,
+    Heavy,
+    Ascii,
+    Borderless,
+}
+
+// the presets of the table styles that comfy-table has no preset for (its
+// ascii preset leaves out the crossings of the header rule), in its order of
+// the borders, the header rule, the column separators, the intersections,
+// and the corners
+const DOUBLE_CONDENSED: &str = "║║══╠═╬╣║    ╦╩╔╗╚╝";
+const HEAVY_CONDENSED: &str = "┃┃━━┣━╋┫┃    ┳┻┏┓┗┛";
+const BORDERLESS_CONDENSED: &str = "     ═╪ ┆          ";
+const ASCII_CONDENSED: &str = "||--+=++|    ++++++";
+
+// --------------------------------------------------
+// the rule above the footer of a pretty table, and the line it goes before:
+// the bottom border with its corners turned into intersections, or a light
+// copy of the header rule when there are no borders
+fn get_footer_rule(lines: &[String], style: TableStyle) -> Option<(String, usize)> {
+    let replace = |line: &str, pairs: &[(char, char)]| {
+        line.chars()
+            .map(|c| match pairs.iter().find(|(from, _)| *from == c) {
+                Some((_, to)) => *to,
+                None => c,
+            })
+            .collect::<String>()
+    };
+    let bottom = lines.last()?;
+    let rule = match style {
+        TableStyle::Rounded => replace(bottom, &[('╰', '├'), ('┴', '┼'), ('╯', '┤')]),
+        TableStyle::Sharp => replace(bottom, &[('└', '├'), ('┴', '┼'), ('┘', '┤')]),
+        TableStyle::Double => replace(bottom, &[('╚', '╠'), ('╩', '╬'), ('╝', '╣')]),
+        TableStyle::Heavy => replace(bottom, &[('┗', '┣'), ('┻', '╋'), ('┛', '┫')]),
+        TableStyle::Ascii => bottom.clone(),
+        TableStyle::Borderless => {
+            let header_rule = lines.iter().find(|line| line.contains('═'))?;
+            let rule = replace(header_rule, &[('═', '─'), ('╪', '┼')]);
+            return Some((rule, lines.len() - 1));
+        }
+    };
+    Some((rule, lines.len().checked_sub(2)?))
+}
+
+// --------------------------------------------------
+// print a table with borders as polars displays it, but cell by cell, with
+// the header in bold, nulls dimmed, negative numbers in red, booleans in
+// green or red, and the highlighted cells in inverse red. the widths are
+// measured on the text before it is styled, so the colors leave the columns
+// aligned. the columns are aligned as given, a footer is a last row in bold,
+// below a rule, the repeated values of a column are dimmed, or left out
+// without colors, and a gap is a row of ⋯ before the given row
+#[allow(clippy::too_many_arguments)]
+pub fn print_pretty(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    gap: Option<usize>,
+    repeats: &[Vec<bool>],
+    alignments: &[Alignment],
+    null_value: &str,
+    color: bool,
+    style: TableStyle,
+) {
+    let hide_dtypes = std::env::var_os("POLARS_FMT_TABLE_HIDE_COLUMN_DATA_TYPES").is_some();
+
+    let mut table = Table::new();
+    match style {
+        TableStyle::Rounded => table
+            .load_preset(UTF8_FULL_CONDENSED)
+            .apply_modifier(UTF8_ROUND_CORNERS),
+        TableStyle::Sharp => table.load_preset(UTF8_FULL_CONDENSED),
+        TableStyle::Double => table.load_preset(DOUBLE_CONDENSED),
+        TableStyle::Heavy => table.load_preset(HEAVY_CONDENSED),
+        TableStyle::Ascii => table.load_preset(ASCII_CONDENSED),
+        TableStyle::Borderless => table.load_preset(BORDERLESS_CONDENSED),
+    };
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    match color {
+        true => table.enforce_styling(),
+        false => table.force_no_tty(),
+    };
+
+    let mut widths: Vec<usize> = Vec::with_capacity(df.width());
+    let mut header: Vec<Cell> = Vec::with_capacity(df.width());
+    for series in df.get_columns() {
+        let (text, width) = match hide_dtypes {
+            true => (series.name().to_string(), series.name().width()),
+            false => {
+                let dtype = series.dtype().to_string();
+                let width = series.name().width().max(dtype.width());
+                (format!("{}\n---\n{}", series.name(), dtype), width)
+            }
+        };
+        header.push(Cell::new(text).add_attribute(Attribute::Bold));
+        widths.push(width + 2);
+    }
+
+    for i in 0..df.height() {
+        if gap == Some(i) {
+            let row = (0..df.width()).map(|_| Cell::new(GAP_MARKER).add_attribute(Attribute::Dim));
+            table.add_row(row.collect::<Vec<Cell>>());
+        }
+        let row: Vec<Cell> = df
+            .get_columns()
+            .iter()
+            .zip(widths.iter_mut())
+            .enumerate()
+            .map(|(j, (series, width))| {
+                // the first row after the gap repeats no value
+                let is_repeat = is_repeat(repeats, j, i) && gap != Some(i);
+                if is_repeat && !color {
+                    return Cell::new("");
+                }
+                if matches!(series.get(i), Ok(AnyValue::Null)) {
+                    *width = (*width).max(null_value.width() + 2);
+                    return Cell::new(null_value).add_attribute(Attribute::Dim);
+                }
+                let text = series.str_value(i).unwrap_or_default().to_string();
+                *width = (*width).max(
+                    text.lines()
+                        .map(|line| line.width())
+                        .max()
+                        .unwrap_or_default()
+                        + 2,
+                );
+                match series.dtype() {
+                    _ if is_repeat => Cell::new(&text).add_attribute(Attribute::Dim),
+                    DataType::Utf8 if text == NULL_MARKER => Cell::new(&text).fg(Color::Red),
+                    DataType::Utf8 if text == PRESENT_MARKER => {
+                        Cell::new(&text).add_attribute(Attribute::Dim)
+                    }
+                    _ if is_highlighted(&text) => Cell::new(&text)
+                        .fg(Color::Red)
+                        .add_attribute(Attribute::Reverse),
+                    DataType::Boolean if text == "true" => Cell::new(&text).fg(Color::Green),
+                    DataType::Boolean => Cell::new(&text).fg(Color::Red),
+                    dtype if dtype.is_numeric() && text.starts_with('-') => {
+                        Cell::new(&text).fg(Color::Red)
+                    }
+                    _ => Cell::new(&text),
+                }
+            })
+            .collect();
+        table.add_row(row);
+    }
+
+    // the footer is kept to a single line, so that the rule goes above it
+    if let Some(footer) = footer {
+        let cells: Vec<Cell> = footer
+            .get_columns()
+            .iter()
+            .zip(widths.iter_mut())
+            .map(|(series, width)| {
+                let text = match series.get(0) {
+                    Ok(AnyValue::Null) => null_value.to_string(),
+                    _ => series.str_value(0).unwrap_or_default().to_string(),
+                };
+                *width = (*width).max(text.width() + 2);
+                Cell::new(text).add_attribute(Attribute::Bold)
+            })
+            .collect();
+        let mut row = Row::from(cells);
+        row.max_height(1);
+        table.add_row(row);
+    }
+
+    let constraints = widths.iter().map(|&width| match width <= 5 {
+        true => ColumnConstraint::Absolute(Width::Fixed(width as u16)),
+        false => ColumnConstraint::Boundaries {
+            lower: Width::Fixed(5),
+            upper: Width::Fixed(width.min(u16::MAX as usize) as u16),
+        },
+    });
+    table.set_header(header).set_constraints(constraints);
+    for (column, alignment) in table.column_iter_mut().zip(alignments) {
+        column.set_cell_alignment(match alignment {
+            Alignment::Left => CellAlignment::Left,
+            Alignment::Center => CellAlignment::Center,
+            Alignment::Right => CellAlignment::Right,
+        });
+    }
+
+    match std::env::var("POLARS_TABLE_WIDTH")
+        .ok()
+        .and_then(|width| width.parse::<u16>().ok())
+    {
+        Some(width) => {
+            table.set_width(width);
+        }
+        None if table.width().is_none() && !table.is_tty() => {
+            table.set_width(100);
+        }
+        None => {}
+    }
+
+    let mut lines: Vec<String> = table.lines().collect();
+    if let (Some(_), Some((rule, i))) = (footer, get_footer_rule(&lines, style)) {
+        lines.insert(i, rule);
+    }
+
+    let result =
+        writeln!(std::io::stdout().lock(), "{}", lines.join("\n")).map_err(PolarsError::from);
+    check_printed(result, "a table");
+}
+
+// --------------------------------------------------
+// print a table as columns aligned with spaces under a single header
+// underline, without borders or a shape footer. the columns are aligned as
+// given, a footer is a last row below another underline, the repeated
+// values of a column are left out, and a gap is a row of ⋯ before the given
+// row
+pub fn print_plain(
+    df: &DataFrame,
+    footer: Option<&DataFrame>,
+    gap: Option<usize>,
+    repeats: &[Vec<bool>],
+    alignments: &[Alignment],
+    null_value: &str,
+) {
+    let format_value = |text: &str| text.replace(['\r', '\n'], " ");
+    let names: Vec<String> = df
+        .get_columns()
+        .iter()
+        .map(|series| format_value(series.name()))
+        .collect();
+    let format_values = |series: &Series| -> Vec<String> {
+        let nulls = series.is_null();
+        (0..series.len())
+            .map(|i| match nulls.get(i) {
+                Some(true) => null_value.to_string(),
+                _ => format_value(&series.str_value(i).unwrap_or_default()),
+            })
+            .collect()
+    };
+    let mut columns: Vec<Vec<String>> = df.get_columns().iter().map(format_values).collect();
+    for (j, column) in columns.iter_mut().enumerate() {
+        for (i, value) in column.iter_mut().enumerate() {
+            if is_repeat(repeats, j, i) && gap != Some(i) {
+                value.clear();
+            }
+        }
+    }
+    let footer: Option<Vec<String>> = footer.map(|footer| {
+        footer
+            .get_columns()
+            .iter()
+            .map(|series| format_values(series).swap_remove(0))
+            .collect()
+    });
+    let widths: Vec<usize> = names
+        .iter()
+        .zip(&columns)
+        .enumerate()
+        .map(|(i, (name, column))| {
+            column
+                .iter()
+                .chain([name])
+                .chain(footer.as_ref().map(|footer| &footer[i]))
+                .map(|text| text.width())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let format_line = |cells: Vec<String>| cells.join("  ").trim_end().to_string() + "\n";
+
+    let mut text = format_line(
+        names
+            .iter()
+            .zip(&widths)
+            .zip(alignments)
+            .map(|((name, width), alignment)| pad_text(name, *width, *alignment))
+            .collect(),
+    );
+    text.push_str(&format_line(
+        widths.iter().map(|width| "-".repeat(*width)).collect(),
+    ));
+    for i in 0..df.height() {
+        if gap == Some(i) {
+            text.push_str(&format_line(
+                widths
+                    .iter()
+                    .zip(alignments)
+                    .map(|(width, alignment)| pad_text(GAP_MARKER, *width, *alignment))
+                    .collect(),
+            ));
+        }
+        text.push_str(&format_line(
+            columns
+                .iter()
+                .zip(&widths)
+                .zip(alignments)
+                .map(|((column, width), alignment)| pad_text(&column[i], *width, *alignment))
+                .collect(),
+        ));
+    }
+    if let Some(footer) = footer {
+        text.push_str(&format_line(
+            widths.iter().map(|width| "-".repeat(*width)).collect(),
+        ));
+        text.push_str(&format_line(
+            footer
+                .iter()
+                .zip(&widths)
+                .zip(alignments)
+                .map(|((value, width), alignment)| pad_text(value, *width, *alignment))
+                .collect(),
+        ));
+    }
+
+    let result = write!(std::io::stdout().lock(), "{}", text).map_err(PolarsError::from);
+    check_printed(result, "plain text");
+}
+
+// --------------------------------------------------
+// how the floats of the pretty and plain tables are written: in scientific
+// notation when very large or small as polars does, never, always, or from
+// the given magnitude on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Notation {
+    Auto,
+    Fixed,
+    Scientific,
+    ScientificAbove(f64),
+}
+
+// --------------------------------------------------
+// write the floats of a table in scientific notation, always or from the
+// given magnitude (or below its reciprocal) on. the others are written as
+// polars would print them
+pub fn format_scientific(
+    df: DataFrame,
+    magnitude: Option<f64>,
+    float_precision: Option<usize>,
+) -> DataFrame {
+    let is_scientific = |value: f64| match magnitude {
+        Some(magnitude) => {
+            value.abs() >= magnitude || (value != 0.0 && value.abs() < 1.0 / magnitude)
+        }
+        None => true,
+    };
+
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            if !series.dtype().is_float() {
+                return series.clone();
+            }
+            let values = series
+                .cast(&DataType::Float64)
+                .expect("floats can be cast to f64");
+            let formatted: Utf8Chunked = values
+                .f64()
+                .expect("the column was cast to f64")
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    value.map(|value| match is_scientific(value) {
+                        true => format!("{:.*e}", float_precision.unwrap_or(4), value),
+                        false => series.str_value(i).unwrap_or_default().into_owned(),
+                    })
+                })
+                .collect();
+            formatted.with_name(series.name()).into_series()
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to format the floats of the table")
+}
+
+// --------------------------------------------------
+// write a value, or nothing when its format asks for fields that it lacks
+// (e.g. the hour of a date)
+fn try_format(value: impl std::fmt::Display) -> Option<String> {
+    let mut text = String::new();
+    std::fmt::write(&mut text, format_args!("{}", value))
+        .ok()
+        .map(|_| text)
+}
+
+// --------------------------------------------------
+// write the dates, datetimes, and times of a table with a strftime format,
+// keeping the usual text of the values that it does not suit. datetimes with
+// a time zone are stored as utc, and are shown in the local time zone if
+// asked to
+pub fn format_datetimes(df: DataFrame, format: Option<&str>, local_time: bool) -> DataFrame {
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            let physical = series.to_physical_repr();
+            let formatted: Utf8Chunked = match series.dtype() {
+                DataType::Datetime(time_unit, time_zone) => {
+                    let to_datetime = match time_unit {
+                        TimeUnit::Nanoseconds => timestamp_ns_to_datetime,
+                        TimeUnit::Microseconds => timestamp_us_to_datetime,
+                        TimeUnit::Milliseconds => timestamp_ms_to_datetime,
+                    };
+                    let format_datetime = |value: i64| {
+                        let datetime = to_datetime(value);
+                        let text = match (time_zone, local_time, format) {
+                            (Some(_), true, Some(format)) => {
+                                try_format(Local.from_utc_datetime(&datetime).format(format))
+                            }
+                            (Some(_), true, None) => try_format(Local.from_utc_datetime(&datetime)),
+                            (Some(_), false, Some(format)) => {
+                                try_format(Utc.from_utc_datetime(&datetime).format(format))
+                            }
+                            (None, _, Some(format)) => try_format(datetime.format(format)),
+                            (_, _, None) => None,
+                        };
+                        text.unwrap_or_else(|| datetime.to_string())
+                    };
+                    physical
+                        .i64()
+                        .expect("datetimes are stored as i64")
+                        .into_iter()
+                        .map(|value| value.map(format_datetime))
+                        .collect()
+                }
+                DataType::Date => match format {
+                    Some(format) => physical
+                        .i32()
+                        .expect("dates are stored as i32")
+                        .into_iter()
+                        .map(|value| {
+                            value.map(|value| {
+                                let date = date32_to_date(value);
+                                try_format(date.format(format)).unwrap_or_else(|| date.to_string())
+                            })
+                        })
+                        .collect(),
+                    None => return series.clone(),
+                },
+                DataType::Time => match format {
+                    Some(format) => physical
+                        .i64()
+                        .expect("times are stored as i64")
+                        .into_iter()
+                        .map(|value| {
+                            value.map(|value| {
+                                let time = time64ns_to_time(value);
+                                try_format(time.format(format)).unwrap_or_else(|| time.to_string())
+                            })
+                        })
+                        .collect(),
+                    None => return series.clone(),
+                },
+                _ => return series.clone(),
+            };
+            formatted.with_name(series.name()).into_series()
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to format the dates of the table")
+}
+
+// --------------------------------------------------
+// how the values of a column are made readable: integers (or durations) as
+// a number of days, hours, minutes, and seconds, or integers as a number of
+// bytes in binary units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Humanize {
+    Duration,
+    Bytes,
+}
+
+// --------------------------------------------------
+// write a duration of some nanoseconds as e.g. 2h 13m 5s, leaving out the
+// fractions of a second unless it is shorter than one
+fn format_duration(nanoseconds: i64) -> String {
+    let sign = match nanoseconds < 0 {
+        true => "-",
+        false => "",
+    };
+    let nanoseconds = nanoseconds.unsigned_abs();
+    let seconds = nanoseconds / 1_000_000_000;
+    if seconds == 0 {
+        return match nanoseconds {
+            0 => String::from("0s"),
+            1_000_000.. => format!("{}{}ms", sign, nanoseconds / 1_000_000),
+            1_000.. => format!("{}{}µs", sign, nanoseconds / 1_000),
+            _ => format!("{}{}ns", sign, nanoseconds),
+        };
+    }
+
+    let parts: Vec<String> = [
+        (seconds / 86_400, "d"),
+        (seconds / 3_600 % 24, "h"),
+        (seconds / 60 % 60, "m"),
+        (seconds % 60, "s"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, unit)| format!("{}{}", count, unit))
+    .collect();
+    format!("{}{}", sign, parts.join(" "))
+}
+
+// --------------------------------------------------
+// write a number of bytes as e.g. 1.4 GiB
+pub fn format_bytes(bytes: i64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    let mut size = bytes.unsigned_abs() as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    let sign = match bytes < 0 {
+        true => "-",
+        false => "",
+    };
+    match unit {
+        0 => format!("{}{} B", sign, bytes.unsigned_abs()),
+        _ => format!("{}{:.1} {}", sign, size, units[unit]),
+    }
+}
+
+// --------------------------------------------------
+// humanize the given columns of a table. the integers of a duration column
+// count units of the given number of nanoseconds
+pub fn humanize_columns(
+    df: DataFrame,
+    columns: &[(Humanize, usize)],
+    duration_unit: i64,
+) -> DataFrame {
+    let mut series = df.get_columns().to_vec();
+    for &(humanize, index) in columns {
+        let column = &series[index];
+        let (values, scale) = match (humanize, column.dtype()) {
+            (Humanize::Duration, DataType::Duration(time_unit)) => {
+                let scale = match time_unit {
+                    TimeUnit::Nanoseconds => 1,
+                    TimeUnit::Microseconds => 1_000,
+                    TimeUnit::Milliseconds => 1_000_000,
+                };
+                (column.to_physical_repr().into_owned(), scale)
+            }
+            (Humanize::Duration, dtype) if dtype.is_numeric() => (column.clone(), duration_unit),
+            (Humanize::Bytes, dtype) if dtype.is_numeric() => (column.clone(), 1),
+            (_, dtype) => exit_with_error(&format!(
+                "--humanize: the column {} holds {} values, not {}",
+                column.name(),
+                dtype,
+                match humanize {
+                    Humanize::Duration => "numbers or durations",
+                    Humanize::Bytes => "numbers",
+                }
+            )),
+        };
+
+        let values = values
+            .cast(&DataType::Int64)
+            .expect("numbers can be cast to i64");
+        let formatted: Utf8Chunked = values
+            .i64()
+            .expect("the column was cast to i64")
+            .into_iter()
+            .map(|value| {
+                value.map(|value| match humanize {
+                    Humanize::Duration => format_duration(value.saturating_mul(scale)),
+                    Humanize::Bytes => format_bytes(value),
+                })
+            })
+            .collect();
+        series[index] = formatted.with_name(column.name()).into_series();
+    }
+
+    DataFrame::new(series).expect("Unable to humanize the columns of the table")
+}
+
+// --------------------------------------------------
+// write a value of a list or struct column, and the values nested in it, as
+// text: the first elements of a list with the number of others, e.g.
+// [a, b, c] +4 more, and the fields of a struct, e.g. {x: 1, y: 2.5}
+fn format_nested_value(series: &Series, i: usize) -> Option<String> {
+    if series.is_null().get(i) != Some(false) {
+        return None;
+    }
+    let format_value = |series: &Series, i: usize| {
+        format_nested_value(series, i).unwrap_or_else(|| String::from("null"))
+    };
+
+    match series.dtype() {
+        DataType::List(_) => {
+            let values = series
+                .list()
+                .expect("the column holds lists")
+                .get_as_series(i)?;
+            let shown: Vec<String> = (0..values.len().min(MAX_LIST_ITEMS))
+                .map(|j| format_value(&values, j))
+                .collect();
+            Some(match values.len().saturating_sub(MAX_LIST_ITEMS) {
+                0 => format!("[{}]", shown.join(", ")),
+                more => format!("[{}] +{} more", shown.join(", "), more),
+            })
+        }
+        DataType::Struct(_) => {
+            let fields: Vec<String> = series
+                .struct_()
+                .expect("the column holds structs")
+                .fields()
+                .iter()
+                .map(|field| format!("{}: {}", field.name(), format_value(field, i)))
+                .collect();
+            Some(format!("{{{}}}", fields.join(", ")))
+        }
+        DataType::Utf8 => series
+            .utf8()
+            .expect("the column holds text")
+            .get(i)
+            .map(String::from),
+        _ => series.str_value(i).ok().map(|value| value.into_owned()),
+    }
+}
+
+// --------------------------------------------------
+// write the list and struct columns of a table as text, which polars would
+// print as dense blobs
+pub fn format_nested(df: DataFrame) -> DataFrame {
+    let is_nested = |dtype: &DataType| matches!(dtype, DataType::List(_) | DataType::Struct(_));
+    if !df.dtypes().iter().any(is_nested) {
+        return df;
+    }
+
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| match is_nested(series.dtype()) {
+            true => (0..series.len())
+                .map(|i| format_nested_value(series, i))
+                .collect::<Utf8Chunked>()
+                .with_name(series.name())
+                .into_series(),
+            false => series.clone(),
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to format the nested values of the table")
+}
+
+// --------------------------------------------------
+// how binary values are shown: the hex of as many bytes as fit, all of it,
+// base64, or only their length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryDisplay {
+    Hex,
+    FullHex,
+    Base64,
+    Hidden,
+}
+
+// --------------------------------------------------
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+// --------------------------------------------------
+// write a binary value as text. the hex preview e.g. 0x1A2B3C… (128 B) is
+// kept to the given display width, however long the value
+fn format_binary_value(bytes: &[u8], display: BinaryDisplay, max_width: usize) -> String {
+    let length = format!("({} B)", bytes.len());
+    match display {
+        BinaryDisplay::Hex => {
+            let hex = encode_hex(bytes);
+            match 2 + hex.len() + 1 + length.len() <= max_width {
+                true => format!("0x{} {}", hex, length),
+                false => {
+                    let n_bytes = max_width.saturating_sub(2 + 1 + 1 + length.len()) / 2;
+                    format!("0x{}… {}", &hex[..2 * n_bytes], length)
+                }
+            }
+        }
+        BinaryDisplay::FullHex => format!("0x{}", encode_hex(bytes)),
+        BinaryDisplay::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+        BinaryDisplay::Hidden => length,
+    }
+}
+
+// --------------------------------------------------
+// write the binary columns of a table as text, whose raw bytes would be
+// garbled (or garble the terminal) when printed
+pub fn format_binary(df: DataFrame, display: BinaryDisplay, max_width: usize) -> DataFrame {
+    if !df.dtypes().contains(&DataType::Binary) {
+        return df;
+    }
+
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| match series.binary() {
+            Ok(values) => values
+                .into_iter()
+                .map(|value| value.map(|bytes| format_binary_value(bytes, display, max_width)))
+                .collect::<Utf8Chunked>()
+                .with_name(series.name())
+                .into_series(),
+            Err(_) => series.clone(),
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to format the binary values of the table")
+}
+
+// --------------------------------------------------
+// the null map of a table, with a marker in place of each value telling
+// whether it is null, and a footer with the number of nulls of each column
+pub fn get_null_map(df: &DataFrame) -> (DataFrame, DataFrame) {
+    let markers: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            series
+                .is_null()
+                .into_iter()
+                .map(|is_null| match is_null {
+                    Some(true) => NULL_MARKER,
+                    _ => PRESENT_MARKER,
+                })
+                .collect::<Utf8Chunked>()
+                .with_name(series.name())
+                .into_series()
+        })
+        .collect();
+    let counts: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| Series::new(series.name(), [series.null_count() as u64]))
+        .collect();
+
+    (
+        DataFrame::new(markers).expect("Unable to map the nulls of the table"),
+        DataFrame::new(counts).expect("Unable to count the nulls of the table"),
+    )
+}
+
+// --------------------------------------------------
+// which values of a column equal the value above them
+pub fn get_repeats(series: &Series) -> Vec<bool> {
+    let mut previous: Option<AnyValue> = None;
+    (0..series.len())
+        .map(|i| {
+            let value = series.get(i).ok();
+            let is_repeat = previous.is_some() && value == previous;
+            previous = value;
+            is_repeat
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// whether the value in row i of column j repeats the one above it, when the
+// repeats of that column are left out
+fn is_repeat(repeats: &[Vec<bool>], j: usize, i: usize) -> bool {
+    repeats
+        .get(j)
+        .and_then(|column| column.get(i))
+        .copied()
+        .unwrap_or_default()
+}
+
+// --------------------------------------------------
+// the aggregate of the numeric columns in the footer of a table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Count,
+}
+
+// --------------------------------------------------
+// the footer of a table, a row with the aggregate of each numeric column and
+// nothing in the others. the count leaves out the nulls
+pub fn get_totals(df: &DataFrame, aggregate: Aggregate) -> DataFrame {
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .map(|series| {
+            if !series.dtype().is_numeric() {
+                return Series::new(series.name(), [""]);
+            }
+            match aggregate {
+                Aggregate::Sum => series.sum_as_series(),
+                Aggregate::Mean => Series::new(series.name(), [series.mean()]),
+                Aggregate::Min => series.min_as_series(),
+                Aggregate::Max => series.max_as_series(),
+                Aggregate::Count => {
+                    Series::new(series.name(), [(series.len() - series.null_count()) as u64])
+                }
+            }
+        })
+        .collect();
+
+    DataFrame::new(columns).expect("Unable to aggregate the columns of the table")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_asciidoc_cells() {
+        let cases = [
+            ("a|b", "a&#124;b"),
+            // an escaped bar stays a backslash and a bar, not a backslash
+            // followed by a cell separator
+            ("a\\|b", "a&#92;&#124;b"),
+            ("a\\", "a&#92;"),
+            ("say \"hi\"", "say \"hi\""),
+            ("link:x[y]", "link:x&#91;y&#93;"),
+            ("#1 *bold* _em_", "&#35;1 &#42;bold&#42; &#95;em&#95;"),
+            ("{attribute}", "&#123;attribute&#125;"),
+            ("a &#124; b", "a &#38;&#35;124; b"),
+            ("<<xref>>", "&#60;&#60;xref>>"),
+            ("a\nb\rc\u{1}d", "a b c d"),
+        ];
+        for (text, escaped) in cases {
+            assert_eq!(escape_asciidoc(text), escaped, "{:?}", text);
+        }
+
+        // no separator is left in a row of escaped cells but its own
+        let row: Vec<String> = ["a\\|b", "c\\", "|"]
+            .iter()
+            .map(|cell| format!("|{}", escape_asciidoc(cell)))
+            .collect();
+        assert_eq!(row.join(" ").matches('|').count(), 3);
+    }
+
+    #[test]
+    fn quotes_typst_strings() {
+        let cases = [
+            ("a|b", "\"a|b\""),
+            ("a\\|b", "\"a\\\\|b\""),
+            ("a\\", "\"a\\\\\""),
+            ("say \"hi\"", "\"say \\\"hi\\\"\""),
+            ("#let x = [y]", "\"#let x = [y]\""),
+            ("a\nb\rc\td", "\"a\\nb\\rc\\td\""),
+            ("a\u{1}b\u{7f}", "\"a\\u{1}b\\u{7f}\""),
+        ];
+        for (text, quoted) in cases {
+            assert_eq!(quote_typst(text), quoted, "{:?}", text);
+        }
+    }
+}
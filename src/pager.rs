@@ -0,0 +1,97 @@
+#[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
+use std::process::Child;
+#[cfg(unix)]
+use std::sync::Mutex;
+
+// the pager that stdout has been redirected into
+#[cfg(unix)]
+static PAGER: Mutex<Option<Child>> = Mutex::new(None);
+
+// --------------------------------------------------
+// when the printed tables are paged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paging {
+    Auto,
+    Always,
+    Never,
+}
+
+// --------------------------------------------------
+// the height of the terminal that stdout prints to
+#[cfg(unix)]
+pub fn get_terminal_height() -> Option<usize> {
+    // SAFETY: the size is a plain struct, filled in by the ioctl
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    match unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } {
+        0 if size.ws_row > 0 => Some(size.ws_row as usize),
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+// redirect stdout into $PAGER (less -SRX by default, which scrolls long lines
+// sideways and keeps the colors), unless it already is. the pager is run
+// through the shell, and when it cannot be started the tables are printed
+// as they are
+#[cfg(unix)]
+pub fn start_pager() {
+    use std::os::fd::AsRawFd;
+    use std::process::{Command, Stdio};
+
+    let mut pager = PAGER.lock().expect("the pager is not poisoned");
+    if pager.is_some() {
+        return;
+    }
+
+    let command = std::env::var("PAGER")
+        .ok()
+        .filter(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| String::from("less -SRX"));
+    let Ok(mut child) = Command::new("sh")
+        .args(["-c", &command])
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return;
+    };
+    let stdin = child.stdin.take().expect("the standard input is piped");
+
+    let _ = std::io::stdout().flush();
+    // SAFETY: both file descriptors are open, and stdout is flushed
+    if unsafe { libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) } == -1 {
+        let _ = child.kill();
+        let _ = child.wait();
+        return;
+    }
+    *pager = Some(child);
+}
+
+// --------------------------------------------------
+// close stdout so that the pager reaches the end of the tables, and wait
+// until it is quit
+#[cfg(unix)]
+pub fn wait_for_pager() {
+    let Some(mut child) = PAGER.lock().expect("the pager is not poisoned").take() else {
+        return;
+    };
+
+    let _ = std::io::stdout().flush();
+    // SAFETY: stdout is flushed and not written to afterwards
+    unsafe { libc::close(libc::STDOUT_FILENO) };
+    let _ = child.wait();
+}
+
+// stdout cannot be redirected into a pager elsewhere, so the tables are
+// printed as they are
+#[cfg(not(unix))]
+pub fn get_terminal_height() -> Option<usize> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn start_pager() {}
+
+#[cfg(not(unix))]
+pub fn wait_for_pager() {}
@@ -0,0 +1,127 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+use polars_parquet::read::{infer_schema, read_metadata, FileReader};
+use std::io::{Read, Seek};
+
+// --------------------------------------------------
+// read the given row groups of parquet content (or all of them), in file
+// order, folding each into the rows read before it until the fold is done
+fn fold_row_groups(
+    mut reader: impl Read + Seek,
+    source: &str,
+    row_groups: Option<&[usize]>,
+    select_columns: Option<Vec<String>>,
+    mut fold: impl FnMut(DataFrame, DataFrame) -> (DataFrame, bool),
+) -> DataFrame {
+    let read_error = |e: PolarsError| -> ! {
+        exit_with_error(&format!(
+            "Unable to parse the Parquet file {}: {}",
+            source, e
+        ))
+    };
+
+    let metadata = read_metadata(&mut reader).unwrap_or_else(|e| read_error(e));
+    let n_row_groups = metadata.row_groups.len();
+    let row_groups = row_groups.map_or_else(|| (0..n_row_groups).collect(), <[usize]>::to_vec);
+    if let Some(row_group) = row_groups.iter().find(|&&i| i >= n_row_groups) {
+        exit_with_error(&format!(
+            "row group {} is out of range, {} has {} row groups (0-{})",
+            row_group,
+            source,
+            n_row_groups,
+            n_row_groups.saturating_sub(1)
+        ));
+    }
+
+    let mut schema = infer_schema(&metadata).unwrap_or_else(|e| read_error(e));
+    if let Some(columns) = &select_columns {
+        schema.fields.retain(|field| columns.contains(&field.name));
+    }
+    let fields = schema.fields.clone();
+
+    let mut selected_row_groups = row_groups;
+    selected_row_groups.sort_unstable();
+    selected_row_groups.dedup();
+    let selected_row_groups = selected_row_groups
+        .into_iter()
+        .map(|i| metadata.row_groups[i].clone())
+        .collect();
+
+    let mut df = DataFrame::from(&Schema::from_iter(fields.iter().map(Field::from)));
+    for chunk in FileReader::new(reader, selected_row_groups, schema, None, None, None) {
+        let chunk = chunk.unwrap_or_else(|e| read_error(e));
+        let chunk_df =
+            DataFrame::try_from((chunk, fields.as_slice())).unwrap_or_else(|e| read_error(e));
+        let done;
+        (df, done) = fold(df, chunk_df);
+        if done {
+            break;
+        }
+    }
+
+    // the schema keeps the file order of the columns, so reorder them and
+    // report the ones that are missing
+    match select_columns {
+        Some(columns) => df.select(columns).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to select columns from {}: {}", source, e))
+        }),
+        None => df,
+    }
+}
+
+// --------------------------------------------------
+// parse only the given row groups of parquet content, in file order. the
+// row limit applies to the rows of the selected groups
+pub fn parse_parquet_row_groups(
+    reader: impl Read + Seek,
+    source: &str,
+    row_groups: &[usize],
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    // the reader's own limit miscounts the rows of later row groups, so stop
+    // once enough rows have been read instead
+    let df = fold_row_groups(
+        reader,
+        source,
+        Some(row_groups),
+        select_columns,
+        |mut df, chunk_df| {
+            df.vstack_mut(&chunk_df).unwrap_or_else(|e| {
+                exit_with_error(&format!(
+                    "Unable to parse the Parquet file {}: {}",
+                    source, e
+                ))
+            });
+            let done = n_rows.is_some_and(|n| df.height() >= n);
+            (df, done)
+        },
+    );
+    match n_rows {
+        Some(n) => df.head(Some(n)),
+        None => df,
+    }
+}
+
+// --------------------------------------------------
+// parse the rows of parquet content that are kept by a sort with a row
+// limit, one row group at a time, so that only the rows kept so far and a
+// row group are held rather than the whole file. the rows kept before come
+// first, so that rows that tie keep their order
+pub fn parse_parquet_sorted_rows(
+    reader: impl Read + Seek,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    keep_rows: impl Fn(DataFrame) -> DataFrame,
+) -> DataFrame {
+    let df = fold_row_groups(reader, source, None, select_columns, |mut df, chunk_df| {
+        df.vstack_mut(&chunk_df).unwrap_or_else(|e| {
+            exit_with_error(&format!(
+                "Unable to parse the Parquet file {}: {}",
+                source, e
+            ))
+        });
+        (keep_rows(df), false)
+    });
+    keep_rows(df)
+}
@@ -0,0 +1,122 @@
+use crate::output::format_bytes;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// how long an input loads before its progress is shown, and how often the
+// progress is redrawn
+const PROGRESS_DELAY: Duration = Duration::from_millis(500);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// the bytes read so far from the input being loaded
+static BYTES_READ: AtomicU64 = AtomicU64::new(0);
+
+// the thread drawing the progress, and the flag that stops it
+static PROGRESS: Mutex<Option<(JoinHandle<()>, Arc<AtomicBool>)>> = Mutex::new(None);
+
+// --------------------------------------------------
+// a reader that counts the bytes read from it towards the progress
+pub struct ProgressReader<R> {
+    reader: R,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        BYTES_READ.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+// --------------------------------------------------
+pub fn track_progress<R: Read>(reader: R) -> ProgressReader<R> {
+    ProgressReader { reader }
+}
+
+// --------------------------------------------------
+// the bytes read out of the total (when it is known), or the size of a file
+// that is mapped into memory rather than read, and the time spent
+fn format_progress(total: Option<u64>, elapsed: Duration) -> String {
+    let bytes_read = BYTES_READ.load(Ordering::Relaxed);
+    let amount = match (bytes_read, total) {
+        (0, Some(total)) => format_bytes(total as i64),
+        (bytes_read, Some(total)) if total > 0 => format!(
+            "{} / {} ({}%)",
+            format_bytes(bytes_read as i64),
+            format_bytes(total as i64),
+            (bytes_read.saturating_mul(100) / total).min(100)
+        ),
+        (bytes_read, _) => format_bytes(bytes_read as i64),
+    };
+    format!("{}, {}s", amount, elapsed.as_secs())
+}
+
+// --------------------------------------------------
+// draw a spinner and the progress on a line of stderr, once the input has
+// loaded for a while, until it is done. the line is cleared afterwards
+fn draw_progress(source: &str, total: Option<u64>, done: &AtomicBool) {
+    let start = Instant::now();
+    while let Some(delay) = PROGRESS_DELAY.checked_sub(start.elapsed()) {
+        if done.load(Ordering::Relaxed) {
+            return;
+        }
+        std::thread::park_timeout(delay);
+    }
+
+    let mut stderr = std::io::stderr();
+    for frame in SPINNER.iter().cycle() {
+        if done.load(Ordering::Relaxed) {
+            break;
+        }
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{} Loading {}: {}",
+            frame,
+            source,
+            format_progress(total, start.elapsed())
+        );
+        let _ = stderr.flush();
+        std::thread::park_timeout(PROGRESS_INTERVAL);
+    }
+    let _ = write!(stderr, "\r\x1b[2K");
+    let _ = stderr.flush();
+}
+
+// --------------------------------------------------
+// show the progress of loading an input of the given size on stderr, unless
+// stderr is redirected
+pub fn start_progress(source: &str, total: Option<u64>) {
+    let mut progress = PROGRESS.lock().expect("the progress is not poisoned");
+    if progress.is_some() || !std::io::stderr().is_terminal() {
+        return;
+    }
+
+    BYTES_READ.store(0, Ordering::Relaxed);
+    let done = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let source = source.to_string();
+        let done = Arc::clone(&done);
+        std::thread::spawn(move || draw_progress(&source, total, &done))
+    };
+    *progress = Some((thread, done));
+}
+
+// --------------------------------------------------
+// stop showing the progress, clearing its line
+pub fn finish_progress() {
+    let Some((thread, done)) = PROGRESS
+        .lock()
+        .expect("the progress is not poisoned")
+        .take()
+    else {
+        return;
+    };
+
+    done.store(true, Ordering::Relaxed);
+    thread.thread().unpark();
+    let _ = thread.join();
+}
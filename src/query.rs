@@ -0,0 +1,56 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+use polars::sql::SQLContext;
+use std::path::Path;
+
+// --------------------------------------------------
+// the name that an input is queried by: its file name without its
+// extensions, with the characters other than letters, digits, and
+// underscores replaced, e.g. sales_2024 for sales-2024.csv.gz
+pub fn get_query_table_name(filepath: &str) -> String {
+    let file_name = Path::new(filepath)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(filepath);
+    let stem = file_name.split('.').next().unwrap_or_default();
+    let name: String = stem
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    // e.g. stdin (-) or the clipboard (@clipboard)
+    let name = name.trim_start_matches('_');
+    match name.chars().next() {
+        None => String::from("stdin"),
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name.to_string(),
+    }
+}
+
+// --------------------------------------------------
+// run a sql query against the inputs, each registered under its name, and
+// the first also as tbl
+pub fn run_query(tables: Vec<(String, DataFrame)>, query: &str) -> DataFrame {
+    let mut context = SQLContext::new();
+    let mut names = Vec::<&str>::new();
+    for (i, (name, df)) in tables.iter().enumerate() {
+        if names.contains(&name.as_str()) {
+            exit_with_error(&format!(
+                "Unable to run the SQL query: two inputs are named {}",
+                name
+            ));
+        }
+        if i == 0 {
+            context.register("tbl", df.clone().lazy());
+        }
+        context.register(name, df.clone().lazy());
+        names.push(name);
+    }
+
+    context
+        .execute(query)
+        .and_then(LazyFrame::collect)
+        .unwrap_or_else(|e| exit_with_error(&format!("Unable to run the SQL query: {}", e)))
+}
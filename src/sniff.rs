@@ -0,0 +1,319 @@
+use crate::compression::{decompress, get_compression_from_magic};
+use crate::encoding::{is_utf16, UTF8_BOM};
+use crate::FileFormat;
+use std::io::Read;
+
+// number of leading bytes inspected to guess the format
+pub const SNIFF_LENGTH: usize = 4096;
+
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+const IPC_MAGIC: &[u8] = b"ARROW1";
+// ipc stream messages start with a continuation marker
+const IPC_STREAM_MAGIC: &[u8] = b"\xff\xff\xff\xff";
+const AVRO_MAGIC: &[u8] = b"Obj\x01";
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+// duckdb files start with a checksum, followed by the magic bytes
+const DUCKDB_MAGIC: &[u8] = b"DUCK";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const OLE_MAGIC: &[u8] = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+
+// delimiters tried in order of preference when the field counts tie
+const DELIMITERS: [char; 4] = ['\t', ',', ';', '|'];
+// number of leading records compared when guessing the delimiter
+const SNIFF_RECORDS: usize = 10;
+
+// --------------------------------------------------
+// read up to `limit` bytes, stopping early at the end of the stream
+fn read_prefix(reader: &mut impl Read, limit: usize) -> Vec<u8> {
+    let mut prefix = Vec::<u8>::with_capacity(limit);
+    // a truncated compressed stream errors once the prefix is used up
+    let _ = reader.take(limit as u64).read_to_end(&mut prefix);
+    prefix
+}
+
+// --------------------------------------------------
+// check whether text is an html page (or fragment) holding a table
+fn is_html(text: &[u8]) -> bool {
+    if text.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'<') {
+        return false;
+    }
+
+    let text = text.to_ascii_lowercase();
+    [&b"<!doctype html"[..], b"<html", b"<table"]
+        .iter()
+        .any(|tag| text.windows(tag.len()).any(|window| window == *tag))
+}
+
+// --------------------------------------------------
+// guess the format of a table from the leading bytes of its content
+fn sniff_format_from_prefix(prefix: &[u8]) -> FileFormat {
+    if prefix.starts_with(PARQUET_MAGIC) {
+        return FileFormat::Parquet;
+    }
+
+    if prefix.starts_with(IPC_MAGIC) {
+        return FileFormat::Ipc;
+    }
+
+    if prefix.starts_with(IPC_STREAM_MAGIC) {
+        return FileFormat::IpcStream;
+    }
+
+    if prefix.starts_with(AVRO_MAGIC) {
+        return FileFormat::Avro;
+    }
+
+    if prefix.starts_with(SQLITE_MAGIC) {
+        return FileFormat::Sqlite;
+    }
+
+    if prefix.get(8..12) == Some(DUCKDB_MAGIC) {
+        return FileFormat::DuckDb;
+    }
+
+    // xlsx and ods are zip archives, xls is an ole compound document
+    if prefix.starts_with(ZIP_MAGIC) || prefix.starts_with(OLE_MAGIC) {
+        return FileFormat::Excel;
+    }
+
+    let text = prefix.strip_prefix(UTF8_BOM).unwrap_or(prefix);
+    if is_html(text) {
+        return FileFormat::Html;
+    }
+
+    match text.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => FileFormat::Json,
+        Some(b'{') => FileFormat::NdJson,
+        _ => FileFormat::Csv,
+    }
+}
+
+// --------------------------------------------------
+// read a bounded prefix of the content, looking through gzip, zstd, bzip2,
+// and xz compression
+fn read_content_prefix(reader: &mut impl Read) -> Vec<u8> {
+    let prefix = read_prefix(reader, SNIFF_LENGTH);
+
+    let decompressed = get_compression_from_magic(&prefix).and_then(|compression| {
+        decompress(prefix.as_slice(), &compression)
+            .ok()
+            .map(|mut decoder| read_prefix(&mut decoder, SNIFF_LENGTH))
+    });
+
+    decompressed.unwrap_or(prefix)
+}
+
+// --------------------------------------------------
+// guess the format of a table by reading a bounded prefix of its content.
+// the bytes read are consumed, so callers should rewind or re-buffer the
+// reader afterwards
+pub fn sniff_format(reader: &mut impl Read) -> FileFormat {
+    sniff_format_from_prefix(&read_content_prefix(reader))
+}
+
+// --------------------------------------------------
+// check whether the content is utf-16 text, which has to be transcoded
+// before it is parsed
+pub fn sniff_utf16(reader: &mut impl Read) -> bool {
+    is_utf16(&read_content_prefix(reader))
+}
+
+// --------------------------------------------------
+// split delimited text into records, keeping line breaks inside quotes
+fn split_records(text: &[u8]) -> Vec<&[u8]> {
+    let mut records = Vec::<&[u8]>::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (i, byte) in text.iter().enumerate() {
+        match byte {
+            b'"' => in_quotes = !in_quotes,
+            b'\n' if !in_quotes => {
+                records.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < text.len() {
+        records.push(&text[start..]);
+    }
+
+    records
+        .into_iter()
+        .map(|record| record.strip_suffix(b"\r").unwrap_or(record))
+        .filter(|record| !record.is_empty())
+        .collect()
+}
+
+// --------------------------------------------------
+// count the fields of a record, ignoring delimiters inside quotes
+fn count_fields(record: &[u8], delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut n_fields = 1;
+
+    for byte in record {
+        match byte {
+            b'"' => in_quotes = !in_quotes,
+            _ if *byte == delimiter && !in_quotes => n_fields += 1,
+            _ => {}
+        }
+    }
+
+    n_fields
+}
+
+// --------------------------------------------------
+// guess the delimiter of a table from the leading bytes of its content,
+// picking the one that splits every record into the same number of fields
+fn sniff_delimiter_from_prefix(prefix: &[u8]) -> char {
+    let mut records = split_records(prefix);
+
+    // the last record may have been cut short by the prefix limit
+    if records.len() > 1 && !prefix.ends_with(b"\n") {
+        records.pop();
+    }
+    records.truncate(SNIFF_RECORDS);
+
+    DELIMITERS
+        .iter()
+        .rev()
+        .filter_map(|&delimiter| {
+            let mut counts = records
+                .iter()
+                .map(|record| count_fields(record, delimiter as u8));
+            let n_fields = counts.next()?;
+            (n_fields > 1 && counts.all(|count| count == n_fields)).then_some((delimiter, n_fields))
+        })
+        .max_by_key(|&(_, n_fields)| n_fields)
+        .map_or(',', |(delimiter, _)| delimiter)
+}
+
+// --------------------------------------------------
+// guess the delimiter of a table by reading a bounded prefix of its content.
+// like `sniff_format`, the bytes read are consumed
+pub fn sniff_delimiter(reader: &mut impl Read) -> char {
+    sniff_delimiter_from_prefix(&read_content_prefix(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use std::io::Write;
+
+    const CSV: &[u8] = b"id,name\n1,a\n2,b\n";
+    const JSON: &[u8] = b"[{\"id\": 1}, {\"id\": 2}]";
+    const NDJSON: &[u8] = b"{\"id\": 1}\n{\"id\": 2}\n";
+
+    fn sniff(bytes: &[u8]) -> FileFormat {
+        sniff_format(&mut &bytes[..])
+    }
+
+    fn frame() -> DataFrame {
+        df!("id" => [1i64, 2], "name" => ["a", "b"]).unwrap()
+    }
+
+    #[test]
+    fn sniffs_gzip_content() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(NDJSON).unwrap();
+        assert_eq!(sniff(&encoder.finish().unwrap()), FileFormat::NdJson);
+    }
+
+    #[test]
+    fn sniffs_zstd_content() {
+        let compressed = zstd::encode_all(JSON, 0).unwrap();
+        assert_eq!(sniff(&compressed), FileFormat::Json);
+    }
+
+    #[test]
+    fn sniffs_bzip2_content() {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(CSV).unwrap();
+        assert_eq!(sniff(&encoder.finish().unwrap()), FileFormat::Csv);
+    }
+
+    #[test]
+    fn sniffs_xz_content() {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(NDJSON).unwrap();
+        assert_eq!(sniff(&encoder.finish().unwrap()), FileFormat::NdJson);
+    }
+
+    #[test]
+    fn sniffs_zip_as_excel() {
+        assert_eq!(sniff(b"PK\x03\x04\x14\x00\x06\x00"), FileFormat::Excel);
+    }
+
+    #[test]
+    fn sniffs_parquet() {
+        let mut bytes = Vec::<u8>::new();
+        ParquetWriter::new(&mut bytes).finish(&mut frame()).unwrap();
+        assert_eq!(sniff(&bytes), FileFormat::Parquet);
+    }
+
+    #[test]
+    fn sniffs_ipc_file() {
+        let mut bytes = Vec::<u8>::new();
+        IpcWriter::new(&mut bytes).finish(&mut frame()).unwrap();
+        assert_eq!(sniff(&bytes), FileFormat::Ipc);
+    }
+
+    #[test]
+    fn sniffs_ipc_stream() {
+        let mut bytes = Vec::<u8>::new();
+        IpcStreamWriter::new(&mut bytes)
+            .finish(&mut frame())
+            .unwrap();
+        assert_eq!(sniff(&bytes), FileFormat::IpcStream);
+    }
+
+    #[test]
+    fn sniffs_json() {
+        assert_eq!(sniff(JSON), FileFormat::Json);
+        assert_eq!(sniff(b"\n  [1, 2]"), FileFormat::Json);
+    }
+
+    #[test]
+    fn sniffs_ndjson() {
+        assert_eq!(sniff(NDJSON), FileFormat::NdJson);
+    }
+
+    #[test]
+    fn falls_back_to_csv() {
+        assert_eq!(sniff(CSV), FileFormat::Csv);
+        assert_eq!(sniff(b""), FileFormat::Csv);
+    }
+
+    fn sniff_delimiter_of(text: &str) -> char {
+        sniff_delimiter(&mut text.as_bytes())
+    }
+
+    #[test]
+    fn sniffs_each_delimiter() {
+        assert_eq!(sniff_delimiter_of("id\tname\n1\ta\n2\tb\n"), '\t');
+        assert_eq!(sniff_delimiter_of("id,name\n1,a\n2,b\n"), ',');
+        assert_eq!(sniff_delimiter_of("id;name\n1;a\n2;b\n"), ';');
+        assert_eq!(sniff_delimiter_of("id|name\n1|a\n2|b\n"), '|');
+    }
+
+    #[test]
+    fn ignores_delimiters_in_quoted_fields() {
+        let text = "name;city\n\"Doe, Jane\";\"Paris, France\"\n\"Roe, Rick\";Rome\n";
+        assert_eq!(sniff_delimiter_of(text), ';');
+        let text = "name\tnote\n\"a\"\t\"x, y, z\"\n\"b\"\t\"w\"\n";
+        assert_eq!(sniff_delimiter_of(text), '\t');
+    }
+
+    #[test]
+    fn sniffs_single_column_as_comma() {
+        assert_eq!(sniff_delimiter_of("name\nalice\nbob\n"), ',');
+        assert_eq!(sniff_delimiter_of("note\n\"a, b\"\n\"c; d\"\n"), ',');
+    }
+
+    #[test]
+    fn sniffs_inconsistent_counts_as_comma() {
+        assert_eq!(sniff_delimiter_of("a;b\n1;2;3\n4\n"), ',');
+    }
+}
@@ -0,0 +1,203 @@
+use polars::prelude::*;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+
+// --------------------------------------------------
+// a single cell read from a sqlite row
+enum Cell {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Cell {
+    fn is_null(&self) -> bool {
+        matches!(self, Cell::Null)
+    }
+}
+
+// --------------------------------------------------
+fn open_database(filepath: &str) -> Connection {
+    Connection::open_with_flags(filepath, OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap_or_else(|e| {
+        crate::exit_with_error(&format!("Unable to open the database {}: {}", filepath, e))
+    })
+}
+
+// --------------------------------------------------
+// quote an identifier for use in a sqlite query
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+// --------------------------------------------------
+// get the names of the tables and views in the database
+pub fn get_table_names(filepath: &str) -> Vec<String> {
+    let connection = open_database(filepath);
+
+    let mut statement = connection
+        .prepare(
+            "SELECT name FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+             ORDER BY name",
+        )
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!("Unable to list the tables in {}: {}", filepath, e))
+        });
+
+    statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(Iterator::collect)
+        .unwrap_or_else(|e| {
+            crate::exit_with_error(&format!("Unable to list the tables in {}: {}", filepath, e))
+        })
+}
+
+// --------------------------------------------------
+// build a typed series from a column of cells, falling back to strings
+// when the cells do not share a type
+fn cells_to_series(name: &str, cells: Vec<Cell>) -> Series {
+    if cells.iter().all(Cell::is_null) {
+        return Series::full_null(name, cells.len(), &DataType::Utf8);
+    }
+
+    if cells
+        .iter()
+        .all(|cell| cell.is_null() || matches!(cell, Cell::Integer(_)))
+    {
+        let column: Vec<Option<i64>> = cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Integer(value) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, column);
+    }
+
+    if cells
+        .iter()
+        .all(|cell| cell.is_null() || matches!(cell, Cell::Integer(_) | Cell::Real(_)))
+    {
+        let column: Vec<Option<f64>> = cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Integer(value) => Some(*value as f64),
+                Cell::Real(value) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, column);
+    }
+
+    if cells
+        .iter()
+        .all(|cell| cell.is_null() || matches!(cell, Cell::Blob(_)))
+    {
+        let column: Vec<Option<&[u8]>> = cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Blob(value) => Some(value.as_slice()),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, column);
+    }
+
+    let column: Vec<Option<String>> = cells
+        .into_iter()
+        .map(|cell| match cell {
+            Cell::Null => None,
+            Cell::Integer(value) => Some(value.to_string()),
+            Cell::Real(value) => Some(value.to_string()),
+            Cell::Text(value) => Some(value),
+            Cell::Blob(value) => Some(String::from_utf8_lossy(&value).into_owned()),
+        })
+        .collect();
+    Series::new(name, column)
+}
+
+// --------------------------------------------------
+// parse a table (or view) of a sqlite database, or the result of a query
+pub fn parse_sqlite_table(
+    filepath: &str,
+    table: &str,
+    query: Option<&str>,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let connection = open_database(filepath);
+
+    let columns = match select_columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<String>>()
+            .join(", "),
+        None => String::from("*"),
+    };
+    let mut sql = match query {
+        Some(query) => {
+            let query = query.trim().trim_end_matches(';');
+            match columns == "*" && n_rows.is_none() {
+                true => query.to_string(),
+                false => format!("SELECT {} FROM ({}) AS query", columns, query),
+            }
+        }
+        None => format!("SELECT {} FROM {}", columns, quote_identifier(table)),
+    };
+    if let Some(n) = n_rows {
+        sql.push_str(&format!(" LIMIT {}", n));
+    }
+
+    let query_error = |e: rusqlite::Error| -> ! {
+        match query {
+            Some(query) => crate::exit_with_error(&format!(
+                "Unable to run the query `{}` against the database {}: {}",
+                query, filepath, e
+            )),
+            None => crate::exit_with_error(&format!(
+                "Unable to query the table {} in the database {}: {}",
+                table, filepath, e
+            )),
+        }
+    };
+
+    let mut statement = connection.prepare(&sql).unwrap_or_else(|e| query_error(e));
+    let column_names: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let mut cells: Vec<Vec<Cell>> = column_names.iter().map(|_| Vec::new()).collect();
+    let mut rows = statement.query([]).unwrap_or_else(|e| query_error(e));
+    while let Some(row) = rows.next().unwrap_or_else(|e| {
+        crate::exit_with_error(&format!("Unable to read a row from {}: {}", filepath, e))
+    }) {
+        for (i, column) in cells.iter_mut().enumerate() {
+            let cell = match row.get_ref(i) {
+                Ok(ValueRef::Integer(value)) => Cell::Integer(value),
+                Ok(ValueRef::Real(value)) => Cell::Real(value),
+                Ok(ValueRef::Text(value)) => Cell::Text(String::from_utf8_lossy(value).into()),
+                Ok(ValueRef::Blob(value)) => Cell::Blob(value.to_vec()),
+                Ok(ValueRef::Null) | Err(_) => Cell::Null,
+            };
+            column.push(cell);
+        }
+    }
+
+    let columns: Vec<Series> = column_names
+        .iter()
+        .zip(cells)
+        .map(|(name, column)| cells_to_series(name, column))
+        .collect();
+
+    DataFrame::new(columns).unwrap_or_else(|e| {
+        crate::exit_with_error(&format!(
+            "Unable to parse the result of the query against {}: {}",
+            filepath, e
+        ))
+    })
+}
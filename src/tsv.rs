@@ -0,0 +1,29 @@
+// --------------------------------------------------
+// quote a field of tab-separated text that the csv reader would otherwise
+// misread: one holding a quote, a tab, or a line break
+pub fn escape_field(field: &str) -> String {
+    if field.contains(['"', '\t', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_plain_fields_alone() {
+        assert_eq!(escape_field("a b, c"), "a b, c");
+        assert_eq!(escape_field(""), "");
+    }
+
+    #[test]
+    fn quotes_fields_the_reader_would_misread() {
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("a\tb"), "\"a\tb\"");
+        assert_eq!(escape_field("a\nb"), "\"a\nb\"");
+        assert_eq!(escape_field("a\r\nb"), "\"a\r\nb\"");
+    }
+}
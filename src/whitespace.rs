@@ -0,0 +1,38 @@
+use crate::tsv::escape_field;
+
+// --------------------------------------------------
+// rewrite text whose columns are separated by runs of whitespace (as in the
+// output of ps or df) as tab-separated text, padding short lines with empty
+// fields and naming the columns that the header row is missing
+pub fn whitespace_to_tsv(bytes: &[u8], has_header: bool) -> Vec<u8> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut rows: Vec<Vec<&str>> = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+        .filter(|fields| !fields.is_empty())
+        .collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    let generated_names: Vec<String> = (0..width).map(|i| format!("column_{}", i + 1)).collect();
+    if has_header {
+        if let Some(header) = rows.first_mut() {
+            let n_named = header.len();
+            header.extend(generated_names[n_named..].iter().map(String::as_str));
+        }
+    }
+
+    let mut tsv = String::new();
+    for fields in &rows {
+        for i in 0..width {
+            if i > 0 {
+                tsv.push('\t');
+            }
+            if let Some(field) = fields.get(i) {
+                tsv.push_str(&escape_field(field));
+            }
+        }
+        tsv.push('\n');
+    }
+
+    tsv.into_bytes()
+}
@@ -0,0 +1,154 @@
+use crate::exit_with_error;
+use polars::prelude::*;
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+// --------------------------------------------------
+// format a value that does not fit a column type, such as a sequence
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => value.clone(),
+        Value::Sequence(values) => {
+            let values: Vec<String> = values.iter().map(format_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        Value::Mapping(mapping) => {
+            let entries: Vec<String> = mapping
+                .iter()
+                .map(|(key, value)| format!("{}: {}", format_value(key), format_value(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Tagged(tagged) => format!("{} {}", tagged.tag, format_value(&tagged.value)),
+    }
+}
+
+// --------------------------------------------------
+// flatten nested mappings into dotted column names, e.g. `address.city`
+fn flatten_record(
+    prefix: &str,
+    value: &Value,
+    record: &mut HashMap<String, Value>,
+    column_names: &mut Vec<String>,
+) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = format_value(key);
+                let name = match prefix {
+                    "" => key,
+                    _ => format!("{}.{}", prefix, key),
+                };
+                flatten_record(&name, value, record, column_names);
+            }
+        }
+        _ => {
+            if !column_names.iter().any(|column_name| column_name == prefix) {
+                column_names.push(prefix.to_string());
+            }
+            record.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+// --------------------------------------------------
+// build a typed series from a column of values, falling back to strings
+// when the values do not share a type
+fn values_to_series(name: &str, values: &[Option<&Value>]) -> Series {
+    let values: Vec<Option<&Value>> = values
+        .iter()
+        .map(|value| value.filter(|value| !value.is_null()))
+        .collect();
+    let present: Vec<&Value> = values.iter().flatten().copied().collect();
+
+    if present.is_empty() {
+        return Series::full_null(name, values.len(), &DataType::Utf8);
+    }
+
+    if present.iter().all(|value| value.is_i64()) {
+        let column: Vec<Option<i64>> = values
+            .iter()
+            .map(|value| value.and_then(Value::as_i64))
+            .collect();
+        return Series::new(name, column);
+    }
+
+    if present.iter().all(|value| value.is_number()) {
+        let column: Vec<Option<f64>> = values
+            .iter()
+            .map(|value| value.and_then(Value::as_f64))
+            .collect();
+        return Series::new(name, column);
+    }
+
+    if present.iter().all(|value| value.is_bool()) {
+        let column: Vec<Option<bool>> = values
+            .iter()
+            .map(|value| value.and_then(Value::as_bool))
+            .collect();
+        return Series::new(name, column);
+    }
+
+    let column: Vec<Option<String>> = values.iter().map(|value| value.map(format_value)).collect();
+    Series::new(name, column)
+}
+
+// --------------------------------------------------
+// parse a yaml sequence of mappings, one row per mapping. keys missing
+// from some mappings become nulls
+pub fn parse_yaml(
+    bytes: Vec<u8>,
+    source: &str,
+    select_columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+) -> DataFrame {
+    let document: Value = serde_yaml::from_slice(&bytes).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to parse the YAML file {}: {}", source, e))
+    });
+    let Value::Sequence(items) = document else {
+        exit_with_error(&format!(
+            "Expected a list of mappings in the YAML file {}",
+            source
+        ));
+    };
+
+    let mut column_names = Vec::<String>::new();
+    let records: Vec<HashMap<String, Value>> = items
+        .iter()
+        .take(n_rows.unwrap_or(usize::MAX))
+        .map(|item| {
+            if !item.is_mapping() {
+                exit_with_error(&format!(
+                    "Expected a list of mappings in the YAML file {}",
+                    source
+                ));
+            }
+            let mut record = HashMap::new();
+            flatten_record("", item, &mut record, &mut column_names);
+            record
+        })
+        .collect();
+
+    let columns: Vec<Series> = column_names
+        .iter()
+        .map(|name| {
+            let values: Vec<Option<&Value>> =
+                records.iter().map(|record| record.get(name)).collect();
+            values_to_series(name, &values)
+        })
+        .collect();
+
+    let df = DataFrame::new(columns).unwrap_or_else(|e| {
+        exit_with_error(&format!("Unable to parse the YAML file {}: {}", source, e))
+    });
+
+    match select_columns {
+        Some(columns) => df.select(columns).unwrap_or_else(|e| {
+            exit_with_error(&format!("Unable to select columns from {}: {}", source, e))
+        }),
+        None => df,
+    }
+}
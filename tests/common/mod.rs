@@ -0,0 +1,38 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use std::process::Output;
+
+// --------------------------------------------------
+// run printbl with the arguments and the bytes on stdin, checking that it
+// succeeds
+pub fn run_for_output(args: &[&str], stdin: impl Into<Vec<u8>>) -> Output {
+    let output = cargo_bin_cmd!("printbl")
+        .args(args)
+        .write_stdin(stdin.into())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "printbl {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+// --------------------------------------------------
+// run printbl with the arguments and the bytes on stdin, returning stdout
+// and stderr
+pub fn run_with_stdin(args: &[&str], stdin: impl Into<Vec<u8>>) -> (String, String) {
+    let output = run_for_output(args, stdin);
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+// --------------------------------------------------
+// run printbl with the arguments, returning stdout
+#[allow(dead_code)]
+pub fn run(args: &[&str]) -> String {
+    run_with_stdin(args, Vec::new()).0
+}
@@ -0,0 +1,508 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use common::{run, run_with_stdin};
+use polars::prelude::*;
+
+mod common;
+
+// --------------------------------------------------
+// a small frame with an integer, a string, and a float column, with nulls
+fn sample_frame() -> DataFrame {
+    df!(
+        "id" => [1i64, 2, 3, 4],
+        "name" => [Some("a"), Some("b"), None, Some("d")],
+        "price" => [Some(1.5), None, Some(3.0), Some(-2.25)],
+    )
+    .unwrap()
+}
+
+// --------------------------------------------------
+// write the contents to a file of the directory, returning its path
+fn write_file(dir: &tempfile::TempDir, name: &str, contents: &[u8]) -> String {
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_an_ipc_stream_from_stdin() {
+    let mut stream = Vec::<u8>::new();
+    IpcStreamWriter::new(&mut stream)
+        .finish(&mut sample_frame())
+        .unwrap();
+
+    let (stdout, _) = run_with_stdin(&["-", "--output-format", "csv"], stream.clone());
+    assert_eq!(stdout, "id,name,price\n1,a,1.5\n2,b,\n3,,3.0\n4,d,-2.25\n");
+
+    let (stdout, _) = run_with_stdin(&["-", "-n", "2", "--output-format", "csv"], stream);
+    assert_eq!(stdout, "id,name,price\n1,a,1.5\n2,b,\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_a_feather_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.feather");
+    IpcWriter::new(std::fs::File::create(&path).unwrap())
+        .finish(&mut sample_frame())
+        .unwrap();
+    let path = path.to_str().unwrap();
+
+    let stdout = run(&[
+        path,
+        "--select",
+        "id,name",
+        "-n",
+        "2",
+        "--output-format",
+        "csv",
+    ]);
+    assert_eq!(stdout, "id,name\n1,a\n2,b\n");
+
+    let stdout = run(&[path, "--column-names-only"]);
+    assert_eq!(stdout, "[\n    \"id\",\n    \"name\",\n    \"price\",\n]\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn detects_the_delimiter_of_piped_text() {
+    let output = cargo_bin_cmd!("printbl")
+        .args(["-", "--verbose", "--output-format", "csv"])
+        .write_stdin("id\tname\n1\t\"a, b\"\n")
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "id,name\n1,\"a, b\"\n"
+    );
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Detected delimiter '\\t' in stdin"));
+
+    // an explicit delimiter wins over the detected one
+    let (stdout, _) = run_with_stdin(
+        &["-", "--delimiter", ",", "--output-format", "csv"],
+        b"id\tname\n1\ta\n".to_vec(),
+    );
+    assert_eq!(stdout, "id\tname\n1\ta\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_psv_txt_and_mapped_extensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let psv = write_file(&dir, "data.psv", b"id|name\n1|a\n");
+    let txt = write_file(&dir, "data.txt", b"id\tname\n1\ta\n");
+    let dat = write_file(&dir, "data.dat", b"id;name\n1;a\n");
+
+    for args in [
+        vec![psv.as_str()],
+        vec![txt.as_str()],
+        vec![dat.as_str(), "--ext-map", "dat=;"],
+    ] {
+        let stdout = run(&[args.as_slice(), &["--output-format", "csv"]].concat());
+        assert_eq!(stdout, "id,name\n1,a\n", "printbl {}", args.join(" "));
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn sniffs_files_with_unknown_extensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let tsv = write_file(&dir, "data.unknown", b"id\tname\n1\ta\n");
+    let json = write_file(&dir, "data.export", b"[{\"id\": 1, \"name\": \"a\"}]");
+    let none = write_file(&dir, "data", b"id;name\n1;a\n");
+
+    for path in [tsv, json, none] {
+        let stdout = run(&[path.as_str(), "--output-format", "csv"]);
+        assert_eq!(stdout, "id,name\n1,a\n", "printbl {}", path);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_parquet_and_ipc_from_stdin_like_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut parquet = Vec::<u8>::new();
+    ParquetWriter::new(&mut parquet)
+        .finish(&mut sample_frame())
+        .unwrap();
+    let mut ipc = Vec::<u8>::new();
+    IpcWriter::new(&mut ipc)
+        .finish(&mut sample_frame())
+        .unwrap();
+
+    for (name, bytes) in [("data.parquet", parquet), ("data.arrow", ipc)] {
+        let path = write_file(&dir, name, &bytes);
+        for args in [
+            &["--select", "id,price", "--output-format", "csv"][..],
+            &["-n", "2", "--output-format", "csv"],
+            &["--describe", "--output-format", "csv"],
+            &["--column-names-only"],
+        ] {
+            let from_file = run(&[&[path.as_str()], args].concat());
+            let (from_stdin, _) = run_with_stdin(&[&["-"], args].concat(), bytes.clone());
+            assert_eq!(from_stdin, from_file, "{} {}", name, args.join(" "));
+        }
+    }
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn reads_a_named_pipe() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pipe");
+    let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    // opening the pipe blocks until printbl opens it for reading
+    let writer_path = path.clone();
+    let writer = std::thread::spawn(move || {
+        std::fs::write(writer_path, "id\tname\n1\ta\n2\tb\n").unwrap();
+    });
+    let stdout = run(&[path.to_str().unwrap(), "--output-format", "csv"]);
+    writer.join().unwrap();
+    assert_eq!(stdout, "id,name\n1,a\n2,b\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_the_os_error_for_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.csv");
+    let output = cargo_bin_cmd!("printbl").arg(&path).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unable to open"), "{}", stderr);
+    assert!(stderr.contains("No such file or directory"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_json_from_stdin() {
+    let cases = [
+        (
+            "[{\"id\": 1, \"name\": \"a\"}, {\"id\": 2, \"name\": \"b\"}]",
+            "id,name\n1,a\n2,b\n",
+        ),
+        (
+            "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"name\": \"b\"}\n",
+            "id,name\n1,a\n2,b\n",
+        ),
+        // text whose first field starts with [ is still csv
+        ("\"[id]\",name\n1,a\n", "[id],name\n1,a\n"),
+        ("[id],name\n1,a\n", "[id],name\n1,a\n"),
+        ("[id];name\n1;a\n", "[id],name\n1,a\n"),
+    ];
+    for (stdin, expected) in cases {
+        let (stdout, _) = run_with_stdin(&["-", "--output-format", "csv"], stdin);
+        assert_eq!(stdout, expected, "{:?}", stdin);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_invalid_json_given_by_format() {
+    let output = cargo_bin_cmd!("printbl")
+        .args(["-", "--format", "json"])
+        .write_stdin("[id],name\n1,a\n")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Unable to parse the JSON in stdin"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+// --------------------------------------------------
+#[test]
+fn strips_byte_order_marks() {
+    let dir = tempfile::tempdir().unwrap();
+    let tsv = "id\tname\n1\ta\n";
+    let utf8 = [&b"\xef\xbb\xbf"[..], tsv.as_bytes()].concat();
+    let utf16_le: Vec<u8> = [0xff, 0xfe]
+        .into_iter()
+        .chain(tsv.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let utf16_be: Vec<u8> = [0xfe, 0xff]
+        .into_iter()
+        .chain(tsv.encode_utf16().flat_map(u16::to_be_bytes))
+        .collect();
+
+    for (name, bytes) in [
+        ("utf8.tsv", utf8),
+        ("utf16le.tsv", utf16_le),
+        ("utf16be.tsv", utf16_be),
+    ] {
+        let path = write_file(&dir, name, &bytes);
+        let stdout = run(&[&path, "--select", "id", "--output-format", "csv"]);
+        assert_eq!(stdout, "id\n1\n", "{}", name);
+        let stdout = run(&[&path, "--column-names-only"]);
+        assert_eq!(stdout, "[\n    \"id\",\n    \"name\",\n]\n", "{}", name);
+
+        let (stdout, _) = run_with_stdin(
+            &["-", "--select", "id", "--output-format", "csv"],
+            bytes.clone(),
+        );
+        assert_eq!(stdout, "id\n1\n", "stdin {}", name);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn stops_reading_ndjson_from_stdin_after_n_rows() {
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("printbl"))
+        .args([
+            "-",
+            "--format",
+            "ndjson",
+            "-n",
+            "3",
+            "--output-format",
+            "csv",
+        ])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // write events until printbl closes the pipe
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        use std::io::Write;
+        for i in 0.. {
+            if writeln!(stdin, "{{\"id\": {}, \"name\": \"event\"}}", i).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+    while child.try_wait().unwrap().is_none() {
+        if std::time::Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("printbl kept reading the unbounded stream");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    writer.join().unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "id,name\n0,event\n1,event\n2,event\n"
+    );
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_files_whose_columns_disagree() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = write_file(&dir, "a.csv", b"a,b\n1,2\n");
+    let cases = [
+        ("b.csv", "a,c\n1,2\n", "Column b of "),
+        ("c.csv", "a,b\nx,2\n", "Column a has type str in "),
+    ];
+    for (name, contents, error) in cases {
+        let path = write_file(&dir, name, contents.as_bytes());
+        let output = cargo_bin_cmd!("printbl")
+            .args([&first, &path])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{}", name);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("Error: "), "{}", stderr);
+        assert!(stderr.contains(error), "{}", stderr);
+        assert!(stderr.contains(name), "{}", stderr);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_compressed_files_that_cannot_be_decompressed() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["data.csv.gz", "data.json.gz"] {
+        let path = write_file(&dir, name, b"id,name\n1,a\n");
+        let output = cargo_bin_cmd!("printbl").arg(&path).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{}", name);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with(&format!("Error: Unable to decompress the file {}: ", path)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_sqlite_tables_and_rejects_tables_and_queries_of_other_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let database = dir.path().join("data.sqlite");
+    let connection = rusqlite::Connection::open(&database).unwrap();
+    connection
+        .execute_batch("CREATE TABLE t (id INTEGER, name TEXT); INSERT INTO t VALUES (1, 'a');")
+        .unwrap();
+    drop(connection);
+    let database = database.to_str().unwrap();
+
+    let stdout = run(&[database, "--table", "t", "--output-format", "csv"]);
+    assert_eq!(stdout, "id,name\n1,a\n");
+    let stdout = run(&[
+        database,
+        "--query",
+        "SELECT name FROM t",
+        "--output-format",
+        "csv",
+    ]);
+    assert_eq!(stdout, "name\na\n");
+
+    let csv = write_file(&dir, "data.csv", b"id,name\n1,a\n");
+    for args in [
+        vec![csv.as_str(), "--table", "t"],
+        vec![csv.as_str(), "--query", "SELEC * FROM t"],
+        vec![csv.as_str(), "--format", "sqlite"],
+    ] {
+        let output = cargo_bin_cmd!("printbl").args(&args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "printbl {}", args.join(" "));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("Error: "), "{}", stderr);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_avro_files_that_cannot_be_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_file(&dir, "data.avro", b"not avro");
+    let output = cargo_bin_cmd!("printbl").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!("Error: Unable to parse the Avro file {}: ", path)),
+        "{}",
+        stderr
+    );
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_the_cause_of_corrupt_parquet_and_ipc_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let parquet = write_file(&dir, "bad.parquet", b"PAR1garbagePAR1");
+    let ipc = write_file(&dir, "bad.arrow", b"ARROW1garbage");
+    let cases = [
+        (vec![parquet.as_str()], "Unable to parse the Parquet file"),
+        (vec![ipc.as_str()], "Unable to parse the IPC file"),
+        (
+            vec![ipc.as_str(), "--column-names-only"],
+            "Unable to read the schema of the IPC file",
+        ),
+    ];
+    for (args, error) in cases {
+        let output = cargo_bin_cmd!("printbl").args(&args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "printbl {}", args.join(" "));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        // the error is followed by its cause
+        let prefix = format!("Error: {} {}: ", error, args[0]);
+        assert!(stderr.starts_with(&prefix), "{}", stderr);
+        assert!(stderr.trim_end().len() > prefix.len(), "{}", stderr);
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_spreadsheets_that_cannot_be_opened() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_file(&dir, "data.xlsx", b"id,name\n1,a\n");
+    for args in [vec![path.as_str()], vec![path.as_str(), "--list-sheets"]] {
+        let output = cargo_bin_cmd!("printbl").args(&args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "printbl {}", args.join(" "));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with(&format!("Error: Unable to open the spreadsheet {}: ", path)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_vcf_and_gff_files_that_cannot_be_parsed() {
+    let dir = tempfile::tempdir().unwrap();
+    let vcf = write_file(
+        &dir,
+        "bad.vcf",
+        b"##fileformat=VCFv4.2\n#CHROM\tPOS\n1\t2\t3\n",
+    );
+    let gff = write_file(&dir, "bad.gff", b"chr1\tsource\n");
+    let cases = [
+        (vec![vcf.as_str()], "Unable to parse the VCF file", &vcf),
+        (
+            vec![gff.as_str(), "--select", "nope"],
+            "Unable to select columns from",
+            &gff,
+        ),
+    ];
+    for (args, message, path) in cases {
+        let output = cargo_bin_cmd!("printbl").args(&args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "printbl {}", args.join(" "));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with(&format!("Error: {} {}: ", message, path)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_values_of_sliced_and_split_text_that_do_not_fit_their_column() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_file(&dir, "data.txt", b"id  name\n1   a\nx   b\n");
+    let cases = [
+        vec![path.as_str(), "--fixed-width", "4,4"],
+        vec![path.as_str(), "--whitespace"],
+    ];
+    for args in cases {
+        let output = cargo_bin_cmd!("printbl")
+            .args(&args)
+            .args(["--infer-schema-length", "1"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "printbl {}", args.join(" "));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with(&format!(
+                "Error: Unable to parse the file {}: Could not parse `x`",
+                path
+            )),
+            "{}",
+            stderr
+        );
+    }
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn exits_with_the_status_of_a_failed_exec_command() {
+    let output = cargo_bin_cmd!("printbl")
+        .args(["--exec", "printf 'a\\n1\\n'; exit 3"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Error: `printf 'a\\n1\\n'; exit 3` failed with exit status: 3\n"
+    );
+}
@@ -0,0 +1,226 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use common::{run, run_for_output};
+use polars::prelude::*;
+
+mod common;
+
+const CSV: &str = "id,price,name,ok\n1,1.5,a,true\n2,,b,false\n3,3.25,,true\n4,-2.0,d,\n";
+
+// --------------------------------------------------
+#[test]
+fn round_trips_csv_through_parquet() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = dir.path().join("data.csv");
+    std::fs::write(&csv, CSV).unwrap();
+    let csv = csv.to_str().unwrap();
+
+    for compression in ["zstd", "snappy", "lz4", "uncompressed"] {
+        let parquet = dir.path().join(format!("{}.parquet", compression));
+        let parquet = parquet.to_str().unwrap();
+        run(&[
+            csv,
+            "-o",
+            parquet,
+            "--compression",
+            compression,
+            "--row-group-size",
+            "2",
+        ]);
+
+        let mut reader = ParquetReader::new(std::fs::File::open(parquet).unwrap());
+        let n_row_groups = reader.get_metadata().unwrap().row_groups.len();
+        assert_eq!(n_row_groups, 2, "{}", compression);
+        let df = reader.finish().unwrap();
+        assert_eq!(df.shape(), (4, 4), "{}", compression);
+        assert_eq!(
+            df.dtypes(),
+            [
+                DataType::Int64,
+                DataType::Float64,
+                DataType::Utf8,
+                DataType::Boolean
+            ],
+            "{}",
+            compression
+        );
+        assert_eq!(
+            run(&[parquet, "--output-format", "csv"]),
+            run(&[csv, "--output-format", "csv"]),
+            "{}",
+            compression
+        );
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn suggests_a_longer_schema_inference_for_late_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = dir.path().join("late.csv");
+    let rows: Vec<String> = (1..=30).map(|i| i.to_string()).collect();
+    std::fs::write(&csv, format!("n\n{}\nx\n", rows.join("\n"))).unwrap();
+    let parquet = dir.path().join("late.parquet");
+
+    let output = cargo_bin_cmd!("printbl")
+        .arg(&csv)
+        .args(["--infer-schema-length", "10", "-o"])
+        .arg(&parquet)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--infer-schema-length"), "{}", stderr);
+    assert!(!parquet.exists());
+}
+
+// --------------------------------------------------
+#[test]
+fn round_trips_an_arrow_stream_between_printbl_runs() {
+    // nulls and nested columns, which text formats would not preserve
+    let tags = Series::new(
+        "tags",
+        [
+            Some(Series::new("", ["a", "b"])),
+            None,
+            Some(Series::new_empty("", &DataType::Utf8)),
+        ],
+    );
+    let mut df = df!(
+        "id" => [1i64, 2, 3],
+        "price" => [Some(1.5), None, Some(-2.25)],
+        "name" => [Some("a"), None, Some("c")],
+    )
+    .unwrap();
+    df.with_column(tags).unwrap();
+    let point = df!("x" => [1i32, 2, 3], "y" => [Some("p"), Some("q"), None])
+        .unwrap()
+        .into_struct("point")
+        .into_series();
+    df.with_column(point).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.parquet");
+    ParquetWriter::new(std::fs::File::create(&path).unwrap())
+        .finish(&mut df)
+        .unwrap();
+
+    let stream = run_for_output(
+        &[path.to_str().unwrap(), "--output-format", "arrow-stream"],
+        Vec::new(),
+    )
+    .stdout;
+    let stream = run_for_output(&["-", "--output-format", "arrow-stream"], stream).stdout;
+    let read_back = IpcStreamReader::new(std::io::Cursor::new(stream))
+        .finish()
+        .unwrap();
+
+    assert_eq!(read_back.schema(), df.schema());
+    assert!(read_back.frame_equal_missing(&df), "{}", read_back);
+}
+
+// --------------------------------------------------
+#[test]
+fn displays_nulls_apart_from_the_string_null() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nulls.csv");
+    std::fs::write(&path, "id,name\n1,null\n2,\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    for view in ["--pretty", "--plain", "--markdown", "--vertical"] {
+        let stdout = run(&[path, view, "--null-display", "∅"]);
+        assert_eq!(stdout.matches("null").count(), 1, "{}\n{}", view, stdout);
+        assert_eq!(stdout.matches('∅').count(), 1, "{}\n{}", view, stdout);
+
+        // nulls are displayed as null by default
+        let stdout = run(&[path, view]);
+        assert_eq!(stdout.matches("null").count(), 2, "{}\n{}", view, stdout);
+    }
+
+    // exports keep the nulls as nulls
+    let stdout = run(&[path, "--output-format", "csv"]);
+    assert_eq!(stdout, "id,name\n1,null\n2,\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn notes_the_values_left_out_of_counts_under_printed_views() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("values.csv");
+    std::fs::write(&path, "status\na\nb\nb\nc\nd\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    for view in [
+        "table", "plain", "markdown", "org", "rst", "asciidoc", "typst",
+    ] {
+        let stdout = run(&[
+            path,
+            "--value-counts",
+            "status",
+            "-n",
+            "2",
+            "--output-format",
+            view,
+        ]);
+        assert!(
+            stdout.ends_with("… 2 more distinct values\n"),
+            "{}\n{}",
+            view,
+            stdout
+        );
+    }
+    let stdout = run(&[path, "--value-counts", "status", "-n", "2", "--vertical"]);
+    assert!(stdout.ends_with("… 2 more distinct values\n"), "{}", stdout);
+
+    let stdout = run(&[
+        path,
+        "--value-counts",
+        "status",
+        "-n",
+        "2",
+        "--output-format",
+        "csv",
+    ]);
+    assert!(!stdout.contains("more distinct values"), "{}", stdout);
+}
+
+// --------------------------------------------------
+#[test]
+fn rounds_floats_only_in_printed_views() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("floats.csv");
+    std::fs::write(&path, "x\n0.000000001\n1.23456\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let stdout = run(&[
+        path,
+        "--output-format",
+        "markdown",
+        "--float-precision",
+        "2",
+    ]);
+    assert!(stdout.contains("| 0.00 |"), "{}", stdout);
+    assert!(stdout.contains("| 1.23 |"), "{}", stdout);
+
+    // the exports keep every digit, so seeded data is not lost
+    assert_eq!(
+        run(&[path, "--output-format", "sql"]),
+        "INSERT INTO \"data\" (\"x\") VALUES\n  (1e-9),\n  (1.23456);\n"
+    );
+    let csv = dir.path().join("out.csv");
+    run(&[path, "--float-precision", "2", "-o", csv.to_str().unwrap()]);
+    assert_eq!(std::fs::read_to_string(&csv).unwrap(), "x\n1e-9\n1.23456\n");
+
+    for format in ["csv", "sql"] {
+        let output = cargo_bin_cmd!("printbl")
+            .args([path, "--output-format", format, "--float-precision", "2"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{}", format);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.starts_with("Error: --float-precision requires --output-format"),
+            "{}",
+            stderr
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use common::run_with_stdin;
+use polars::prelude::*;
+
+mod common;
+
+// --------------------------------------------------
+#[test]
+fn reports_removed_rows_alike() {
+    let stdin = "id,name\n1,a\n1,a\n2,\n3,\n";
+    let cases = [
+        (&["--unique"][..], "Removed 1 row of duplicates\n"),
+        (&["--unique-by", "name"], "Removed 2 rows of duplicates\n"),
+        (&["--drop-nulls=name"], "Removed 2 rows with nulls\n"),
+        (&["--only-nulls", "name"], "Removed 2 rows without nulls\n"),
+    ];
+    for (args, report) in cases {
+        let args = [&["-", "--output-format", "csv"], args].concat();
+        let (_, stderr) = run_with_stdin(&args, stdin);
+        assert_eq!(stderr, report, "printbl {}", args.join(" "));
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn prints_the_top_and_bottom_rows() {
+    let stdin = "id,v\n1,3\n2,\n3,1\n4,5\n5,2\n6,5\n7,3\n";
+    let cases = [
+        // ties keep their order, and nulls come last
+        (&["--top", "3", "v"][..], "id,v\n4,5\n6,5\n1,3\n"),
+        (&["--bottom", "3", "v"], "id,v\n3,1\n5,2\n1,3\n"),
+        (
+            &["--top", "10", "v"],
+            "id,v\n4,5\n6,5\n1,3\n7,3\n5,2\n3,1\n2,\n",
+        ),
+        (&["--top", "3", "v", "-n", "2"], "id,v\n4,5\n6,5\n"),
+        (
+            &["--top", "3", "v", "--unique-by", "v"],
+            "id,v\n4,5\n1,3\n5,2\n",
+        ),
+    ];
+    for (args, expected) in cases {
+        let args = [&["-", "--output-format", "csv"], args].concat();
+        let (stdout, _) = run_with_stdin(&args, stdin);
+        assert_eq!(stdout, expected, "printbl {}", args.join(" "));
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn samples_no_more_rows_than_the_table_without_replacement() {
+    let stdin = "i\n1\n2\n3\n4\n5\n";
+    let cases = [
+        (&["--sample", "-n", "50"][..], 5),
+        (&["--sample-frac", "1"], 5),
+        (&["--sample", "-n", "50", "--with-replacement"], 50),
+        (&["--sample-frac", "1", "--with-replacement"], 5),
+    ];
+    for (args, n_rows) in cases {
+        let args = [&["-", "--seed", "1", "--output-format", "csv"], args].concat();
+        let (stdout, _) = run_with_stdin(&args, stdin);
+        assert_eq!(
+            stdout.lines().count(),
+            n_rows + 1,
+            "printbl {}",
+            args.join(" ")
+        );
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn filters_and_derives_with_the_same_expressions() {
+    let stdin = "id,price,name\n1,-2,a\n2,3,b\n3,-1.5,c\n";
+    let cases = [
+        (
+            &["--filter", "price >= -1.5 && name != 'b'"][..],
+            "id,price,name\n3,-1.5,c\n",
+        ),
+        (
+            &["--filter", "id = 2 || `price` < -1.75"],
+            "id,price,name\n1,-2.0,a\n2,3.0,b\n",
+        ),
+        (
+            &["--derive", "total = id - -price * 2", "--select", "total"],
+            "total\n-3.0\n8.0\n0.0\n",
+        ),
+    ];
+    for (args, expected) in cases {
+        let args = [&["-", "--output-format", "csv"], args].concat();
+        let (stdout, _) = run_with_stdin(&args, stdin);
+        assert_eq!(stdout, expected, "printbl {}", args.join(" "));
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn prints_the_top_and_sorted_rows_of_parquet_row_groups_like_those_of_text() {
+    let stdin = "id,v\n1,3\n2,\n3,1\n4,5\n5,2\n6,5\n7,3\n8,\n9,1\n";
+    let df = CsvReader::new(std::io::Cursor::new(stdin))
+        .finish()
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.parquet");
+    ParquetWriter::new(std::fs::File::create(&path).unwrap())
+        .with_row_group_size(Some(2))
+        .finish(&mut df.clone())
+        .unwrap();
+    let path = path.to_str().unwrap();
+
+    for args in [
+        &["--top", "3", "v"][..],
+        &["--bottom", "3", "v"],
+        &["--top", "20", "v"],
+        &["--top", "4", "v", "-n", "2"],
+        &["--bottom", "2", "v", "--select", "v"],
+        &["--top", "3", "v", "--index"],
+        &["--top", "3", "v", "--unique-by", "v"],
+        &["--sort", "v", "-n", "3"],
+        &["--sort", "-v,id", "-n", "4"],
+        &["--sort", "v", "--nulls-first", "-n", "3"],
+        &["--sort", "-v", "--head"],
+    ] {
+        let args = [&["--output-format", "csv"], args].concat();
+        let (from_text, _) = run_with_stdin(&[&["-"], args.as_slice()].concat(), stdin);
+        let (from_parquet, _) = run_with_stdin(&[&[path], args.as_slice()].concat(), "");
+        assert_eq!(from_parquet, from_text, "printbl {}", args.join(" "));
+    }
+}